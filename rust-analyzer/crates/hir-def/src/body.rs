@@ -17,7 +17,7 @@ use la_arena::{Arena, ArenaMap};
 use limit::Limit;
 use profile::Count;
 use rustc_hash::FxHashMap;
-use syntax::{ast, AstPtr, SyntaxNodePtr};
+use syntax::{ast, AstNode, AstPtr, SyntaxNodePtr, TextRange};
 
 use crate::{
     attr::{Attrs, RawAttrs},
@@ -28,8 +28,8 @@ use crate::{
     nameres::DefMap,
     path::{ModPath, Path},
     src::{HasChildSource, HasSource},
-    AsMacroCall, BlockId, DefWithBodyId, HasModule, LocalModuleId, Lookup, MacroId, ModuleId,
-    UnresolvedMacro,
+    AsMacroCall, BlockId, DefWithBodyId, FunctionId, HasModule, LocalModuleId, Lookup, MacroId,
+    ModuleId, UnresolvedMacro,
 };
 
 pub use lower::LowerCtx;
@@ -341,6 +341,32 @@ impl Body {
         db.body_with_source_map(def).0
     }
 
+    /// Lowers `fn_def`, an edited copy of the source of `def` that isn't part of any file, as the
+    /// body of `def`. `original_range` maps the ranges of `fn_def` that the edit leaves alone to
+    /// the file of `def`; blocks and macro calls found there reuse the items and expansions of the
+    /// original source, the ones the edit adds aren't expanded.
+    pub fn speculative_with_source_map(
+        db: &dyn DefDatabase,
+        def: FunctionId,
+        fn_def: &ast::Fn,
+        original_range: &dyn Fn(TextRange) -> Option<TextRange>,
+    ) -> (Body, BodySourceMap) {
+        let _p = profile::span("speculative_with_source_map");
+        let f = def.lookup(db);
+        let src = f.source(db);
+        let expander = Expander::new(db, src.file_id, f.module(db));
+        let speculative = lower::Speculative {
+            file_id: src.file_id,
+            original: src.value.syntax().clone(),
+            original_range,
+        };
+        let body = fn_def.body().map(ast::Expr::from);
+        let (mut body, source_map) =
+            lower::lower(db, expander, fn_def.param_list(), body, Some(speculative));
+        body.shrink_to_fit();
+        (body, source_map)
+    }
+
     /// Returns an iterator over all block expressions in this body that define inner items.
     pub fn blocks<'a>(
         &'a self,
@@ -373,7 +399,7 @@ impl Body {
         params: Option<ast::ParamList>,
         body: Option<ast::Expr>,
     ) -> (Body, BodySourceMap) {
-        lower::lower(db, expander, params, body)
+        lower::lower(db, expander, params, body, None)
     }
 
     fn shrink_to_fit(&mut self) {
@@ -19,7 +19,7 @@ use syntax::{
         self, ArrayExprKind, AstChildren, HasArgList, HasLoopBody, HasName, LiteralKind,
         SlicePatComponents,
     },
-    AstNode, AstPtr, SyntaxNodePtr,
+    AstNode, AstPtr, SyntaxNode, SyntaxNodePtr, TextRange, T,
 };
 
 use crate::{
@@ -34,9 +34,9 @@ use crate::{
     },
     intern::Interned,
     item_scope::BuiltinShadowMode,
-    path::{GenericArgs, Path},
+    path::{GenericArgs, ModPath, Path},
     type_ref::{Mutability, Rawness, TypeRef},
-    AdtId, BlockLoc, ModuleDefId, UnresolvedMacro,
+    AdtId, BlockId, BlockLoc, ModuleDefId, UnresolvedMacro,
 };
 
 pub struct LowerCtx<'a> {
@@ -73,11 +73,50 @@ impl<'a> LowerCtx<'a> {
     }
 }
 
+/// The source a body is lowered from when it is an edited copy of the source in the file, see
+/// [`Body::speculative_with_source_map`].
+pub(super) struct Speculative<'a> {
+    /// The file the copy stands in for.
+    pub(super) file_id: HirFileId,
+    /// The original source of the body.
+    pub(super) original: SyntaxNode,
+    /// Maps the ranges of the copy that the edit leaves alone to the file.
+    pub(super) original_range: &'a dyn Fn(TextRange) -> Option<TextRange>,
+}
+
+impl Speculative<'_> {
+    fn original_node(&self, node: &SyntaxNode) -> Option<SyntaxNode> {
+        let range = (self.original_range)(node.text_range())?;
+        let root = self.original.ancestors().last()?;
+        root.covering_element(range)
+            .ancestors()
+            .take_while(|it| it.text_range() == range)
+            .find(|it| it.kind() == node.kind())
+    }
+
+    /// The block of the file that `block` keeps the braces of, the statements may be edited.
+    fn original_block(&self, block: &ast::BlockExpr) -> Option<ast::BlockExpr> {
+        let l_curly = block.stmt_list()?.l_curly_token()?;
+        let range = (self.original_range)(l_curly.text_range())?;
+        let root = self.original.ancestors().last()?;
+        let l_curly =
+            root.covering_element(range).into_token().filter(|it| it.kind() == T!['{'])?;
+        l_curly.parent()?.parent().and_then(ast::BlockExpr::cast)
+    }
+
+    /// The macro call of the file that `call` is a copy of, at the same place. Calls in the code
+    /// the edit adds or moves have none, even when the file has a call with the same text.
+    fn original_macro_call(&self, call: &ast::MacroCall) -> Option<ast::MacroCall> {
+        self.original_node(call.syntax()).and_then(ast::MacroCall::cast)
+    }
+}
+
 pub(super) fn lower(
     db: &dyn DefDatabase,
     expander: Expander,
     params: Option<ast::ParamList>,
     body: Option<ast::Expr>,
+    speculative: Option<Speculative<'_>>,
 ) -> (Body, BodySourceMap) {
     ExprCollector {
         db,
@@ -94,6 +133,8 @@ pub(super) fn lower(
             or_pats: Default::default(),
         },
         expander,
+        speculative,
+        current_block: None,
         name_to_pat_grouping: Default::default(),
         is_lowering_inside_or_pat: false,
         is_lowering_assignee_expr: false,
@@ -107,6 +148,8 @@ struct ExprCollector<'a> {
     ast_id_map: Arc<AstIdMap>,
     body: Body,
     source_map: BodySourceMap,
+    speculative: Option<Speculative<'a>>,
+    current_block: Option<BlockId>,
     // a poor-mans union-find?
     name_to_pat_grouping: FxHashMap<Name, Vec<PatId>>,
     is_lowering_inside_or_pat: bool,
@@ -147,7 +190,17 @@ impl ExprCollector<'_> {
     }
 
     fn ctx(&self) -> LowerCtx<'_> {
-        LowerCtx::new(self.db, self.expander.current_file_id)
+        match self.speculative_copy() {
+            // The nodes of an edited copy aren't in the file, they have no ids
+            Some(_) => LowerCtx::with_hygiene(self.db, &self.expander.cfg_expander.hygiene),
+            None => LowerCtx::new(self.db, self.expander.current_file_id),
+        }
+    }
+
+    /// The original source when lowering the nodes of an edited copy of it, which excludes the
+    /// macro expansions.
+    fn speculative_copy(&self) -> Option<&Speculative<'_>> {
+        self.speculative.as_ref().filter(|it| it.file_id == self.expander.current_file_id)
     }
 
     fn alloc_expr(&mut self, expr: Expr, ptr: ExprPtr) -> ExprId {
@@ -569,6 +622,30 @@ impl ExprCollector<'_> {
         let outer_file = self.expander.current_file_id;
 
         let macro_call_ptr = self.expander.to_source(AstPtr::new(&mcall));
+        let mcall = match self.speculative_copy() {
+            Some(speculative) => match speculative.original_macro_call(&mcall) {
+                Some(original) => original,
+                // Macro calls added by the edit can't be expanded, they only need to resolve
+                None => {
+                    let hygiene = &self.expander.cfg_expander.hygiene;
+                    let path = mcall
+                        .path()
+                        .and_then(|path| ModPath::from_src(self.db.upcast(), path, hygiene));
+                    if let Some(path) = path {
+                        if record_diagnostics
+                            && self.expander.resolve_path_as_macro(self.db, &path).is_none()
+                        {
+                            self.source_map.diagnostics.push(BodyDiagnostic::UnresolvedMacroCall {
+                                node: InFile::new(outer_file, syntax_ptr),
+                                path,
+                            });
+                        }
+                    }
+                    return collector(self, None);
+                }
+            },
+            None => mcall,
+        };
         let res = self.expander.enter_expand(self.db, mcall);
 
         let res = match res {
@@ -699,21 +776,30 @@ impl ExprCollector<'_> {
     }
 
     fn collect_block(&mut self, block: ast::BlockExpr) -> ExprId {
-        let file_local_id = self.ast_id_map.ast_id(&block);
-        let ast_id = AstId::new(self.expander.current_file_id, file_local_id);
-        let block_loc =
-            BlockLoc { ast_id, module: self.expander.def_map.module_id(self.expander.module) };
-        let block_id = self.db.intern_block(block_loc);
+        let block_id = match self.speculative_copy() {
+            Some(speculative) => match speculative.original_block(&block) {
+                Some(original) => self.intern_block(&original),
+                // Blocks added by the edit have no items, they share the ones of the enclosing block
+                None => match self.current_block {
+                    Some(block_id) => block_id,
+                    None => return self.missing_expr(),
+                },
+            },
+            None => self.intern_block(&block),
+        };
 
         let (module, def_map) = match self.db.block_def_map(block_id) {
             Some(def_map) => {
-                self.body.block_scopes.push(block_id);
+                if !self.body.block_scopes.contains(&block_id) {
+                    self.body.block_scopes.push(block_id);
+                }
                 (def_map.root(), def_map)
             }
             None => (self.expander.module, self.expander.def_map.clone()),
         };
         let prev_def_map = mem::replace(&mut self.expander.def_map, def_map);
         let prev_local_module = mem::replace(&mut self.expander.module, module);
+        let prev_block = mem::replace(&mut self.current_block, Some(block_id));
 
         let mut statements = Vec::new();
         block.statements().for_each(|s| self.collect_stmt(&mut statements, s));
@@ -743,9 +829,18 @@ impl ExprCollector<'_> {
 
         self.expander.def_map = prev_def_map;
         self.expander.module = prev_local_module;
+        self.current_block = prev_block;
         expr_id
     }
 
+    fn intern_block(&self, block: &ast::BlockExpr) -> BlockId {
+        let file_local_id = self.ast_id_map.ast_id(block);
+        let ast_id = AstId::new(self.expander.current_file_id, file_local_id);
+        let block_loc =
+            BlockLoc { ast_id, module: self.expander.def_map.module_id(self.expander.module) };
+        self.db.intern_block(block_loc)
+    }
+
     fn collect_block_opt(&mut self, expr: Option<ast::BlockExpr>) -> ExprId {
         match expr {
             Some(block) => self.collect_block(block),
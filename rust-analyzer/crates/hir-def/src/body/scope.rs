@@ -85,7 +85,7 @@ impl ExprScopes {
 }
 
 impl ExprScopes {
-    fn new(body: &Body) -> ExprScopes {
+    pub fn new(body: &Body) -> ExprScopes {
         let mut scopes =
             ExprScopes { scopes: Arena::default(), scope_by_expr: FxHashMap::default() };
         let mut root = scopes.root_scope();
@@ -3,6 +3,8 @@ mod block;
 use base_db::{fixture::WithFixture, SourceDatabase};
 use expect_test::Expect;
 
+use syntax::TextSize;
+
 use crate::ModuleDefId;
 
 use super::*;
@@ -25,6 +27,58 @@ fn lower(ra_fixture: &str) -> Arc<Body> {
     db.body(fn_def.unwrap().into())
 }
 
+/// Lowers the function of the fixture and, as a speculative body of it, a copy of the function
+/// where `from` is replaced with `to`. Returns the expansions of the macro calls of both.
+fn speculative_macro_files(
+    ra_fixture: &str,
+    from: &str,
+    to: &str,
+) -> (Vec<Option<HirFileId>>, Vec<Option<HirFileId>>) {
+    let db = crate::test_db::TestDB::with_files(ra_fixture);
+
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+    let fn_def = def_map[def_map.root()]
+        .scope
+        .declarations()
+        .find_map(|decl| match decl {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let src = fn_def.lookup(&db).source(&db);
+
+    let text = src.value.syntax().ancestors().last().unwrap().to_string();
+    let edit_start = TextSize::from(text.find(from).unwrap() as u32);
+    let delete = TextRange::at(edit_start, TextSize::of(from));
+    let insert = TextRange::at(edit_start, TextSize::of(to));
+    let edited = text.replacen(from, to, 1);
+    let copy =
+        ast::SourceFile::parse(&edited).tree().syntax().descendants().find_map(ast::Fn::cast);
+    let original_range = |range: TextRange| {
+        if range.end() <= insert.start() {
+            Some(range)
+        } else if range.start() >= insert.end() {
+            Some(range - insert.len() + delete.len())
+        } else {
+            None
+        }
+    };
+
+    let macro_files = |source_map: &BodySourceMap, fn_def: &ast::Fn| {
+        fn_def
+            .syntax()
+            .descendants()
+            .filter_map(ast::MacroCall::cast)
+            .map(|call| source_map.node_macro_file(InFile::new(src.file_id, &call)))
+            .collect()
+    };
+    let (_, source_map) = db.body_with_source_map(fn_def.into());
+    let (_, speculative_source_map) =
+        Body::speculative_with_source_map(&db, fn_def, &copy.clone().unwrap(), &original_range);
+    (macro_files(&source_map, &src.value), macro_files(&speculative_source_map, &copy.unwrap()))
+}
+
 fn block_def_map_at(ra_fixture: &str) -> String {
     let (db, position) = crate::test_db::TestDB::with_position(ra_fixture);
 
@@ -125,3 +179,44 @@ mod m {
 "#,
     );
 }
+
+#[test]
+fn speculative_body_keeps_expansions_of_repeated_macro_calls() {
+    let (original, speculative) = speculative_macro_files(
+        r#"
+macro_rules! m {
+    ($e:expr) => { $e + 1 };
+}
+fn main() {
+    let a = m!(1);
+    let b = m!(1);
+    let c = 2;
+}
+"#,
+        "let c = 2;",
+        "let c = 3;",
+    );
+    assert_eq!(original.len(), 2);
+    assert!(original.iter().all(Option::is_some));
+    assert_ne!(original[0], original[1]);
+    assert_eq!(speculative, original);
+}
+
+#[test]
+fn speculative_body_does_not_expand_edited_macro_calls() {
+    let (original, speculative) = speculative_macro_files(
+        r#"
+macro_rules! m {
+    ($e:expr) => { $e + 1 };
+}
+fn main() {
+    let a = m!(1);
+    let b = m!(1);
+}
+"#,
+        "let b = m!(1);",
+        "let b = m!(1) * 2;",
+    );
+    // The call of `b` is in the code of the edit, the identical call of `a` isn't its original
+    assert_eq!(speculative, vec![original[0], None]);
+}
@@ -576,9 +576,21 @@ pub fn resolver_for_scope(
     db: &dyn DefDatabase,
     owner: DefWithBodyId,
     scope_id: Option<ScopeId>,
+) -> Resolver {
+    resolver_for_scope_in(db, owner, db.expr_scopes(owner), scope_id)
+}
+
+/// Like [`resolver_for_scope`], but for the scopes of a body that isn't the one of `owner` in the
+/// database, see [`Body::speculative_with_source_map`].
+///
+/// [`Body::speculative_with_source_map`]: crate::body::Body::speculative_with_source_map
+pub fn resolver_for_scope_in(
+    db: &dyn DefDatabase,
+    owner: DefWithBodyId,
+    scopes: Arc<ExprScopes>,
+    scope_id: Option<ScopeId>,
 ) -> Resolver {
     let mut r = owner.resolver(db);
-    let scopes = db.expr_scopes(owner);
     let scope_chain = scopes.scope_chain(scope_id).collect::<Vec<_>>();
     r.scopes.reserve(scope_chain.len());
 
@@ -0,0 +1,340 @@
+//! Points-to analysis for raw pointers.
+//!
+//! Within one body, a raw pointer is traced back to the allocation it points into: a local owning
+//! its elements (an array, a `Vec`, a `Box`), or the target of a reference the body received.
+//! Pointers are followed through `as_ptr`/`as_mut_ptr`, casts of borrows, indexing, pointer
+//! arithmetic and the definitions of the locals holding them, keeping track of the element offset
+//! when it is a constant.
+
+use std::sync::Arc;
+
+use hir_def::{
+    body::Body,
+    expr::{BinaryOp, Expr, ExprId, Literal, Pat, PatId, Statement, UnaryOp},
+    resolver::{resolver_for_expr, ValueNs},
+    DefWithBodyId,
+};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    dataflow::{BodyDataflow, ProgramPoint},
+    db::HirDatabase,
+    InferenceResult, Interner, Mutability, TyExt, TyKind,
+};
+
+/// How long a chain of pointer derivations we follow.
+const MAX_DEPTH: usize = 16;
+
+/// What we know about the allocation behind a root local.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RootKind {
+    /// The local owns the allocation: an array, a `Vec`, a `Box`...
+    Owned,
+    /// The local is a `&mut` reference of unknown origin, nothing else in the body aliases it.
+    Unique,
+    /// The local is a shared reference or a raw pointer of unknown origin.
+    Shared,
+    /// The type of the local isn't known.
+    Unknown,
+}
+
+/// Where a pointer points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointsTo {
+    /// The binding of the local the allocation is reached from.
+    pub root: PatId,
+    pub kind: RootKind,
+    /// The offset of the pointer in the allocation, in elements, when it is a constant.
+    pub offset: Option<i128>,
+}
+
+/// How the ranges of `len` elements starting at two pointers relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aliasing {
+    /// The pointers point into different allocations.
+    DifferentAllocations,
+    /// The pointers point into the same allocation at these offsets, and the ranges don't overlap.
+    DisjointRanges { root: PatId, offsets: (i128, i128) },
+    /// The pointers point into the same allocation and the ranges overlap.
+    OverlappingRanges { root: PatId },
+    /// The pointers point into the same allocation, the overlap depends on runtime values.
+    SameAllocation { root: PatId },
+    /// Where one of the pointers points isn't known.
+    Unknown,
+}
+
+pub struct AliasAnalysis {
+    owner: DefWithBodyId,
+    body: Arc<Body>,
+    infer: Arc<InferenceResult>,
+    dataflow: BodyDataflow,
+    /// The value each binding of a `let` and each assigned path gets.
+    assigned_values: FxHashMap<ProgramPoint, ExprId>,
+    /// The locals the paths of the body refer to.
+    locals: FxHashMap<ExprId, PatId>,
+}
+
+impl AliasAnalysis {
+    pub fn new(db: &dyn HirDatabase, owner: DefWithBodyId) -> AliasAnalysis {
+        let body = db.body(owner);
+        let infer = db.infer(owner);
+        let dataflow = BodyDataflow::new(db, owner);
+
+        let mut assigned_values = FxHashMap::default();
+        let mut locals = FxHashMap::default();
+        for (expr, data) in body.exprs.iter() {
+            match data {
+                Expr::Block { statements, .. } => {
+                    for statement in statements.iter() {
+                        if let Statement::Let { pat, initializer: Some(initializer), .. } =
+                            *statement
+                        {
+                            if let Pat::Bind { subpat: None, .. } = body[pat] {
+                                assigned_values.insert(ProgramPoint::Pat(pat), initializer);
+                            }
+                        }
+                    }
+                }
+                &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { op: None }) } => {
+                    if let Expr::Path(_) = body[lhs] {
+                        assigned_values.insert(ProgramPoint::Expr(lhs), rhs);
+                    }
+                }
+                Expr::Path(path) => {
+                    let resolver = resolver_for_expr(db.upcast(), owner, expr);
+                    if let Some(ValueNs::LocalBinding(pat)) =
+                        resolver.resolve_path_in_value_ns_fully(db.upcast(), path.mod_path())
+                    {
+                        locals.insert(expr, body.pattern_representative(pat));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        AliasAnalysis { owner, body, infer, dataflow, assigned_values, locals }
+    }
+
+    pub fn owner(&self) -> DefWithBodyId {
+        self.owner
+    }
+
+    /// Where the pointer or reference `expr` evaluates to points.
+    pub fn points_to(&self, expr: ExprId) -> Option<PointsTo> {
+        self.pointer(expr, 0)
+    }
+
+    /// How the ranges of `len` elements starting at the pointers `a` and `b` relate.
+    pub fn range_aliasing(&self, a: ExprId, b: ExprId, len: Option<ExprId>) -> Aliasing {
+        let (a, b) = match (self.points_to(a), self.points_to(b)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Aliasing::Unknown,
+        };
+        if a.root != b.root {
+            return match (a.kind, b.kind) {
+                (RootKind::Unknown, _) | (_, RootKind::Unknown) => Aliasing::Unknown,
+                // Two shared references may point to the same memory
+                (RootKind::Shared, RootKind::Shared) => Aliasing::Unknown,
+                _ => Aliasing::DifferentAllocations,
+            };
+        }
+        let root = a.root;
+        match (a.offset, b.offset, len.and_then(|len| self.constant(len, 0))) {
+            (Some(a), Some(b), Some(len)) if len > 0 && (a - b).abs() < len => {
+                Aliasing::OverlappingRanges { root }
+            }
+            (Some(a), Some(b), Some(_)) => Aliasing::DisjointRanges { root, offsets: (a, b) },
+            _ => Aliasing::SameAllocation { root },
+        }
+    }
+
+    /// The value of a local at `expr`, when a single definition with a known value reaches it.
+    fn local_value(&self, expr: ExprId) -> Option<ExprId> {
+        let local = *self.locals.get(&expr)?;
+        let definitions = self.dataflow.reaching_definitions(ProgramPoint::Expr(expr), local);
+        match definitions.as_slice() {
+            [definition] => self.assigned_values.get(&definition.point).copied(),
+            _ => None,
+        }
+    }
+
+    fn constant(&self, expr: ExprId, depth: usize) -> Option<i128> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match &self.body[expr] {
+            Expr::Literal(Literal::Int(value, _)) => Some(*value),
+            Expr::Literal(Literal::Uint(value, _)) => i128::try_from(*value).ok(),
+            Expr::Path(_) => self.constant(self.local_value(expr)?, depth + 1),
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::ArithOp(op)) } => {
+                let (lhs, rhs) = (self.constant(lhs, depth + 1)?, self.constant(rhs, depth + 1)?);
+                match op {
+                    hir_def::expr::ArithOp::Add => lhs.checked_add(rhs),
+                    hir_def::expr::ArithOp::Sub => lhs.checked_sub(rhs),
+                    hir_def::expr::ArithOp::Mul => lhs.checked_mul(rhs),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn root(&self, expr: ExprId) -> Option<PointsTo> {
+        let root = *self.locals.get(&expr)?;
+        let kind = match self.infer[expr].kind(Interner) {
+            TyKind::Ref(Mutability::Mut, ..) => RootKind::Unique,
+            TyKind::Ref(Mutability::Not, ..) | TyKind::Raw(..) => RootKind::Shared,
+            TyKind::Error | TyKind::InferenceVar(..) => RootKind::Unknown,
+            _ => RootKind::Owned,
+        };
+        Some(PointsTo { root, kind, offset: Some(0) })
+    }
+
+    /// Where the pointer or reference `expr` evaluates to points.
+    fn pointer(&self, expr: ExprId, depth: usize) -> Option<PointsTo> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match &self.body[expr] {
+            Expr::Path(_) => match self.local_value(expr) {
+                Some(value) => self.pointer(value, depth + 1),
+                None => self.root(expr),
+            },
+            &Expr::Ref { expr: place, .. } => self.place(place, depth + 1),
+            &Expr::Cast { expr: inner, .. } => self.pointer(inner, depth + 1),
+            Expr::MethodCall { receiver, method_name, args, .. } => {
+                let name = method_name.to_string();
+                match (name.as_str(), &**args) {
+                    ("as_ptr" | "as_mut_ptr", []) => self.place(*receiver, depth + 1),
+                    ("cast" | "cast_mut" | "cast_const", []) => self.pointer(*receiver, depth + 1),
+                    ("add" | "offset" | "wrapping_add" | "wrapping_offset", [count]) => {
+                        let pointer = self.pointer(*receiver, depth + 1)?;
+                        let count = self.constant(*count, depth + 1);
+                        Some(PointsTo {
+                            offset: pointer.offset.zip(count).map(|(a, b)| a + b),
+                            ..pointer
+                        })
+                    }
+                    ("sub" | "wrapping_sub", [count]) => {
+                        let pointer = self.pointer(*receiver, depth + 1)?;
+                        let count = self.constant(*count, depth + 1);
+                        Some(PointsTo {
+                            offset: pointer.offset.zip(count).map(|(a, b)| a - b),
+                            ..pointer
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            &Expr::Call { callee, ref args, .. } => match (&self.body[callee], &**args) {
+                // `Box::into_raw(b)` and `Box::leak(b)` point into the box
+                (Expr::Path(path), [arg])
+                    if matches!(
+                        path.mod_path().segments().last().map(|it| it.to_smol_str()).as_deref(),
+                        Some("into_raw" | "leak")
+                    ) =>
+                {
+                    self.place(*arg, depth + 1)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Where the elements of the place `expr` are.
+    fn place(&self, expr: ExprId, depth: usize) -> Option<PointsTo> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match &self.body[expr] {
+            Expr::Path(_) => {
+                let root = self.root(expr)?;
+                match root.kind {
+                    // Going through a reference, to wherever it points
+                    RootKind::Unique | RootKind::Shared | RootKind::Unknown => {
+                        match self.local_value(expr) {
+                            Some(value) => self.pointer(value, depth + 1).or(Some(root)),
+                            None => Some(root),
+                        }
+                    }
+                    RootKind::Owned => Some(root),
+                }
+            }
+            &Expr::Index { base, index } => {
+                let base = self.place(base, depth + 1)?;
+                let start = match &self.body[index] {
+                    Expr::Range { lhs: None, .. } => Some(0),
+                    &Expr::Range { lhs: Some(lhs), .. } => self.constant(lhs, depth + 1),
+                    _ => self.constant(index, depth + 1),
+                };
+                Some(PointsTo { offset: base.offset.zip(start).map(|(a, b)| a + b), ..base })
+            }
+            &Expr::UnaryOp { expr: inner, op: UnaryOp::Deref } => self.pointer(inner, depth + 1),
+            &Expr::Ref { expr: place, .. } => self.place(place, depth + 1),
+            Expr::MethodCall { receiver, method_name, args, .. }
+                if args.is_empty()
+                    && matches!(
+                        method_name.to_string().as_str(),
+                        "as_slice" | "as_mut_slice" | "as_ref" | "as_mut"
+                    ) =>
+            {
+                self.place(*receiver, depth + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The calls of `copy_nonoverlapping` in the body whose source and destination provably overlap.
+pub fn overlapping_copies(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<ExprId> {
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let calls: Vec<(ExprId, &[ExprId])> = body
+        .exprs
+        .iter()
+        .filter_map(|(expr, data)| match data {
+            Expr::Call { callee, args, .. }
+                if args.len() == 3 && calls_copy_nonoverlapping(db, &body, &infer, *callee) =>
+            {
+                Some((expr, &**args))
+            }
+            _ => None,
+        })
+        .collect();
+    if calls.is_empty() {
+        return Vec::new();
+    }
+
+    let analysis = AliasAnalysis::new(db, owner);
+    calls
+        .into_iter()
+        .filter(|(_, args)| {
+            matches!(
+                analysis.range_aliasing(args[0], args[1], Some(args[2])),
+                Aliasing::OverlappingRanges { .. }
+            )
+        })
+        .map(|(expr, _)| expr)
+        .collect()
+}
+
+fn calls_copy_nonoverlapping(
+    db: &dyn HirDatabase,
+    body: &Body,
+    infer: &InferenceResult,
+    callee: ExprId,
+) -> bool {
+    // Without `core` the callee doesn't resolve, go by the name of the path then
+    let name = match infer[callee].as_fn_def(db) {
+        Some(func) => db.function_data(func).name.to_smol_str(),
+        None => match &body[callee] {
+            Expr::Path(path) => match path.mod_path().segments().last() {
+                Some(name) => name.to_smol_str(),
+                None => return false,
+            },
+            _ => return false,
+        },
+    };
+    name == "copy_nonoverlapping"
+}
@@ -12,7 +12,8 @@ use chalk_solve::rust_ir::{self, OpaqueTyDatumBound, WellKnownTrait};
 use base_db::CrateId;
 use hir_def::{
     lang_item::{lang_attr, LangItemTarget},
-    AssocItemId, GenericDefId, HasModule, ItemContainerId, Lookup, ModuleId, TypeAliasId,
+    AssocItemId, EnumVariantId, GenericDefId, HasModule, ItemContainerId, Lookup, ModuleId,
+    TypeAliasId, VariantId,
 };
 use hir_expand::name::name;
 
@@ -161,7 +162,25 @@ impl<'a> chalk_solve::RustIrDatabase<Interner> for ChalkContext<'a> {
     }
     fn impl_provided_for(&self, auto_trait_id: TraitId, kind: &chalk_ir::TyKind<Interner>) -> bool {
         debug!("impl_provided_for {:?}, {:?}", auto_trait_id, kind);
-        false // FIXME
+        // Only nominal types and raw pointers have impls of their own, the fingerprint of other
+        // types like references is the one of a component.
+        let fp = match kind {
+            TyKind::Adt(..) | TyKind::Raw(..) | TyKind::Foreign(..) => {
+                TyFingerprint::for_trait_impl(&kind.clone().intern(Interner))
+            }
+            _ => None,
+        };
+        let fp = match fp {
+            Some(it) => it,
+            None => return false,
+        };
+        // A positive or negative impl replaces the impl chalk would derive from the components.
+        let trait_: hir_def::TraitId = from_chalk_trait_id(auto_trait_id);
+        let in_deps = self.db.trait_impls_in_deps(self.krate);
+        let in_self = self.db.trait_impls_in_crate(self.krate);
+        [in_deps, in_self]
+            .iter()
+            .any(|impls| impls.for_trait_and_self_ty(trait_, fp).next().is_some())
     }
     fn associated_ty_value(&self, id: AssociatedTyValueId) -> Arc<AssociatedTyValue> {
         self.db.associated_ty_value(self.krate, id)
@@ -555,18 +574,30 @@ pub(crate) fn struct_datum_query(
     };
     let flags = rust_ir::AdtFlags {
         upstream,
-        // FIXME set fundamental and phantom_data flags correctly
+        // FIXME set fundamental flag correctly
         fundamental: false,
-        phantom_data: false,
+        phantom_data: db.attrs(adt_id.into()).lang().map_or(false, |it| it == "phantom_data"),
+    };
+    // The field types are bound by the ADT's generics, like the where clauses.
+    let variant_datum = |variant: VariantId| rust_ir::AdtVariantDatum {
+        fields: db.field_types(variant).iter().map(|(_, ty)| ty.skip_binders().clone()).collect(),
     };
-    // FIXME provide enum variants properly (for auto traits)
-    let variant = rust_ir::AdtVariantDatum {
-        fields: Vec::new(), // FIXME add fields (only relevant for auto traits),
+    let (kind, variants) = match adt_id {
+        hir_def::AdtId::StructId(it) => (rust_ir::AdtKind::Struct, vec![variant_datum(it.into())]),
+        hir_def::AdtId::UnionId(it) => (rust_ir::AdtKind::Union, vec![variant_datum(it.into())]),
+        hir_def::AdtId::EnumId(it) => {
+            let variants = db
+                .enum_data(it)
+                .variants
+                .iter()
+                .map(|(local_id, _)| variant_datum(EnumVariantId { parent: it, local_id }.into()))
+                .collect();
+            (rust_ir::AdtKind::Enum, variants)
+        }
     };
-    let struct_datum_bound = rust_ir::AdtDatumBound { variants: vec![variant], where_clauses };
+    let struct_datum_bound = rust_ir::AdtDatumBound { variants, where_clauses };
     let struct_datum = StructDatum {
-        // FIXME set ADT kind
-        kind: rust_ir::AdtKind::Struct,
+        kind,
         id: struct_id,
         binders: make_binders(db, &generic_params, struct_datum_bound),
         flags,
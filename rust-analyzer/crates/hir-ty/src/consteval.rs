@@ -7,6 +7,7 @@ use std::{
 
 use chalk_ir::{BoundVar, DebruijnIndex, GenericArgData, IntTy, Scalar};
 use hir_def::{
+    body::Body,
     builtin_type::BuiltinInt,
     expr::{ArithOp, BinaryOp, Expr, ExprId, Literal, Pat, PatId},
     path::ModPath,
@@ -509,6 +510,27 @@ pub(crate) fn const_eval_query_variant(
     )
 }
 
+/// Evaluates `expr` of the body of `owner`, without values for the locals of the body.
+pub(crate) fn eval_body_expr(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    expr: ExprId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    eval_const(
+        expr,
+        &mut ConstEvalCtx {
+            db,
+            owner,
+            exprs: &body.exprs,
+            pats: &body.pats,
+            local_data: HashMap::default(),
+            infer,
+        },
+    )
+}
+
 pub(crate) fn eval_to_const<'a>(
     expr: Idx<Expr>,
     mode: ParamLoweringMode,
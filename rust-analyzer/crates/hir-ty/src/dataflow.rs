@@ -0,0 +1,1014 @@
+//! Dataflow analysis over the body of a definition.
+//!
+//! The body is lowered to a control flow graph whose program points are the expressions, in
+//! evaluation order, and the bindings of patterns. On top of it we compute the dominators, the
+//! definitions of each local that reach a program point, def-use chains, and whether a local is
+//! mutated or moved on the way between two program points.
+//!
+//! The analysis is an approximation meant for IDE features: closures are treated as using their
+//! captures where they are created, and a local is considered moved when its type is not `Copy`
+//! and it is used by value.
+
+use std::sync::Arc;
+
+use hir_def::{
+    body::Body,
+    expr::{BinaryOp, Expr, ExprId, Pat, PatId, Statement, UnaryOp},
+    resolver::{resolver_for_expr, ValueNs},
+    DefWithBodyId, HasModule, TraitId,
+};
+use hir_expand::name::Name;
+use la_arena::{Arena, Idx};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::SmolStr;
+
+use crate::{
+    db::HirDatabase, infer::OverloadedDeref, method_resolution::implements_trait,
+    replace_errors_with_variables, Adjust, AutoBorrow, BindingMode, InferenceResult, Interner,
+    Mutability, TraitEnvironment, Ty, TyKind,
+};
+
+pub type BasicBlockId = Idx<BasicBlock>;
+
+/// A point of the body at which locals are accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgramPoint {
+    /// The evaluation of an expression, after its operands.
+    Expr(ExprId),
+    /// The binding of a pattern, after the value it matches.
+    Pat(PatId),
+}
+
+/// A sequence of program points that are executed one after the other.
+#[derive(Debug, Default)]
+pub struct BasicBlock {
+    pub points: Vec<ProgramPoint>,
+    pub successors: Vec<BasicBlockId>,
+    pub predecessors: Vec<BasicBlockId>,
+}
+
+/// The control flow graph of a body. The bodies of closures and async blocks are not part of it.
+#[derive(Debug)]
+pub struct ControlFlowGraph {
+    pub blocks: Arena<BasicBlock>,
+    pub entry: BasicBlockId,
+    pub exit: BasicBlockId,
+    locations: FxHashMap<ProgramPoint, (BasicBlockId, usize)>,
+}
+
+impl ControlFlowGraph {
+    pub fn new(body: &Body) -> ControlFlowGraph {
+        let mut blocks = Arena::default();
+        let entry = blocks.alloc(BasicBlock::default());
+        let exit = blocks.alloc(BasicBlock::default());
+        let mut builder =
+            CfgBuilder { body, blocks, current: Some(entry), exit, scopes: Vec::new() };
+
+        for &param in &body.params {
+            builder.bind(param);
+        }
+        builder.lower_expr(body.body_expr);
+        builder.jump(exit);
+
+        let mut locations = FxHashMap::default();
+        for (id, block) in builder.blocks.iter() {
+            for (idx, &point) in block.points.iter().enumerate() {
+                locations.insert(point, (id, idx));
+            }
+        }
+        ControlFlowGraph { blocks: builder.blocks, entry, exit, locations }
+    }
+
+    /// The block of a program point and its index in the block.
+    pub fn location(&self, point: ProgramPoint) -> Option<(BasicBlockId, usize)> {
+        self.locations.get(&point).copied()
+    }
+
+    /// A key ordering program points by their block, then by their position in the block.
+    fn order(&self, point: ProgramPoint) -> Option<(u32, usize)> {
+        self.location(point).map(|(block, idx)| (u32::from(block.into_raw()), idx))
+    }
+
+    /// The blocks reachable from the entry, in reverse postorder.
+    pub fn reverse_postorder(&self) -> Vec<BasicBlockId> {
+        let mut visited = FxHashSet::default();
+        let mut postorder = Vec::new();
+        let mut stack = vec![(self.entry, 0)];
+        visited.insert(self.entry);
+        while let Some((block, next)) = stack.pop() {
+            match self.blocks[block].successors.get(next) {
+                Some(&succ) => {
+                    stack.push((block, next + 1));
+                    if visited.insert(succ) {
+                        stack.push((succ, 0));
+                    }
+                }
+                None => postorder.push(block),
+            }
+        }
+        postorder.reverse();
+        postorder
+    }
+
+    /// Computes the dominator tree of the blocks reachable from the entry.
+    pub fn dominators(&self) -> Dominators {
+        // "A Simple, Fast Dominance Algorithm" by Cooper, Harvey and Kennedy
+        let rpo = self.reverse_postorder();
+        let order: FxHashMap<BasicBlockId, usize> =
+            rpo.iter().enumerate().map(|(idx, &block)| (block, idx)).collect();
+        let mut idom: FxHashMap<BasicBlockId, BasicBlockId> = FxHashMap::default();
+        idom.insert(self.entry, self.entry);
+
+        let intersect = |idom: &FxHashMap<BasicBlockId, BasicBlockId>, mut a, mut b| {
+            while a != b {
+                while order[&a] > order[&b] {
+                    a = idom[&a];
+                }
+                while order[&b] > order[&a] {
+                    b = idom[&b];
+                }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in rpo.iter().skip(1) {
+                let mut preds = self.blocks[block]
+                    .predecessors
+                    .iter()
+                    .copied()
+                    .filter(|pred| idom.contains_key(pred));
+                let first = match preds.next() {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let new_idom = preds.fold(first, |acc, pred| intersect(&idom, acc, pred));
+                if idom.get(&block) != Some(&new_idom) {
+                    idom.insert(block, new_idom);
+                    changed = true;
+                }
+            }
+        }
+
+        Dominators { entry: self.entry, idom }
+    }
+}
+
+/// The dominator tree of a control flow graph.
+#[derive(Debug)]
+pub struct Dominators {
+    entry: BasicBlockId,
+    idom: FxHashMap<BasicBlockId, BasicBlockId>,
+}
+
+impl Dominators {
+    /// The immediate dominator of a block, `None` for the entry and for unreachable blocks.
+    pub fn immediate_dominator(&self, block: BasicBlockId) -> Option<BasicBlockId> {
+        if block == self.entry {
+            return None;
+        }
+        self.idom.get(&block).copied()
+    }
+
+    /// Whether every path from the entry to `b` goes through `a`.
+    pub fn dominates(&self, a: BasicBlockId, mut b: BasicBlockId) -> bool {
+        if !self.idom.contains_key(&b) {
+            return false;
+        }
+        loop {
+            if a == b {
+                return true;
+            }
+            match self.immediate_dominator(b) {
+                Some(idom) => b = idom,
+                None => return false,
+            }
+        }
+    }
+}
+
+struct BreakScope {
+    label: Option<Name>,
+    is_loop: bool,
+    break_target: BasicBlockId,
+    continue_target: BasicBlockId,
+}
+
+struct CfgBuilder<'a> {
+    body: &'a Body,
+    blocks: Arena<BasicBlock>,
+    /// The block being filled, `None` after a diverging expression.
+    current: Option<BasicBlockId>,
+    exit: BasicBlockId,
+    scopes: Vec<BreakScope>,
+}
+
+impl CfgBuilder<'_> {
+    fn new_block(&mut self) -> BasicBlockId {
+        self.blocks.alloc(BasicBlock::default())
+    }
+
+    fn edge(&mut self, from: BasicBlockId, to: BasicBlockId) {
+        if !self.blocks[from].successors.contains(&to) {
+            self.blocks[from].successors.push(to);
+            self.blocks[to].predecessors.push(from);
+        }
+    }
+
+    /// Adds an edge from the current block to `to`, if the current block is reachable.
+    fn jump(&mut self, to: BasicBlockId) {
+        if let Some(current) = self.current {
+            self.edge(current, to);
+        }
+    }
+
+    /// Starts a new block branching off the current one.
+    fn fork(&mut self) -> BasicBlockId {
+        let block = self.new_block();
+        self.jump(block);
+        block
+    }
+
+    /// Continues at a join block, which is unreachable when nothing jumps to it.
+    fn resume(&mut self, block: BasicBlockId) {
+        self.current = (!self.blocks[block].predecessors.is_empty()).then(|| block);
+    }
+
+    fn push(&mut self, point: ProgramPoint) {
+        if let Some(current) = self.current {
+            self.blocks[current].points.push(point);
+        }
+    }
+
+    fn bind(&mut self, pat: PatId) {
+        if let Pat::Bind { .. } = self.body[pat] {
+            self.push(ProgramPoint::Pat(pat));
+        }
+        self.body[pat].walk_child_pats(|child| self.bind(child));
+    }
+
+    fn scope(&self, label: &Option<Name>, for_continue: bool) -> Option<&BreakScope> {
+        self.scopes
+            .iter()
+            .rev()
+            .find(|scope| match label {
+                Some(label) => scope.label.as_ref() == Some(label),
+                None => scope.is_loop,
+            })
+            .filter(|scope| !for_continue || scope.is_loop)
+    }
+
+    fn lower_loop(
+        &mut self,
+        label: Option<Name>,
+        header: BasicBlockId,
+        exit: BasicBlockId,
+        body: ExprId,
+    ) {
+        self.scopes.push(BreakScope {
+            label,
+            is_loop: true,
+            break_target: exit,
+            continue_target: header,
+        });
+        self.lower_expr(body);
+        self.jump(header);
+        self.scopes.pop();
+    }
+
+    fn lower_expr(&mut self, expr: ExprId) {
+        let body = self.body;
+        match &body[expr] {
+            &Expr::If { condition, then_branch, else_branch } => {
+                self.lower_expr(condition);
+                let condition_end = self.current;
+                let join = self.new_block();
+
+                let then_block = self.fork();
+                self.current = Some(then_block);
+                self.lower_expr(then_branch);
+                self.jump(join);
+
+                self.current = condition_end;
+                if let Some(else_branch) = else_branch {
+                    let else_block = self.fork();
+                    self.current = Some(else_block);
+                    self.lower_expr(else_branch);
+                }
+                self.jump(join);
+                self.resume(join);
+            }
+            &Expr::Let { pat, expr: scrutinee } => {
+                self.lower_expr(scrutinee);
+                self.bind(pat);
+            }
+            Expr::Block { statements, tail, label, .. } => {
+                // Only a labeled block can be left early
+                let join = label.map(|label| {
+                    let join = self.new_block();
+                    self.scopes.push(BreakScope {
+                        label: Some(body.labels[label].name.clone()),
+                        is_loop: false,
+                        break_target: join,
+                        continue_target: join,
+                    });
+                    join
+                });
+                for statement in statements.iter() {
+                    match *statement {
+                        Statement::Let { pat, initializer, else_branch, .. } => {
+                            if let Some(initializer) = initializer {
+                                self.lower_expr(initializer);
+                            }
+                            if let Some(else_branch) = else_branch {
+                                // The `else` block diverges, it doesn't flow back
+                                let matched = self.current;
+                                let else_block = self.fork();
+                                self.current = Some(else_block);
+                                self.lower_expr(else_branch);
+                                self.current = matched;
+                            }
+                            self.bind(pat);
+                        }
+                        Statement::Expr { expr, .. } => self.lower_expr(expr),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.lower_expr(*tail);
+                }
+                if let Some(join) = join {
+                    self.scopes.pop();
+                    self.jump(join);
+                    self.resume(join);
+                }
+            }
+            &Expr::Loop { body: loop_body, label } => {
+                let header = self.new_block();
+                let exit = self.new_block();
+                self.jump(header);
+                self.current = Some(header);
+                self.lower_loop(
+                    label.map(|label| body.labels[label].name.clone()),
+                    header,
+                    exit,
+                    loop_body,
+                );
+                self.resume(exit);
+            }
+            &Expr::While { condition, body: loop_body, label } => {
+                let header = self.new_block();
+                let exit = self.new_block();
+                self.jump(header);
+                self.current = Some(header);
+                self.lower_expr(condition);
+                self.jump(exit);
+                let body_block = self.fork();
+                self.current = Some(body_block);
+                self.lower_loop(
+                    label.map(|label| body.labels[label].name.clone()),
+                    header,
+                    exit,
+                    loop_body,
+                );
+                self.resume(exit);
+            }
+            &Expr::For { iterable, pat, body: loop_body, label } => {
+                self.lower_expr(iterable);
+                let header = self.new_block();
+                let exit = self.new_block();
+                self.jump(header);
+                self.current = Some(header);
+                self.jump(exit);
+                let body_block = self.fork();
+                self.current = Some(body_block);
+                self.bind(pat);
+                self.lower_loop(
+                    label.map(|label| body.labels[label].name.clone()),
+                    header,
+                    exit,
+                    loop_body,
+                );
+                self.resume(exit);
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                self.lower_expr(*scrutinee);
+                let start = self.current;
+                let join = self.new_block();
+                for arm in arms.iter() {
+                    // A failed guard falls through to the next arms, which `start` reaches too
+                    self.current = start;
+                    let arm_block = self.fork();
+                    self.current = Some(arm_block);
+                    self.bind(arm.pat);
+                    if let Some(guard) = arm.guard {
+                        self.lower_expr(guard);
+                    }
+                    self.lower_expr(arm.expr);
+                    self.jump(join);
+                }
+                self.resume(join);
+            }
+            Expr::Break { expr: value, label } => {
+                if let Some(value) = value {
+                    self.lower_expr(*value);
+                }
+                self.push(ProgramPoint::Expr(expr));
+                if let Some(target) = self.scope(label, false).map(|scope| scope.break_target) {
+                    self.jump(target);
+                }
+                self.current = None;
+                return;
+            }
+            Expr::Continue { label } => {
+                self.push(ProgramPoint::Expr(expr));
+                if let Some(target) = self.scope(label, true).map(|scope| scope.continue_target) {
+                    self.jump(target);
+                }
+                self.current = None;
+                return;
+            }
+            Expr::Return { expr: value } => {
+                if let Some(value) = value {
+                    self.lower_expr(*value);
+                }
+                self.push(ProgramPoint::Expr(expr));
+                self.jump(self.exit);
+                self.current = None;
+                return;
+            }
+            &Expr::Try { expr: inner } => {
+                self.lower_expr(inner);
+                self.push(ProgramPoint::Expr(expr));
+                // The early return of the residual
+                self.jump(self.exit);
+                return;
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::LogicOp(_)) } => {
+                self.lower_expr(lhs);
+                let join = self.new_block();
+                self.jump(join);
+                let rhs_block = self.fork();
+                self.current = Some(rhs_block);
+                self.lower_expr(rhs);
+                self.jump(join);
+                self.resume(join);
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { .. }) } => {
+                self.lower_expr(rhs);
+                self.lower_assignee(lhs);
+            }
+            Expr::Closure { .. } | Expr::Async { .. } | Expr::Const { .. } => {}
+            it => {
+                let mut children = Vec::new();
+                it.walk_child_exprs(|child| children.push(child));
+                for child in children {
+                    self.lower_expr(child);
+                }
+            }
+        }
+        self.push(ProgramPoint::Expr(expr));
+    }
+
+    /// Lowers the left hand side of an assignment. The places it names are defined after the
+    /// right hand side is evaluated.
+    fn lower_assignee(&mut self, expr: ExprId) {
+        match &self.body[expr] {
+            Expr::Path(_) | Expr::Underscore => self.push(ProgramPoint::Expr(expr)),
+            Expr::Tuple { exprs, is_assignee_expr: true }
+            | Expr::Call { args: exprs, is_assignee_expr: true, .. } => {
+                for &child in exprs.iter() {
+                    self.lower_assignee(child);
+                }
+                self.push(ProgramPoint::Expr(expr));
+            }
+            Expr::RecordLit { fields, is_assignee_expr: true, .. } => {
+                for field in fields.iter() {
+                    self.lower_assignee(field.expr);
+                }
+                self.push(ProgramPoint::Expr(expr));
+            }
+            Expr::Array(hir_def::expr::Array::ElementList { elements, is_assignee_expr: true }) => {
+                for &child in elements.iter() {
+                    self.lower_assignee(child);
+                }
+                self.push(ProgramPoint::Expr(expr));
+            }
+            _ => self.lower_expr(expr),
+        }
+    }
+}
+
+/// How a program point accesses a local.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessKind {
+    /// The local gets a new value, from its binding or an assignment.
+    Define,
+    /// The value of the local is read or borrowed.
+    Read,
+    /// The local is changed in place, through a `&mut` borrow or an assignment to a part of it.
+    Mutate,
+    /// The value of the local is moved out.
+    Move,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Access {
+    /// The binding of the local, the first one for bindings of or-patterns.
+    pub local: PatId,
+    pub kind: AccessKind,
+}
+
+/// A definition of a local: the program point at which it gets a new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Definition {
+    pub local: PatId,
+    pub point: ProgramPoint,
+}
+
+/// How an expression is used by its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Place {
+    Value,
+    Borrow,
+    MutBorrow,
+    Assignee,
+}
+
+struct AccessCollector<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    env: Arc<TraitEnvironment>,
+    copy_trait: Option<TraitId>,
+    accesses: FxHashMap<ProgramPoint, Vec<Access>>,
+    /// The closure all accesses are attributed to, while walking its body.
+    closure: Option<ExprId>,
+}
+
+impl AccessCollector<'_> {
+    fn record(&mut self, expr: ExprId, local: PatId, kind: AccessKind) {
+        let point = ProgramPoint::Expr(self.closure.unwrap_or(expr));
+        let access = Access { local, kind };
+        let accesses = self.accesses.entry(point).or_default();
+        if !accesses.contains(&access) {
+            accesses.push(access);
+        }
+    }
+
+    fn local(&self, expr: ExprId) -> Option<PatId> {
+        let path = match &self.body[expr] {
+            Expr::Path(path) => path,
+            _ => return None,
+        };
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+        match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())? {
+            ValueNs::LocalBinding(pat) => Some(self.body.pattern_representative(pat)),
+            _ => None,
+        }
+    }
+
+    fn is_copy(&self, ty: &Ty) -> bool {
+        match ty.kind(Interner) {
+            TyKind::Scalar(_)
+            | TyKind::Ref(Mutability::Not, ..)
+            | TyKind::Raw(..)
+            | TyKind::FnDef(..)
+            | TyKind::Function(_)
+            | TyKind::Never => true,
+            TyKind::Ref(Mutability::Mut, ..) | TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => {
+                false
+            }
+            _ => match self.copy_trait {
+                Some(copy_trait) => implements_trait(
+                    &replace_errors_with_variables(ty),
+                    self.db,
+                    self.env.clone(),
+                    copy_trait,
+                ),
+                None => false,
+            },
+        }
+    }
+
+    /// How an expression is used once the adjustments of method calls and coercions are applied.
+    fn adjusted_place(&self, expr: ExprId, place: Place) -> Place {
+        let adjustments = match self.infer.expr_adjustments.get(&expr) {
+            Some(it) => it,
+            None => return place,
+        };
+        if let Some(Adjust::Deref(None)) = adjustments.first().map(|it| &it.kind) {
+            if let TyKind::Ref(..) | TyKind::Raw(..) = self.infer[expr].kind(Interner) {
+                // Only the reference or the pointer is read, to get to its target
+                return Place::Borrow;
+            }
+        }
+        adjustments
+            .iter()
+            .find_map(|adjustment| match adjustment.kind {
+                Adjust::Borrow(AutoBorrow::Ref(mutability))
+                | Adjust::Borrow(AutoBorrow::RawPtr(mutability))
+                | Adjust::Deref(Some(OverloadedDeref(mutability))) => match mutability {
+                    Mutability::Mut => Some(Place::MutBorrow),
+                    Mutability::Not => Some(Place::Borrow),
+                },
+                _ => None,
+            })
+            .unwrap_or(place)
+    }
+
+    /// How the value matched by `pat` is used: moved when a binding takes it by value.
+    fn scrutinee_place(&self, pat: PatId) -> Place {
+        if self.pat_moves(pat) {
+            Place::Value
+        } else {
+            Place::Borrow
+        }
+    }
+
+    fn pat_moves(&self, pat: PatId) -> bool {
+        if let Pat::Bind { .. } = self.body[pat] {
+            let by_value =
+                matches!(self.infer.pat_binding_modes.get(&pat), None | Some(BindingMode::Move));
+            if by_value && !self.is_copy(&self.infer[pat]) {
+                return true;
+            }
+        }
+        let mut moves = false;
+        self.body[pat].walk_child_pats(|child| moves |= self.pat_moves(child));
+        moves
+    }
+
+    fn walk(&mut self, expr: ExprId, place: Place) {
+        let place = self.adjusted_place(expr, place);
+        let body = self.body;
+        match &body[expr] {
+            Expr::Path(_) => {
+                if let Some(local) = self.local(expr) {
+                    match place {
+                        Place::Value => {
+                            self.record(expr, local, AccessKind::Read);
+                            if !self.is_copy(&self.infer[expr]) {
+                                self.record(expr, local, AccessKind::Move);
+                            }
+                        }
+                        Place::Borrow => self.record(expr, local, AccessKind::Read),
+                        Place::MutBorrow => {
+                            self.record(expr, local, AccessKind::Read);
+                            self.record(expr, local, AccessKind::Mutate);
+                        }
+                        Place::Assignee => self.record(expr, local, AccessKind::Define),
+                    }
+                }
+            }
+            &Expr::Ref { expr: inner, mutability, .. } => {
+                let place = match mutability {
+                    hir_def::type_ref::Mutability::Mut => Place::MutBorrow,
+                    hir_def::type_ref::Mutability::Shared => Place::Borrow,
+                };
+                self.walk(inner, place);
+            }
+            &Expr::Field { expr: inner, .. } => {
+                let place = match place {
+                    Place::Value if self.is_copy(&self.infer[expr]) => Place::Borrow,
+                    Place::Assignee => Place::MutBorrow,
+                    place => place,
+                };
+                self.walk(inner, place);
+            }
+            &Expr::Index { base, index } => {
+                let place = match place {
+                    Place::Value | Place::Borrow => Place::Borrow,
+                    Place::MutBorrow | Place::Assignee => Place::MutBorrow,
+                };
+                self.walk(base, place);
+                self.walk(index, Place::Value);
+            }
+            &Expr::UnaryOp { expr: inner, op: UnaryOp::Deref } => {
+                // Going through a reference or a pointer doesn't change the local holding it
+                let place = match self.infer[inner].kind(Interner) {
+                    TyKind::Ref(..) | TyKind::Raw(..) => Place::Borrow,
+                    _ => match place {
+                        Place::Value | Place::Borrow => Place::Borrow,
+                        Place::MutBorrow | Place::Assignee => Place::MutBorrow,
+                    },
+                };
+                self.walk(inner, place);
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { op }) } => {
+                self.walk(rhs, Place::Value);
+                match (op, self.local(lhs)) {
+                    (Some(_), Some(local)) => {
+                        self.record(lhs, local, AccessKind::Read);
+                        self.record(lhs, local, AccessKind::Define);
+                    }
+                    (Some(_), None) => self.walk(lhs, Place::MutBorrow),
+                    (None, _) => self.walk_assignee(lhs),
+                }
+            }
+            &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::CmpOp(_)) } => {
+                // Comparisons take their operands by reference
+                self.walk(lhs, Place::Borrow);
+                self.walk(rhs, Place::Borrow);
+            }
+            &Expr::Let { pat, expr: scrutinee } => {
+                let place = self.scrutinee_place(pat);
+                self.walk(scrutinee, place);
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                let place = arms
+                    .iter()
+                    .map(|arm| self.scrutinee_place(arm.pat))
+                    .find(|place| *place == Place::Value)
+                    .unwrap_or(Place::Borrow);
+                self.walk(*scrutinee, place);
+                for arm in arms.iter() {
+                    if let Some(guard) = arm.guard {
+                        self.walk(guard, Place::Value);
+                    }
+                    self.walk(arm.expr, Place::Value);
+                }
+            }
+            Expr::Block { statements, tail, .. } => {
+                for statement in statements.iter() {
+                    match *statement {
+                        Statement::Let { pat, initializer, else_branch, .. } => {
+                            if let Some(initializer) = initializer {
+                                let place = self.scrutinee_place(pat);
+                                self.walk(initializer, place);
+                            }
+                            if let Some(else_branch) = else_branch {
+                                self.walk(else_branch, Place::Value);
+                            }
+                        }
+                        Statement::Expr { expr, .. } => self.walk(expr, Place::Value),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.walk(*tail, Place::Value);
+                }
+            }
+            &Expr::Closure { body: closure_body, .. } | &Expr::Async { body: closure_body } => {
+                let outer = self.closure.replace(self.closure.unwrap_or(expr));
+                self.walk(closure_body, Place::Value);
+                self.closure = outer;
+            }
+            Expr::Const { .. } => {}
+            it => {
+                let mut children = Vec::new();
+                it.walk_child_exprs(|child| children.push(child));
+                for child in children {
+                    self.walk(child, Place::Value);
+                }
+            }
+        }
+    }
+
+    fn walk_assignee(&mut self, expr: ExprId) {
+        match &self.body[expr] {
+            Expr::Path(_) => self.walk(expr, Place::Assignee),
+            Expr::Underscore => {}
+            Expr::Tuple { exprs, is_assignee_expr: true }
+            | Expr::Call { args: exprs, is_assignee_expr: true, .. } => {
+                for &child in exprs.iter() {
+                    self.walk_assignee(child);
+                }
+            }
+            Expr::RecordLit { fields, is_assignee_expr: true, .. } => {
+                for field in fields.iter() {
+                    self.walk_assignee(field.expr);
+                }
+            }
+            Expr::Array(hir_def::expr::Array::ElementList { elements, is_assignee_expr: true }) => {
+                for &child in elements.iter() {
+                    self.walk_assignee(child);
+                }
+            }
+            _ => self.walk(expr, Place::Assignee),
+        }
+    }
+}
+
+/// The dataflow facts of a body.
+#[derive(Debug)]
+pub struct BodyDataflow {
+    pub cfg: ControlFlowGraph,
+    pub dominators: Dominators,
+    accesses: FxHashMap<ProgramPoint, Vec<Access>>,
+    /// The definitions reaching the start of each block.
+    reaching: FxHashMap<BasicBlockId, FxHashSet<Definition>>,
+    uses: FxHashMap<Definition, Vec<ProgramPoint>>,
+    definitions: FxHashMap<(ProgramPoint, PatId), Vec<Definition>>,
+}
+
+impl BodyDataflow {
+    pub fn new(db: &dyn HirDatabase, owner: DefWithBodyId) -> BodyDataflow {
+        let body = db.body(owner);
+        let infer = db.infer(owner);
+        let krate = owner.module(db.upcast()).krate();
+        let env = owner
+            .as_generic_def_id()
+            .map_or_else(|| Arc::new(TraitEnvironment::empty(krate)), |d| db.trait_environment(d));
+        let copy_trait =
+            db.lang_item(krate, SmolStr::new_inline("copy")).and_then(|it| it.as_trait());
+
+        let mut collector = AccessCollector {
+            db,
+            owner,
+            body: &body,
+            infer: &infer,
+            env,
+            copy_trait,
+            accesses: FxHashMap::default(),
+            closure: None,
+        };
+        for (pat, _) in body.pats.iter() {
+            if let Pat::Bind { .. } = body[pat] {
+                let local = body.pattern_representative(pat);
+                collector
+                    .accesses
+                    .entry(ProgramPoint::Pat(pat))
+                    .or_default()
+                    .push(Access { local, kind: AccessKind::Define });
+            }
+        }
+        collector.walk(body.body_expr, Place::Value);
+        let accesses = collector.accesses;
+
+        let cfg = ControlFlowGraph::new(&body);
+        let dominators = cfg.dominators();
+        let mut dataflow = BodyDataflow {
+            cfg,
+            dominators,
+            accesses,
+            reaching: FxHashMap::default(),
+            uses: FxHashMap::default(),
+            definitions: FxHashMap::default(),
+        };
+        dataflow.compute_reaching_definitions();
+        dataflow.compute_chains();
+        dataflow
+    }
+
+    /// The accesses to locals made at a program point.
+    pub fn accesses(&self, point: ProgramPoint) -> &[Access] {
+        self.accesses.get(&point).map_or(&[], |it| it.as_slice())
+    }
+
+    /// Whether every path from the entry to `b` goes through `a` first.
+    pub fn dominates(&self, a: ProgramPoint, b: ProgramPoint) -> bool {
+        match (self.cfg.location(a), self.cfg.location(b)) {
+            (Some((a_block, a_idx)), Some((b_block, b_idx))) if a_block == b_block => {
+                a_idx <= b_idx
+            }
+            (Some((a_block, _)), Some((b_block, _))) => self.dominators.dominates(a_block, b_block),
+            _ => false,
+        }
+    }
+
+    /// The definitions of `local` that reach `point`, before its own accesses.
+    pub fn reaching_definitions(&self, point: ProgramPoint, local: PatId) -> Vec<Definition> {
+        let (block, idx) = match self.cfg.location(point) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        let mut reaching = self.reaching.get(&block).cloned().unwrap_or_default();
+        for &point in &self.cfg.blocks[block].points[..idx] {
+            self.transfer(point, &mut reaching);
+        }
+        let mut defs: Vec<Definition> =
+            reaching.into_iter().filter(|def| def.local == local).collect();
+        defs.sort_by_key(|def| self.cfg.order(def.point));
+        defs
+    }
+
+    /// The definitions a use of `local` at `point` may see, following the use-def chain.
+    pub fn definitions(&self, point: ProgramPoint, local: PatId) -> &[Definition] {
+        self.definitions.get(&(point, local)).map_or(&[], |it| it.as_slice())
+    }
+
+    /// The program points that may see the value of `def`, following the def-use chain.
+    pub fn uses(&self, def: Definition) -> &[ProgramPoint] {
+        self.uses.get(&def).map_or(&[], |it| it.as_slice())
+    }
+
+    /// Whether `local` may be assigned, mutated or moved after `from` and before `to`, on a path
+    /// going from one to the other. The accesses made at `from` and `to` themselves don't count.
+    pub fn is_mutated_or_moved_between(
+        &self,
+        local: PatId,
+        from: ProgramPoint,
+        to: ProgramPoint,
+    ) -> bool {
+        let (from, to) = match (self.cfg.location(from), self.cfg.location(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return false,
+        };
+        let after_from =
+            self.points_between(from, |block| &self.cfg.blocks[block].successors, true);
+        let before_to =
+            self.points_between(to, |block| &self.cfg.blocks[block].predecessors, false);
+        after_from.intersection(&before_to).any(|point| {
+            self.accesses(*point).iter().any(|access| {
+                access.local == local
+                    && matches!(
+                        access.kind,
+                        AccessKind::Define | AccessKind::Mutate | AccessKind::Move
+                    )
+            })
+        })
+    }
+
+    /// The program points reachable from a location, walking the graph forward or backward.
+    fn points_between<'a>(
+        &'a self,
+        (start_block, start_idx): (BasicBlockId, usize),
+        next: impl Fn(BasicBlockId) -> &'a Vec<BasicBlockId>,
+        forward: bool,
+    ) -> FxHashSet<ProgramPoint> {
+        let points = &self.cfg.blocks[start_block].points;
+        let mut result: FxHashSet<ProgramPoint> = if forward {
+            points[start_idx + 1..].iter().copied().collect()
+        } else {
+            points[..start_idx].iter().copied().collect()
+        };
+        let mut visited = FxHashSet::default();
+        let mut stack = next(start_block).clone();
+        while let Some(block) = stack.pop() {
+            if !visited.insert(block) {
+                continue;
+            }
+            // Coming back to the start block through a loop reaches all of it
+            result.extend(self.cfg.blocks[block].points.iter().copied());
+            stack.extend(next(block).iter().copied());
+        }
+        result
+    }
+
+    fn transfer(&self, point: ProgramPoint, reaching: &mut FxHashSet<Definition>) {
+        for access in self.accesses(point) {
+            if access.kind == AccessKind::Define {
+                reaching.retain(|def| def.local != access.local);
+                reaching.insert(Definition { local: access.local, point });
+            }
+        }
+    }
+
+    fn compute_reaching_definitions(&mut self) {
+        let rpo = self.cfg.reverse_postorder();
+        let mut exits: FxHashMap<BasicBlockId, FxHashSet<Definition>> = FxHashMap::default();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in &rpo {
+                let mut reaching: FxHashSet<Definition> = FxHashSet::default();
+                for pred in &self.cfg.blocks[block].predecessors {
+                    if let Some(defs) = exits.get(pred) {
+                        reaching.extend(defs.iter().copied());
+                    }
+                }
+                self.reaching.insert(block, reaching.clone());
+                for &point in &self.cfg.blocks[block].points {
+                    self.transfer(point, &mut reaching);
+                }
+                if exits.get(&block) != Some(&reaching) {
+                    exits.insert(block, reaching);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    fn compute_chains(&mut self) {
+        let mut uses: FxHashMap<Definition, Vec<ProgramPoint>> = FxHashMap::default();
+        let mut definitions = FxHashMap::default();
+        for (block, data) in self.cfg.blocks.iter() {
+            let mut reaching = self.reaching.get(&block).cloned().unwrap_or_default();
+            for &point in &data.points {
+                for access in self.accesses(point) {
+                    if access.kind == AccessKind::Define {
+                        continue;
+                    }
+                    let mut defs: Vec<Definition> =
+                        reaching.iter().copied().filter(|def| def.local == access.local).collect();
+                    defs.sort_by_key(|def| self.cfg.order(def.point));
+                    for &def in &defs {
+                        let uses = uses.entry(def).or_default();
+                        if !uses.contains(&point) {
+                            uses.push(point);
+                        }
+                    }
+                    definitions.insert((point, access.local), defs);
+                }
+                self.transfer(point, &mut reaching);
+            }
+        }
+        for uses in uses.values_mut() {
+            uses.sort_by_key(|point| self.cfg.order(*point));
+        }
+        self.uses = uses;
+        self.definitions = definitions;
+    }
+}
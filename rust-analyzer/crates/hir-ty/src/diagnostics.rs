@@ -3,11 +3,15 @@ mod expr;
 mod match_check;
 mod unsafe_check;
 mod decl_check;
+mod unchecked_call;
 
 pub use crate::diagnostics::{
     decl_check::{incorrect_case, IncorrectCase},
     expr::{
         record_literal_missing_fields, record_pattern_missing_fields, BodyValidationDiagnostic,
     },
-    unsafe_check::{missing_unsafe, unsafe_expressions, UnsafeExpr},
+    unchecked_call::{unchecked_call_value, unchecked_constant_calls, UncheckedValue},
+    unsafe_check::{
+        missing_unsafe, missing_unsafe_in_body, unsafe_expressions, UnsafeExpr, UnsafeExprKind,
+    },
 };
@@ -47,8 +47,18 @@ pub enum BodyValidationDiagnostic {
 impl BodyValidationDiagnostic {
     pub fn collect(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<BodyValidationDiagnostic> {
         let _p = profile::span("BodyValidationDiagnostic::collect");
-        let infer = db.infer(owner);
-        let mut validator = ExprValidator::new(owner, infer);
+        Self::collect_in_body(db, owner, db.body(owner), db.infer(owner))
+    }
+
+    /// Like [`BodyValidationDiagnostic::collect`], but for a body of `owner` that isn't the one
+    /// in the database.
+    pub fn collect_in_body(
+        db: &dyn HirDatabase,
+        owner: DefWithBodyId,
+        body: Arc<Body>,
+        infer: Arc<InferenceResult>,
+    ) -> Vec<BodyValidationDiagnostic> {
+        let mut validator = ExprValidator::new(owner, body, infer);
         validator.validate_body(db);
         validator.diagnostics
     }
@@ -56,17 +66,18 @@ impl BodyValidationDiagnostic {
 
 struct ExprValidator {
     owner: DefWithBodyId,
+    body: Arc<Body>,
     infer: Arc<InferenceResult>,
     pub(super) diagnostics: Vec<BodyValidationDiagnostic>,
 }
 
 impl ExprValidator {
-    fn new(owner: DefWithBodyId, infer: Arc<InferenceResult>) -> ExprValidator {
-        ExprValidator { owner, infer, diagnostics: Vec::new() }
+    fn new(owner: DefWithBodyId, body: Arc<Body>, infer: Arc<InferenceResult>) -> ExprValidator {
+        ExprValidator { owner, body, infer, diagnostics: Vec::new() }
     }
 
     fn validate_body(&mut self, db: &dyn HirDatabase) {
-        let body = db.body(self.owner);
+        let body = self.body.clone();
         let mut filter_map_next_checker = None;
 
         for (id, expr) in body.exprs.iter() {
@@ -151,7 +162,7 @@ impl ExprValidator {
         db: &dyn HirDatabase,
         infer: Arc<InferenceResult>,
     ) {
-        let body = db.body(self.owner);
+        let body = self.body.clone();
 
         let match_expr_ty = &infer[match_expr];
         if match_expr_ty.is_unknown() {
@@ -0,0 +1,144 @@
+//! Checks the preconditions of unchecked constructors, like `char::from_u32_unchecked`, whose
+//! argument is known at analysis time.
+
+use base_db::{CrateOrigin, LangCrateOrigin};
+use hir_def::{
+    body::Body,
+    expr::{Expr, ExprId, Literal},
+    DefWithBodyId, HasModule, Lookup,
+};
+
+use crate::{
+    consteval::{eval_body_expr, ComputedExpr},
+    db::HirDatabase,
+    InferenceResult, TyExt,
+};
+
+/// What a call of `char::from_u32_unchecked`, `str::from_utf8_unchecked`,
+/// `String::from_utf8_unchecked` or `CString::from_vec_unchecked` with a constant argument
+/// constructs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UncheckedValue {
+    Char(char),
+    Str(Box<str>),
+    /// The bytes of a C string, without the nul terminator.
+    CStr(Box<[u8]>),
+    /// The argument breaks the precondition of the constructor, for this reason.
+    Invalid(String),
+}
+
+/// The calls of unchecked constructors in the body whose argument is a constant, with what they
+/// construct.
+pub fn unchecked_constant_calls(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+) -> Vec<(ExprId, UncheckedValue)> {
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    body.exprs
+        .iter()
+        .filter_map(|(call, _)| Some((call, unchecked_value(db, owner, &body, &infer, call)?)))
+        .collect()
+}
+
+/// What the call `call` of an unchecked constructor constructs, if its argument is a constant.
+pub fn unchecked_call_value(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    call: ExprId,
+) -> Option<UncheckedValue> {
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    unchecked_value(db, owner, &body, &infer, call)
+}
+
+fn unchecked_value(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    call: ExprId,
+) -> Option<UncheckedValue> {
+    let (callee, arg) = match &body[call] {
+        Expr::Call { callee, args, .. } if args.len() == 1 => (*callee, args[0]),
+        _ => return None,
+    };
+    let constant = |expr| match eval_body_expr(db, owner, body, infer, expr) {
+        Ok(ComputedExpr::Literal(literal)) => Some(literal),
+        _ => None,
+    };
+    // The byte string behind `b"..."`, `&BYTES` or `b"...".to_vec()`
+    let bytes = |mut expr| loop {
+        match &body[expr] {
+            Expr::Ref { expr: inner, .. } => expr = *inner,
+            Expr::MethodCall { receiver, method_name, args, .. }
+                if args.is_empty()
+                    && matches!(&*method_name.to_smol_str(), "to_vec" | "to_owned" | "into") =>
+            {
+                expr = *receiver
+            }
+            _ => match constant(expr)? {
+                Literal::ByteString(bytes) => return Some(bytes),
+                _ => return None,
+            },
+        }
+    };
+
+    let value = match &*std_callee_name(db, infer, callee)? {
+        "from_u32_unchecked" => {
+            let value = match constant(arg)? {
+                Literal::Int(value, _) => u32::try_from(value).ok()?,
+                Literal::Uint(value, _) => u32::try_from(value).ok()?,
+                _ => return None,
+            };
+            match char::from_u32(value) {
+                Some(c) => UncheckedValue::Char(c),
+                None if (0xD800..0xE000).contains(&value) => UncheckedValue::Invalid(format!(
+                    "{:#X} is a surrogate code point, not a `char`",
+                    value
+                )),
+                None => UncheckedValue::Invalid(format!(
+                    "{:#X} is past the last `char`, {:#X}",
+                    value,
+                    char::MAX as u32
+                )),
+            }
+        }
+        "from_utf8_unchecked" => {
+            let bytes = bytes(arg)?;
+            match std::str::from_utf8(&bytes) {
+                Ok(text) => UncheckedValue::Str(text.into()),
+                Err(error) => {
+                    UncheckedValue::Invalid(format!("the bytes are not UTF-8: {}", error))
+                }
+            }
+        }
+        "from_vec_unchecked" => {
+            let bytes = bytes(arg)?;
+            match bytes.iter().position(|&byte| byte == 0) {
+                Some(index) => {
+                    UncheckedValue::Invalid(format!("the bytes have a nul at index {}", index))
+                }
+                None => UncheckedValue::CStr(bytes),
+            }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// The name of the function `callee` calls, if it's one of the standard library.
+fn std_callee_name(
+    db: &dyn HirDatabase,
+    infer: &InferenceResult,
+    callee: ExprId,
+) -> Option<String> {
+    let func = infer[callee].as_fn_def(db)?;
+    let krate = func.lookup(db.upcast()).module(db.upcast()).krate();
+    match db.crate_graph()[krate].origin {
+        CrateOrigin::Lang(
+            LangCrateOrigin::Core | LangCrateOrigin::Alloc | LangCrateOrigin::Std,
+        ) => Some(db.function_data(func).name.to_string()),
+        _ => None,
+    }
+}
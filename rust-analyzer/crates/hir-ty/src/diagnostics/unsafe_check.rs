@@ -1,10 +1,12 @@
 //! Provides validations for unsafe code. Currently checks if unsafe functions are missing
 //! unsafe blocks.
 
+use std::sync::Arc;
+
 use hir_def::{
-    body::Body,
+    body::{scope::ExprScopes, Body},
     expr::{Expr, ExprId, UnaryOp},
-    resolver::{resolver_for_expr, ResolveValueResult, ValueNs},
+    resolver::{resolver_for_scope_in, ResolveValueResult, ValueNs},
     DefWithBodyId,
 };
 
@@ -13,7 +15,18 @@ use crate::{
 };
 
 pub fn missing_unsafe(db: &dyn HirDatabase, def: DefWithBodyId) -> Vec<ExprId> {
-    let infer = db.infer(def);
+    let body = db.body(def);
+    missing_unsafe_in_body(db, def, &body, &db.infer(def), &db.expr_scopes(def))
+}
+
+/// Like [`missing_unsafe`], but for a body of `def` that isn't the one in the database.
+pub fn missing_unsafe_in_body(
+    db: &dyn HirDatabase,
+    def: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    scopes: &Arc<ExprScopes>,
+) -> Vec<ExprId> {
     let mut res = Vec::new();
 
     let is_unsafe = match def {
@@ -26,8 +39,7 @@ pub fn missing_unsafe(db: &dyn HirDatabase, def: DefWithBodyId) -> Vec<ExprId> {
         return res;
     }
 
-    let body = db.body(def);
-    unsafe_expressions(db, &infer, def, &body, body.body_expr, &mut |expr| {
+    walk_unsafe(db, infer, def, body, scopes, body.body_expr, None, &mut |expr| {
         if !expr.inside_unsafe_block {
             res.push(expr.expr);
         }
@@ -38,7 +50,21 @@ pub fn missing_unsafe(db: &dyn HirDatabase, def: DefWithBodyId) -> Vec<ExprId> {
 
 pub struct UnsafeExpr {
     pub expr: ExprId,
+    pub kind: UnsafeExprKind,
     pub inside_unsafe_block: bool,
+    /// The innermost `unsafe` block containing the expression.
+    pub unsafe_block: Option<ExprId>,
+}
+
+/// The reason an expression needs an `unsafe` context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnsafeExprKind {
+    /// A call of an `unsafe fn`, either as a function or as a method.
+    UnsafeCall,
+    /// A dereference of a raw pointer.
+    RawPtrDeref,
+    /// An access of a `static mut`.
+    MutableStatic,
 }
 
 // FIXME: Move this out, its not a diagnostic only thing anymore, and handle unsafe pattern accesses as well
@@ -50,7 +76,7 @@ pub fn unsafe_expressions(
     current: ExprId,
     unsafe_expr_cb: &mut dyn FnMut(UnsafeExpr),
 ) {
-    walk_unsafe(db, infer, def, body, current, false, unsafe_expr_cb)
+    walk_unsafe(db, infer, def, body, &db.expr_scopes(def), current, None, unsafe_expr_cb)
 }
 
 fn walk_unsafe(
@@ -58,25 +84,38 @@ fn walk_unsafe(
     infer: &InferenceResult,
     def: DefWithBodyId,
     body: &Body,
+    scopes: &Arc<ExprScopes>,
     current: ExprId,
-    inside_unsafe_block: bool,
+    unsafe_block: Option<ExprId>,
     unsafe_expr_cb: &mut dyn FnMut(UnsafeExpr),
 ) {
+    let inside_unsafe_block = unsafe_block.is_some();
     let expr = &body.exprs[current];
     match expr {
         &Expr::Call { callee, .. } => {
             if let Some(func) = infer[callee].as_fn_def(db) {
                 if is_fn_unsafe_to_call(db, func) {
-                    unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                    unsafe_expr_cb(UnsafeExpr {
+                        expr: current,
+                        kind: UnsafeExprKind::UnsafeCall,
+                        inside_unsafe_block,
+                        unsafe_block,
+                    });
                 }
             }
         }
         Expr::Path(path) => {
-            let resolver = resolver_for_expr(db.upcast(), def, current);
+            let scope = scopes.scope_for(current);
+            let resolver = resolver_for_scope_in(db.upcast(), def, scopes.clone(), scope);
             let value_or_partial = resolver.resolve_path_in_value_ns(db.upcast(), path.mod_path());
             if let Some(ResolveValueResult::ValueNs(ValueNs::StaticId(id))) = value_or_partial {
                 if db.static_data(id).mutable {
-                    unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                    unsafe_expr_cb(UnsafeExpr {
+                        expr: current,
+                        kind: UnsafeExprKind::MutableStatic,
+                        inside_unsafe_block,
+                        unsafe_block,
+                    });
                 }
             }
         }
@@ -86,21 +125,40 @@ fn walk_unsafe(
                 .map(|(func, _)| is_fn_unsafe_to_call(db, func))
                 .unwrap_or(false)
             {
-                unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                unsafe_expr_cb(UnsafeExpr {
+                    expr: current,
+                    kind: UnsafeExprKind::UnsafeCall,
+                    inside_unsafe_block,
+                    unsafe_block,
+                });
             }
         }
         Expr::UnaryOp { expr, op: UnaryOp::Deref } => {
             if let TyKind::Raw(..) = &infer[*expr].kind(Interner) {
-                unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                unsafe_expr_cb(UnsafeExpr {
+                    expr: current,
+                    kind: UnsafeExprKind::RawPtrDeref,
+                    inside_unsafe_block,
+                    unsafe_block,
+                });
             }
         }
         Expr::Unsafe { body: child } => {
-            return walk_unsafe(db, infer, def, body, *child, true, unsafe_expr_cb);
+            return walk_unsafe(
+                db,
+                infer,
+                def,
+                body,
+                scopes,
+                *child,
+                Some(current),
+                unsafe_expr_cb,
+            );
         }
         _ => {}
     }
 
     expr.walk_child_exprs(|child| {
-        walk_unsafe(db, infer, def, body, child, inside_unsafe_block, unsafe_expr_cb);
+        walk_unsafe(db, infer, def, body, scopes, child, unsafe_block, unsafe_expr_cb);
     });
 }
@@ -18,13 +18,13 @@ use std::sync::Arc;
 
 use chalk_ir::{cast::Cast, ConstValue, DebruijnIndex, Mutability, Safety, Scalar, TypeFlags};
 use hir_def::{
-    body::Body,
+    body::{scope::ExprScopes, Body},
     builtin_type::BuiltinType,
     data::{ConstData, StaticData},
     expr::{BindingAnnotation, ExprId, PatId},
     lang_item::LangItemTarget,
     path::{path, Path},
-    resolver::{HasResolver, ResolveValueResult, Resolver, TypeNs, ValueNs},
+    resolver::{resolver_for_scope_in, HasResolver, ResolveValueResult, Resolver, TypeNs, ValueNs},
     type_ref::TypeRef,
     AdtId, AssocItemId, DefWithBodyId, EnumVariantId, FieldId, FunctionId, HasModule, Lookup,
     TraitId, TypeAliasId, VariantId,
@@ -60,9 +60,20 @@ mod closure;
 /// The entry point of type inference.
 pub(crate) fn infer_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<InferenceResult> {
     let _p = profile::span("infer_query");
-    let resolver = def.resolver(db.upcast());
     let body = db.body(def);
-    let mut ctx = InferenceContext::new(db, def, &body, resolver);
+    Arc::new(infer_body(db, def, &body, db.expr_scopes(def)))
+}
+
+/// Infers the types of `body` with the scopes `scopes` as the body of `def`, for bodies that
+/// aren't the one of `def` in the database, see `Body::speculative_with_source_map`.
+pub fn infer_body(
+    db: &dyn HirDatabase,
+    def: DefWithBodyId,
+    body: &Body,
+    scopes: Arc<ExprScopes>,
+) -> InferenceResult {
+    let resolver = def.resolver(db.upcast());
+    let mut ctx = InferenceContext::new(db, def, body, scopes, resolver);
 
     match def {
         DefWithBodyId::ConstId(c) => ctx.collect_const(&db.const_data(c)),
@@ -78,7 +89,7 @@ pub(crate) fn infer_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<Infer
 
     ctx.infer_body();
 
-    Arc::new(ctx.resolve_all())
+    ctx.resolve_all()
 }
 
 /// Fully normalize all the types found within `ty` in context of `owner` body definition.
@@ -408,6 +419,7 @@ pub(crate) struct InferenceContext<'a> {
     pub(crate) db: &'a dyn HirDatabase,
     pub(crate) owner: DefWithBodyId,
     pub(crate) body: &'a Body,
+    scopes: Arc<ExprScopes>,
     pub(crate) resolver: Resolver,
     table: unify::InferenceTable<'a>,
     trait_env: Arc<TraitEnvironment>,
@@ -472,6 +484,7 @@ impl<'a> InferenceContext<'a> {
         db: &'a dyn HirDatabase,
         owner: DefWithBodyId,
         body: &'a Body,
+        scopes: Arc<ExprScopes>,
         resolver: Resolver,
     ) -> Self {
         let krate = owner.module(db.upcast()).krate();
@@ -486,12 +499,18 @@ impl<'a> InferenceContext<'a> {
             db,
             owner,
             body,
+            scopes,
             resolver,
             diverges: Diverges::Maybe,
             breakables: Vec::new(),
         }
     }
 
+    fn resolver_for_expr(&self, expr: ExprId) -> Resolver {
+        let scope = self.scopes.scope_for(expr);
+        resolver_for_scope_in(self.db.upcast(), self.owner, self.scopes.clone(), scope)
+    }
+
     fn resolve_all(self) -> InferenceResult {
         let InferenceContext { mut table, mut result, .. } = self;
 
@@ -13,7 +13,6 @@ use hir_def::{
     expr::{ArithOp, Array, BinaryOp, CmpOp, Expr, ExprId, LabelId, Literal, Statement, UnaryOp},
     generics::TypeOrConstParamData,
     path::{GenericArg, GenericArgs},
-    resolver::resolver_for_expr,
     ConstParamId, FieldId, ItemContainerId, Lookup,
 };
 use hir_expand::name::Name;
@@ -113,10 +112,8 @@ impl<'a> InferenceContext<'a> {
                 TyKind::Scalar(Scalar::Bool).intern(Interner)
             }
             Expr::Block { statements, tail, label, id: _ } => {
-                let old_resolver = mem::replace(
-                    &mut self.resolver,
-                    resolver_for_expr(self.db.upcast(), self.owner, tgt_expr),
-                );
+                let resolver = self.resolver_for_expr(tgt_expr);
+                let old_resolver = mem::replace(&mut self.resolver, resolver);
                 let ty = match label {
                     Some(_) => {
                         let break_ty = self.table.new_type_var();
@@ -369,7 +366,7 @@ impl<'a> InferenceContext<'a> {
             }
             Expr::Path(p) => {
                 // FIXME this could be more efficient...
-                let resolver = resolver_for_expr(self.db.upcast(), self.owner, tgt_expr);
+                let resolver = self.resolver_for_expr(tgt_expr);
                 self.infer_path(&resolver, p, tgt_expr.into()).unwrap_or_else(|| self.err_ty())
             }
             Expr::Continue { label } => {
@@ -12,7 +12,9 @@ mod autoderef;
 mod builder;
 mod chalk_db;
 mod chalk_ext;
+pub mod alias;
 pub mod consteval;
+pub mod dataflow;
 mod infer;
 mod inhabitedness;
 mod interner;
@@ -50,8 +52,8 @@ pub use autoderef::autoderef;
 pub use builder::{ParamKind, TyBuilder};
 pub use chalk_ext::*;
 pub use infer::{
-    could_coerce, could_unify, Adjust, Adjustment, AutoBorrow, BindingMode, InferenceDiagnostic,
-    InferenceResult,
+    could_coerce, could_unify, infer_body, Adjust, Adjustment, AutoBorrow, BindingMode,
+    InferenceDiagnostic, InferenceResult,
 };
 pub use interner::Interner;
 pub use lower::{
@@ -9,6 +9,8 @@ mod macros;
 mod display_source_code;
 mod incremental;
 mod diagnostics;
+mod dataflow;
+mod alias;
 
 use std::{collections::HashMap, env, sync::Arc};
 
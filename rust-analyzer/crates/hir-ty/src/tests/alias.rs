@@ -0,0 +1,154 @@
+use expect_test::{expect, Expect};
+use hir_def::{body::Body, db::DefDatabase, expr::Pat};
+
+use crate::{
+    alias::{AliasAnalysis, Aliasing},
+    dataflow::ProgramPoint,
+};
+
+use super::dataflow::{annotated_point, setup};
+
+fn root_name(body: &Body, root: hir_def::expr::PatId) -> String {
+    match &body[root] {
+        Pat::Bind { name, .. } => name.to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+/// Renders how the ranges of `len` elements at the pointers annotated with `a` and `b` relate.
+fn check(ra_fixture: &str, expect: Expect) {
+    let (db, _, def) = setup(ra_fixture);
+    let body = db.body(def);
+    let expr = |label: &str| match annotated_point(&db, def, label) {
+        ProgramPoint::Expr(expr) => expr,
+        ProgramPoint::Pat(_) => unreachable!(),
+    };
+    let analysis = AliasAnalysis::new(&db, def);
+    let actual = match analysis.range_aliasing(expr("a"), expr("b"), Some(expr("len"))) {
+        Aliasing::DifferentAllocations => "different allocations".to_string(),
+        Aliasing::DisjointRanges { root, offsets } => {
+            format!("disjoint ranges of {} at {:?}", root_name(&body, root), offsets)
+        }
+        Aliasing::OverlappingRanges { root } => {
+            format!("overlapping ranges of {}", root_name(&body, root))
+        }
+        Aliasing::SameAllocation { root } => format!("same allocation {}", root_name(&body, root)),
+        Aliasing::Unknown => "unknown".to_string(),
+    };
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn overlapping_ranges_of_an_array() {
+    check(
+        r#"
+fn f() {
+    let mut buf = [0u8; 8];
+    copy(buf.as_ptr(), buf.as_mut_ptr().add(2), 4);
+       //^^^^^^^^^^^^a ^^^^^^^^^^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["overlapping ranges of buf"]],
+    );
+}
+
+#[test]
+fn disjoint_ranges_of_an_array() {
+    check(
+        r#"
+fn f() {
+    let mut buf = [0u8; 8];
+    copy(buf[0..2].as_ptr(), buf[4..].as_mut_ptr(), 2);
+       //^^^^^^^^^^^^^^^^^^a ^^^^^^^^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["disjoint ranges of buf at (0, 4)"]],
+    );
+}
+
+#[test]
+fn different_arrays() {
+    check(
+        r#"
+fn f() {
+    let src = [0u8; 8];
+    let mut dst = [0u8; 8];
+    copy(src.as_ptr(), dst.as_mut_ptr(), 8);
+       //^^^^^^^^^^^^a ^^^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["different allocations"]],
+    );
+}
+
+#[test]
+fn pointers_held_in_locals() {
+    check(
+        r#"
+fn f() {
+    let mut buf = [0u8; 8];
+    let n = 3;
+    let p = buf.as_ptr();
+    let q = p.add(1);
+    copy(q, buf.as_mut_ptr(), n);
+       //^a ^^^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["overlapping ranges of buf"]],
+    );
+}
+
+#[test]
+fn borrows_lead_to_the_owner() {
+    check(
+        r#"
+fn f() {
+    let mut buf = [0u8; 8];
+    let r = &mut buf;
+    copy(&buf[6] as *const u8, r.as_mut_ptr(), 2);
+       //^^^^^^^^^^^^^^^^^^^^a ^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["disjoint ranges of buf at (6, 0)"]],
+    );
+}
+
+#[test]
+fn runtime_offsets() {
+    check(
+        r#"
+fn f(i: usize) {
+    let mut buf = [0u8; 8];
+    copy(buf.as_ptr(), buf.as_mut_ptr().add(i), 2);
+       //^^^^^^^^^^^^a ^^^^^^^^^^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["same allocation buf"]],
+    );
+}
+
+#[test]
+fn shared_references_may_alias() {
+    check(
+        r#"
+fn f(a: &[u8], b: &[u8]) {
+    copy(a.as_ptr(), b.as_ptr(), 2);
+       //^^^^^^^^^^a ^^^^^^^^^^b ^len
+}
+"#,
+        expect![["unknown"]],
+    );
+}
+
+#[test]
+fn mutable_reference_is_unique() {
+    check(
+        r#"
+fn f(a: &mut [u8], b: &[u8]) {
+    copy(b.as_ptr(), a.as_mut_ptr(), 2);
+       //^^^^^^^^^^a ^^^^^^^^^^^^^^b ^len
+}
+"#,
+        expect![["different allocations"]],
+    );
+}
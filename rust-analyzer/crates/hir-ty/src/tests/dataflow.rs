@@ -0,0 +1,388 @@
+use base_db::{fixture::WithFixture, FileId, SourceDatabaseExt};
+use expect_test::{expect, Expect};
+use hir_def::{
+    body::BodySourceMap,
+    db::DefDatabase,
+    expr::{Pat, PatId},
+    DefWithBodyId,
+};
+use stdx::format_to;
+use syntax::TextRange;
+
+use crate::{
+    dataflow::{AccessKind, BodyDataflow, Definition, ProgramPoint},
+    test_db::TestDB,
+};
+
+use super::{expr_node, pat_node, visit_module};
+
+/// The function `f` of the fixture.
+pub(super) fn setup(ra_fixture: &str) -> (TestDB, FileId, DefWithBodyId) {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let mut defs = Vec::new();
+    visit_module(&db, &def_map, module.local_id, &mut |it| defs.push(it));
+    let def = defs.into_iter().find(|it| match it {
+        DefWithBodyId::FunctionId(it) => db.function_data(*it).name.to_string() == "f",
+        _ => false,
+    });
+    (db, file_id, def.unwrap())
+}
+
+fn point_range(db: &TestDB, source_map: &BodySourceMap, point: ProgramPoint) -> TextRange {
+    let node = match point {
+        ProgramPoint::Expr(expr) => expr_node(source_map, expr, db),
+        ProgramPoint::Pat(pat) => pat_node(source_map, pat, db),
+    };
+    node.unwrap().as_ref().original_file_range(db).range
+}
+
+/// The program point of the expression annotated with `label`.
+pub(super) fn annotated_point(db: &TestDB, def: DefWithBodyId, label: &str) -> ProgramPoint {
+    let (body, source_map) = db.body_with_source_map(def);
+    let annotations = db.extract_annotations();
+    let (range, _) = annotations
+        .values()
+        .flatten()
+        .find(|(_, it)| it == label)
+        .unwrap_or_else(|| panic!("no `{}` annotation", label));
+    let point = body
+        .exprs
+        .iter()
+        .map(|(expr, _)| ProgramPoint::Expr(expr))
+        .find(|&point| point_range(db, &source_map, point) == *range);
+    point.unwrap_or_else(|| panic!("no expression at {:?}", range))
+}
+
+pub(super) fn local(db: &TestDB, def: DefWithBodyId, name: &str) -> PatId {
+    let body = db.body(def);
+    let pat = body
+        .pats
+        .iter()
+        .find(|(_, pat)| matches!(pat, Pat::Bind { name: it, .. } if it.to_string() == name));
+    body.pattern_representative(pat.unwrap().0)
+}
+
+/// Renders the def-use chains of all locals of the function in the fixture.
+fn check_chains(ra_fixture: &str, expect: Expect) {
+    let (db, file_id, def) = setup(ra_fixture);
+    let (body, source_map) = db.body_with_source_map(def);
+    let dataflow = BodyDataflow::new(&db, def);
+    let text = db.file_text(file_id);
+    let snippet = |range: TextRange| text[range].to_string();
+
+    let mut definitions: Vec<_> = body
+        .pats
+        .iter()
+        .filter(|(_, pat)| matches!(pat, Pat::Bind { .. }))
+        .map(|(pat, _)| ProgramPoint::Pat(pat))
+        .chain(body.exprs.iter().map(|(expr, _)| ProgramPoint::Expr(expr)))
+        .flat_map(|point| {
+            dataflow
+                .accesses(point)
+                .iter()
+                .filter(|access| access.kind == AccessKind::Define)
+                .map(move |access| Definition { local: access.local, point })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    definitions.sort_by_key(|def| point_range(&db, &source_map, def.point).start());
+
+    let mut buf = String::new();
+    for def in definitions {
+        let range = point_range(&db, &source_map, def.point);
+        format_to!(buf, "{:?} {}:", range, snippet(range));
+        for &point in dataflow.uses(def) {
+            let range = point_range(&db, &source_map, point);
+            format_to!(buf, " {:?} {:?}", range, snippet(range));
+        }
+        buf.push('\n');
+    }
+    expect.assert_eq(&buf);
+}
+
+/// Checks whether the local `name` is mutated or moved between the expressions annotated with
+/// `from` and `to`.
+fn check_between(ra_fixture: &str, name: &str, expected: bool) {
+    let (db, _, def) = setup(ra_fixture);
+    let dataflow = BodyDataflow::new(&db, def);
+    let (from, to) = (annotated_point(&db, def, "from"), annotated_point(&db, def, "to"));
+    assert_eq!(dataflow.is_mutated_or_moved_between(local(&db, def, name), from, to), expected);
+}
+
+/// Checks whether the expression annotated with `a` dominates the one annotated with `b`.
+fn check_dominates(ra_fixture: &str, expected: bool) {
+    let (db, _, def) = setup(ra_fixture);
+    let dataflow = BodyDataflow::new(&db, def);
+    let (a, b) = (annotated_point(&db, def, "a"), annotated_point(&db, def, "b"));
+    assert_eq!(dataflow.dominates(a, b), expected);
+}
+
+#[test]
+fn straight_line_chains() {
+    check_chains(
+        r#"
+fn f(a: i32) -> i32 {
+    let mut x = a;
+    let y = x + 1;
+    x = y;
+    x
+}
+"#,
+        expect![[r#"
+            5..6 a: 38..39 "a"
+            30..35 mut x: 53..54 "x"
+            49..50 y: 68..69 "y"
+            64..65 x: 75..76 "x"
+        "#]],
+    );
+}
+
+#[test]
+fn chains_merge_after_branches() {
+    check_chains(
+        r#"
+fn f(c: bool) -> i32 {
+    let mut x = 1;
+    if c {
+        x = 2;
+    }
+    x
+}
+"#,
+        expect![[r#"
+            5..6 c: 49..50 "c"
+            31..36 mut x: 78..79 "x"
+            61..62 x: 78..79 "x"
+        "#]],
+    );
+}
+
+#[test]
+fn chains_around_loops() {
+    check_chains(
+        r#"
+fn f() {
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+    }
+}
+"#,
+        expect![[r#"
+            17..22 mut i: 38..39 "i" 55..56 "i"
+            55..56 i: 38..39 "i" 55..56 "i"
+        "#]],
+    );
+}
+
+#[test]
+fn closures_use_captures_where_created() {
+    check_chains(
+        r#"
+fn f() {
+    let x = 1;
+    let g = || x;
+    g();
+}
+"#,
+        expect![[r#"
+            17..18 x: 36..40 "|| x"
+            32..33 g: 46..47 "g"
+        "#]],
+    );
+}
+
+#[test]
+fn mutable_borrow_between() {
+    check_between(
+        r#"
+fn f() {
+    let mut v = 1;
+    let a = v;
+          //^ from
+    let r = &mut v;
+    let b = v;
+          //^ to
+}
+"#,
+        "v",
+        true,
+    );
+}
+
+#[test]
+fn shared_borrow_is_not_a_mutation() {
+    check_between(
+        r#"
+fn f() {
+    let mut v = 1;
+    let a = v;
+          //^ from
+    let r = &v;
+    let b = v;
+          //^ to
+}
+"#,
+        "v",
+        false,
+    );
+}
+
+#[test]
+fn assignment_in_branch_between() {
+    check_between(
+        r#"
+fn f(c: bool) {
+    let mut v = 1;
+    let a = v;
+          //^ from
+    if c {
+        v = 2;
+    }
+    let b = v;
+          //^ to
+}
+"#,
+        "v",
+        true,
+    );
+}
+
+#[test]
+fn assignment_after_is_not_between() {
+    check_between(
+        r#"
+fn f() {
+    let mut v = 1;
+    let a = v;
+          //^ from
+    let b = v;
+          //^ to
+    v = 2;
+}
+"#,
+        "v",
+        false,
+    );
+}
+
+#[test]
+fn assignment_in_loop_is_between() {
+    check_between(
+        r#"
+fn f() {
+    let mut v = 1;
+    loop {
+        let b = v;
+              //^ to
+        let a = v;
+              //^ from
+        v = 2;
+    }
+}
+"#,
+        "v",
+        true,
+    );
+}
+
+#[test]
+fn move_between() {
+    check_between(
+        r#"
+//- minicore: copy
+struct S;
+fn take(s: S) {}
+fn f() {
+    let s = S;
+    let a = &s;
+           //^ from
+    take(s);
+    let b = &s;
+           //^ to
+}
+"#,
+        "s",
+        true,
+    );
+}
+
+#[test]
+fn copy_is_not_a_move() {
+    check_between(
+        r#"
+//- minicore: copy, derive
+#[derive(Clone, Copy)]
+struct S;
+fn take(s: S) {}
+fn f() {
+    let s = S;
+    let a = &s;
+           //^ from
+    take(s);
+    let b = &s;
+           //^ to
+}
+"#,
+        "s",
+        false,
+    );
+}
+
+#[test]
+fn method_call_with_mut_autoref_between() {
+    check_between(
+        r#"
+struct V;
+impl V {
+    fn push(&mut self) {}
+    fn len(&self) -> usize { 0 }
+}
+fn f() {
+    let mut v = V;
+    let a = v.len();
+          //^^^^^^^ from
+    v.push();
+    let b = v.len();
+          //^^^^^^^ to
+}
+"#,
+        "v",
+        true,
+    );
+}
+
+#[test]
+fn condition_dominates_branches() {
+    check_dominates(
+        r#"
+fn f(c: bool) {
+    if c {
+     //^ a
+        let x = 1;
+              //^ b
+    }
+}
+"#,
+        true,
+    );
+}
+
+#[test]
+fn branch_does_not_dominate_join() {
+    check_dominates(
+        r#"
+fn f(c: bool) {
+    if c {
+        let x = 1;
+              //^ a
+    }
+    let y = 2;
+          //^ b
+}
+"#,
+        false,
+    );
+}
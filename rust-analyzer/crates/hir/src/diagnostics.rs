@@ -10,7 +10,7 @@ use hir_def::path::ModPath;
 use hir_expand::{name::Name, HirFileId, InFile};
 use syntax::{ast, AstPtr, SyntaxNodePtr, TextRange};
 
-use crate::{MacroKind, Type};
+use crate::{AutoTraitBlocker, MacroKind, Trait, Type};
 
 macro_rules! diagnostics {
     ($($diag:ident,)*) => {
@@ -34,6 +34,7 @@ diagnostics![
     InactiveCode,
     IncorrectCase,
     InvalidDeriveTarget,
+    InvalidUncheckedArgument,
     MacroError,
     MalformedDerive,
     MismatchedArgCount,
@@ -41,6 +42,7 @@ diagnostics![
     MissingMatchArms,
     MissingUnsafe,
     NoSuchField,
+    OverlappingCopy,
     ReplaceFilterMapNextWithFindMap,
     TypeMismatch,
     UnimplementedBuiltinMacro,
@@ -49,6 +51,7 @@ diagnostics![
     UnresolvedMacroCall,
     UnresolvedModule,
     UnresolvedProcMacro,
+    UnsafeAutoTraitImpl,
 ];
 
 #[derive(Debug)]
@@ -132,6 +135,28 @@ pub struct MissingUnsafe {
     pub expr: InFile<AstPtr<ast::Expr>>,
 }
 
+/// A call of `copy_nonoverlapping` whose source and destination ranges overlap.
+#[derive(Debug)]
+pub struct OverlappingCopy {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+}
+
+/// A call of an unchecked constructor, like `char::from_u32_unchecked`, whose constant argument
+/// breaks its precondition.
+#[derive(Debug)]
+pub struct InvalidUncheckedArgument {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub reason: String,
+}
+
+/// An `unsafe impl` of `Send` or `Sync` for a type with fields that don't implement the trait.
+#[derive(Debug)]
+pub struct UnsafeAutoTraitImpl {
+    pub impl_: InFile<AstPtr<ast::Impl>>,
+    pub trait_: Trait,
+    pub blockers: Vec<AutoTraitBlocker>,
+}
+
 #[derive(Debug)]
 pub struct MissingFields {
     pub file: HirFileId,
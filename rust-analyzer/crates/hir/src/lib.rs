@@ -39,33 +39,34 @@ use arrayvec::ArrayVec;
 use base_db::{CrateDisplayName, CrateId, CrateOrigin, Edition, FileId, ProcMacroKind};
 use either::Either;
 use hir_def::{
-    adt::{ReprData, VariantData},
-    body::{BodyDiagnostic, SyntheticSyntax},
-    expr::{BindingAnnotation, LabelId, Pat, PatId},
+    adt::{ReprData, ReprKind, VariantData},
+    body::{scope::ExprScopes, Body, BodyDiagnostic, BodySourceMap, SyntheticSyntax},
+    expr::{BindingAnnotation, LabelId, Literal, Pat, PatId},
     generics::{TypeOrConstParamData, TypeParamProvenance},
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
     nameres::{self, diagnostics::DefDiagnostic},
     per_ns::PerNs,
-    resolver::{HasResolver, Resolver},
+    resolver::{resolver_for_scope_in, HasResolver, Resolver},
     src::HasSource as _,
     AdtId, AssocItemId, AssocItemLoc, AttrDefId, ConstId, ConstParamId, DefWithBodyId, EnumId,
-    FunctionId, GenericDefId, HasModule, ImplId, ItemContainerId, LifetimeParamId,
+    EnumVariantId, FunctionId, GenericDefId, HasModule, ImplId, ItemContainerId, LifetimeParamId,
     LocalEnumVariantId, LocalFieldId, Lookup, MacroExpander, MacroId, ModuleId, StaticId, StructId,
-    TraitId, TypeAliasId, TypeOrConstParamId, TypeParamId, UnionId,
+    TraitId, TypeAliasId, TypeOrConstParamId, TypeParamId, UnionId, VariantId,
 };
 use hir_expand::{name::name, MacroCallKind};
 use hir_ty::{
     all_super_traits, autoderef,
     consteval::{unknown_const_as_generic, ComputedExpr, ConstEvalError, ConstExt},
-    diagnostics::BodyValidationDiagnostic,
+    diagnostics::{BodyValidationDiagnostic, UncheckedValue},
     method_resolution::{self, TyFingerprint},
     primitive::UintTy,
     subst_prefix,
     traits::FnTrait,
     AliasTy, CallableDefId, CallableSig, Canonical, CanonicalVarKinds, Cast, ClosureId,
-    GenericArgData, Interner, ParamKind, QuantifiedWhereClause, Scalar, Substitution,
-    TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind, WhereClause,
+    GenericArgData, InferenceResult, Interner, ParamKind, QuantifiedWhereClause, Scalar,
+    Substitution, TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind,
+    WhereClause,
 };
 use itertools::Itertools;
 use nameres::diagnostics::DefDiagnosticKind;
@@ -83,13 +84,17 @@ pub use crate::{
     attrs::{HasAttrs, Namespace},
     diagnostics::{
         AnyDiagnostic, BreakOutsideOfLoop, InactiveCode, IncorrectCase, InvalidDeriveTarget,
-        MacroError, MalformedDerive, MismatchedArgCount, MissingFields, MissingMatchArms,
-        MissingUnsafe, NoSuchField, ReplaceFilterMapNextWithFindMap, TypeMismatch,
-        UnimplementedBuiltinMacro, UnresolvedExternCrate, UnresolvedImport, UnresolvedMacroCall,
-        UnresolvedModule, UnresolvedProcMacro,
+        InvalidUncheckedArgument, MacroError, MalformedDerive, MismatchedArgCount, MissingFields,
+        MissingMatchArms, MissingUnsafe, NoSuchField, OverlappingCopy,
+        ReplaceFilterMapNextWithFindMap, TypeMismatch, UnimplementedBuiltinMacro,
+        UnresolvedExternCrate, UnresolvedImport, UnresolvedMacroCall, UnresolvedModule,
+        UnresolvedProcMacro, UnsafeAutoTraitImpl,
     },
     has_source::HasSource,
-    semantics::{PathResolution, Semantics, SemanticsScope, TypeInfo, VisibleTraits},
+    semantics::{
+        LocalDefinition, PathResolution, PointerAliasing, Semantics, SemanticsScope, TypeInfo,
+        UncheckedConstant, UnsafeContext, UnsafeOp, UnsafeOpKind, VisibleTraits,
+    },
 };
 
 // Be careful with these re-exports.
@@ -546,6 +551,14 @@ impl Module {
                 emit_def_diagnostic(db, acc, diag);
             }
 
+            let blockers = impl_def.auto_trait_blockers(db);
+            if let (Some(trait_), Some(source), false) =
+                (impl_def.trait_(db), impl_def.source(db), blockers.is_empty())
+            {
+                let impl_ = source.map(|it| AstPtr::new(&it));
+                acc.push(UnsafeAutoTraitImpl { impl_, trait_, blockers }.into());
+            }
+
             for item in impl_def.items(db) {
                 let def: DefWithBody = match item {
                     AssocItem::Function(it) => it.into(),
@@ -967,6 +980,14 @@ impl Enum {
     pub fn is_data_carrying(self, db: &dyn HirDatabase) -> bool {
         self.variants(db).iter().any(|v| !matches!(v.kind(db), StructKind::Unit))
     }
+
+    /// Whether the enum is represented by an integer type chosen with `#[repr]`.
+    pub fn is_int_repr(self, db: &dyn HirDatabase) -> bool {
+        matches!(
+            db.enum_data(self.id).repr,
+            Some(ReprData { kind: ReprKind::BuiltinInt { .. }, .. })
+        )
+    }
 }
 
 impl HasVisibility for Enum {
@@ -1023,6 +1044,23 @@ impl Variant {
     pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
         db.const_eval_variant(self.into())
     }
+
+    /// The discriminant of the variant: its evaluated value if it has one, the discriminant of
+    /// the previous variant plus one otherwise.
+    pub fn discriminant(self, db: &dyn HirDatabase) -> Option<i128> {
+        if self.value(db).is_some() {
+            return match self.eval(db).ok()? {
+                ComputedExpr::Literal(Literal::Int(value, _)) => Some(value),
+                ComputedExpr::Literal(Literal::Uint(value, _)) => value.try_into().ok(),
+                _ => None,
+            };
+        }
+        let variants = self.parent.variants(db);
+        match variants.iter().position(|&it| it == self)?.checked_sub(1) {
+            Some(previous) => variants[previous].discriminant(db)?.checked_add(1),
+            None => Some(0),
+        }
+    }
 }
 
 /// Variants inherit visibility from the parent enum.
@@ -1307,6 +1345,20 @@ impl DefWithBody {
             }
         }
 
+        for expr in hir_ty::alias::overlapping_copies(db, self.into()) {
+            if let Ok(expr) = source_map.expr_syntax(expr) {
+                acc.push(OverlappingCopy { expr }.into());
+            }
+        }
+
+        for (expr, value) in hir_ty::diagnostics::unchecked_constant_calls(db, self.into()) {
+            if let (UncheckedValue::Invalid(reason), Ok(expr)) =
+                (value, source_map.expr_syntax(expr))
+            {
+                acc.push(InvalidUncheckedArgument { expr, reason }.into());
+            }
+        }
+
         for diagnostic in BodyValidationDiagnostic::collect(db, self.into()) {
             match diagnostic {
                 BodyValidationDiagnostic::RecordMissingFields {
@@ -1520,6 +1572,11 @@ impl Function {
         hir_ty::is_fn_unsafe_to_call(db, self.id)
     }
 
+    /// Whether this function is declared in an `extern` block.
+    pub fn is_extern(self, db: &dyn HirDatabase) -> bool {
+        matches!(self.id.lookup(db.upcast()).container, ItemContainerId::ExternBlockId(_))
+    }
+
     /// Whether this function declaration has a definition.
     ///
     /// This is false in the case of required (not provided) trait methods.
@@ -1541,6 +1598,183 @@ impl Function {
         let def_map = db.crate_def_map(loc.krate(db).into());
         def_map.fn_as_proc_macro(self.id).map(|id| Macro { id: id.into() })
     }
+
+    /// The errors in the body of this function, see [`BodyError`].
+    pub fn body_errors(self, db: &dyn HirDatabase) -> Vec<BodyError> {
+        let def = DefWithBodyId::from(self.id);
+        let (body, source_map) = db.body_with_source_map(def);
+        body_errors(db, self.id, body, &source_map, db.infer(def), db.expr_scopes(def), &Some)
+    }
+
+    /// The errors [`Function::body_errors`] finds once the source of this function is replaced
+    /// with `edited`, without changing the database. `original_range` maps the ranges of `edited`
+    /// that the edit leaves alone to the file of this function, the errors in the code the edit
+    /// adds have no range.
+    pub fn speculative_body_errors(
+        self,
+        db: &dyn HirDatabase,
+        edited: &ast::Fn,
+        original_range: impl Fn(TextRange) -> Option<TextRange>,
+    ) -> Vec<BodyError> {
+        let (body, source_map) =
+            Body::speculative_with_source_map(db.upcast(), self.id, edited, &original_range);
+        let body = Arc::new(body);
+        let scopes = Arc::new(ExprScopes::new(&body));
+        let infer = hir_ty::infer_body(db, self.id.into(), &body, scopes.clone());
+        body_errors(db, self.id, body, &source_map, Arc::new(infer), scopes, &original_range)
+    }
+}
+
+/// An error in the body of a function that an edit of the body should not add: a diagnostic
+/// other than a lint, or a name, macro call or method call that doesn't resolve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BodyError {
+    pub kind: BodyErrorKind,
+    /// The range of the error in the file of the function or in a macro expansion, `None` in the
+    /// code an edit of the body adds.
+    pub range: Option<InFile<TextRange>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyErrorKind {
+    MacroError,
+    UnresolvedProcMacro,
+    UnresolvedMacroCall,
+    NoSuchField,
+    BreakOutsideOfLoop,
+    MismatchedArgCount,
+    TypeMismatch,
+    MissingUnsafe,
+    MissingFields,
+    MissingMatchArms,
+    /// A single name that doesn't resolve to a value.
+    UnresolvedName,
+    /// A qualified path that doesn't resolve to a value.
+    UnresolvedQualifiedPath,
+    /// A method call on a receiver of known type that doesn't resolve.
+    UnresolvedMethodCall,
+}
+
+fn body_errors(
+    db: &dyn HirDatabase,
+    func: FunctionId,
+    body: Arc<Body>,
+    source_map: &BodySourceMap,
+    infer: Arc<InferenceResult>,
+    scopes: Arc<ExprScopes>,
+    original_range: &dyn Fn(TextRange) -> Option<TextRange>,
+) -> Vec<BodyError> {
+    let def = DefWithBodyId::from(func);
+    let file_id = func.lookup(db.upcast()).source(db.upcast()).file_id;
+    let mut errors = Vec::new();
+    let mut push = |kind, node: InFile<SyntaxNodePtr>| {
+        let range = if node.file_id == file_id {
+            original_range(node.value.text_range()).map(|range| InFile::new(file_id, range))
+        } else {
+            Some(node.map(|ptr| ptr.text_range()))
+        };
+        errors.push(BodyError { kind, range });
+    };
+    let expr_syntax = |expr| source_map.expr_syntax(expr).ok().map(|it| it.map(|it| it.into()));
+
+    // Unlike `DefWithBody::diagnostics`, this leaves out the diagnostics of the items in the body
+    // and the lints: overlapping copies, unchecked constant arguments, incorrect case and
+    // `filter_map(..).next()`. Errors without syntax are left out as well.
+    for diag in source_map.diagnostics() {
+        let (kind, node) = match diag {
+            BodyDiagnostic::InactiveCode { .. } => continue,
+            BodyDiagnostic::MacroError { node, .. } => (BodyErrorKind::MacroError, node),
+            BodyDiagnostic::UnresolvedProcMacro { node, .. } => {
+                (BodyErrorKind::UnresolvedProcMacro, node)
+            }
+            BodyDiagnostic::UnresolvedMacroCall { node, .. } => {
+                (BodyErrorKind::UnresolvedMacroCall, node)
+            }
+        };
+        push(kind, node.clone().map(|it| it.into()));
+    }
+    for diag in &infer.diagnostics {
+        let (kind, expr) = match *diag {
+            hir_ty::InferenceDiagnostic::NoSuchField { expr } => (BodyErrorKind::NoSuchField, expr),
+            hir_ty::InferenceDiagnostic::BreakOutsideOfLoop { expr, .. } => {
+                (BodyErrorKind::BreakOutsideOfLoop, expr)
+            }
+            hir_ty::InferenceDiagnostic::MismatchedArgCount { call_expr, .. } => {
+                (BodyErrorKind::MismatchedArgCount, call_expr)
+            }
+        };
+        if let Some(node) = expr_syntax(expr) {
+            push(kind, node);
+        }
+    }
+    for (expr, _) in infer.expr_type_mismatches() {
+        if let Some(node) = expr_syntax(expr) {
+            push(BodyErrorKind::TypeMismatch, node);
+        }
+    }
+    for expr in hir_ty::diagnostics::missing_unsafe_in_body(db, def, &body, &infer, &scopes) {
+        if let Some(node) = expr_syntax(expr) {
+            push(BodyErrorKind::MissingUnsafe, node);
+        }
+    }
+    for diagnostic in
+        BodyValidationDiagnostic::collect_in_body(db, def, body.clone(), infer.clone())
+    {
+        let (kind, node) = match diagnostic {
+            BodyValidationDiagnostic::RecordMissingFields {
+                record: Either::Left(expr), ..
+            } => (BodyErrorKind::MissingFields, expr_syntax(expr)),
+            BodyValidationDiagnostic::RecordMissingFields {
+                record: Either::Right(pat), ..
+            } => (
+                BodyErrorKind::MissingFields,
+                source_map
+                    .pat_syntax(pat)
+                    .ok()
+                    .map(|it| it.map(|it| it.either(Into::into, Into::into))),
+            ),
+            BodyValidationDiagnostic::MissingMatchArms { match_expr, .. } => {
+                (BodyErrorKind::MissingMatchArms, expr_syntax(match_expr))
+            }
+            BodyValidationDiagnostic::ReplaceFilterMapNextWithFindMap { .. } => continue,
+        };
+        if let Some(node) = node {
+            push(kind, node);
+        }
+    }
+
+    for (expr_id, expr) in body.exprs.iter() {
+        // Only the source of the function itself, not the macro expansions in it
+        let node = match expr_syntax(expr_id) {
+            Some(node) if node.file_id == file_id => node,
+            _ => continue,
+        };
+        match expr {
+            hir_def::expr::Expr::Path(path) => {
+                if infer.assoc_resolutions_for_expr(expr_id).is_some() {
+                    continue;
+                }
+                let scope = scopes.scope_for(expr_id);
+                let resolver = resolver_for_scope_in(db.upcast(), def, scopes.clone(), scope);
+                if resolver.resolve_path_in_value_ns_fully(db.upcast(), path.mod_path()).is_some() {
+                    continue;
+                }
+                if path.mod_path().is_ident() && path.type_anchor().is_none() {
+                    push(BodyErrorKind::UnresolvedName, node);
+                } else {
+                    push(BodyErrorKind::UnresolvedQualifiedPath, node);
+                }
+            }
+            hir_def::expr::Expr::MethodCall { receiver, .. } => {
+                if !infer[*receiver].is_unknown() && infer.method_resolution(expr_id).is_none() {
+                    push(BodyErrorKind::UnresolvedMethodCall, node);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    errors
 }
 
 // Note: logically, this belongs to `hir_ty`, but we are not using it there yet.
@@ -2716,6 +2950,71 @@ impl Impl {
         db.impl_data(self.id).is_negative
     }
 
+    /// For an `unsafe impl` of an auto trait like `Send` or `Sync`, the fields of the self type
+    /// that don't implement the trait. Empty for other impls.
+    pub fn auto_trait_blockers(self, db: &dyn HirDatabase) -> Vec<AutoTraitBlocker> {
+        let trait_ = match self.trait_(db) {
+            Some(it) if it.is_auto(db) && it.is_unsafe(db) && !self.is_negative(db) => it,
+            _ => return Vec::new(),
+        };
+        let self_ty = self.self_ty(db);
+        let (variants, substs): (Vec<VariantId>, _) = match self_ty.ty.kind(Interner) {
+            TyKind::Adt(hir_ty::AdtId(AdtId::StructId(it)), substs) => (vec![(*it).into()], substs),
+            TyKind::Adt(hir_ty::AdtId(AdtId::UnionId(it)), substs) => (vec![(*it).into()], substs),
+            TyKind::Adt(hir_ty::AdtId(AdtId::EnumId(it)), substs) => {
+                let enum_data = db.enum_data(*it);
+                let variants = enum_data.variants.iter().map(|(local_id, _)| {
+                    VariantId::EnumVariantId(EnumVariantId { parent: *it, local_id })
+                });
+                (variants.collect(), substs)
+            }
+            _ => return Vec::new(),
+        };
+        let send_sync: Vec<Trait> = ["send", "sync"]
+            .into_iter()
+            .filter_map(|name| db.lang_item(self_ty.env.krate, SmolStr::new_inline(name)))
+            .filter_map(LangItemTarget::as_trait)
+            .map(Trait::from)
+            .collect();
+
+        let mut res = Vec::new();
+        for variant in variants {
+            let fields: Vec<_> = db
+                .field_types(variant)
+                .iter()
+                .map(|(local_id, ty)| {
+                    (local_id, self_ty.derived(ty.clone().substitute(Interner, substs)))
+                })
+                .collect();
+            // A pointer to a value the type owns, as told by a `PhantomData` of the value, is
+            // fine as long as the value implements the trait itself.
+            let owned: Vec<Type> = fields
+                .iter()
+                .filter(|(_, ty)| is_adt_named(db, ty, "PhantomData"))
+                .filter_map(|(_, ty)| ty.type_arguments().next())
+                .filter(|it| it.impls_trait(db, trait_, &[]))
+                .collect();
+            for (local_id, ty) in fields {
+                if ty.impls_trait(db, trait_, &[]) {
+                    continue;
+                }
+                let pointee = match ty.ty.kind(Interner) {
+                    TyKind::Raw(_, pointee) => Some(ty.derived(pointee.clone())),
+                    _ if is_adt_named(db, &ty, "NonNull") => ty.type_arguments().next(),
+                    _ => None,
+                };
+                if pointee.map_or(false, |it| owned.contains(&it)) {
+                    continue;
+                }
+                let culprit = auto_trait_culprit(db, &ty, trait_, 0);
+                let kind = AutoTraitBlockerKind::new(db, &culprit, &send_sync);
+                let field = Field { parent: variant.into(), id: local_id };
+                res.push(AutoTraitBlocker { field, ty, culprit, kind });
+            }
+        }
+        res
+    }
+
     pub fn module(self, db: &dyn HirDatabase) -> Module {
         self.id.lookup(db.upcast()).container.into()
     }
@@ -2726,6 +3025,85 @@ impl Impl {
     }
 }
 
+/// A field keeping its type from implementing an auto trait like `Send` or `Sync`.
+#[derive(Debug, Clone)]
+pub struct AutoTraitBlocker {
+    pub field: Field,
+    /// The type of the field, with the generic parameters of the impl.
+    pub ty: Type,
+    /// The innermost part of `ty` that doesn't implement the trait, like the `Rc<u8>` of
+    /// `Vec<Rc<u8>>`.
+    pub culprit: Type,
+    pub kind: AutoTraitBlockerKind,
+}
+
+/// Why the culprit of an [`AutoTraitBlocker`] doesn't implement the auto trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoTraitBlockerKind {
+    /// A raw pointer, which is neither `Send` nor `Sync`.
+    RawPointer,
+    /// An `Rc` or its `Weak`, which count references without atomics. `arc_is_send_sync` tells
+    /// whether an `Arc` of the same value would be `Send` and `Sync`.
+    Rc { arc_is_send_sync: bool },
+    /// A `Cell`-like type, mutable through shared references without synchronization.
+    InteriorMutability,
+    /// A type parameter the impl doesn't require to implement the trait.
+    TypeParam,
+    /// Any other type opting out of the trait.
+    Other,
+}
+
+impl AutoTraitBlockerKind {
+    fn new(db: &dyn HirDatabase, culprit: &Type, send_sync: &[Trait]) -> AutoTraitBlockerKind {
+        if culprit.is_raw_ptr() {
+            return AutoTraitBlockerKind::RawPointer;
+        }
+        if culprit.as_type_param(db).is_some() {
+            return AutoTraitBlockerKind::TypeParam;
+        }
+        let adt = match culprit.as_adt() {
+            Some(it) => it,
+            None => return AutoTraitBlockerKind::Other,
+        };
+        let name = adt.name(db).to_smol_str();
+        let in_rc = || adt.module(db).name(db).map_or(false, |it| it.to_smol_str() == "rc");
+        if name == "Rc" || (name == "Weak" && in_rc()) {
+            let arc_is_send_sync = send_sync.len() == 2
+                && culprit
+                    .type_arguments()
+                    .all(|arg| send_sync.iter().all(|&trait_| arg.impls_trait(db, trait_, &[])));
+            return AutoTraitBlockerKind::Rc { arc_is_send_sync };
+        }
+        match name.as_str() {
+            "Cell" | "RefCell" | "UnsafeCell" | "OnceCell" => {
+                AutoTraitBlockerKind::InteriorMutability
+            }
+            _ => AutoTraitBlockerKind::Other,
+        }
+    }
+}
+
+fn is_adt_named(db: &dyn HirDatabase, ty: &Type, name: &str) -> bool {
+    ty.as_adt().map_or(false, |it| it.name(db).to_smol_str() == name)
+}
+
+/// The innermost part of `ty` that doesn't implement `trait_`: a type argument, element or
+/// referent that doesn't implement it either, else `ty` itself.
+fn auto_trait_culprit(db: &dyn HirDatabase, ty: &Type, trait_: Trait, depth: usize) -> Type {
+    let components = match ty.ty.kind(Interner) {
+        TyKind::Adt(..) => ty.type_arguments().collect(),
+        TyKind::Ref(_, _, inner) | TyKind::Array(inner, _) | TyKind::Slice(inner) => {
+            vec![ty.derived(inner.clone())]
+        }
+        TyKind::Tuple(..) => ty.tuple_fields(db),
+        _ => Vec::new(),
+    };
+    match components.into_iter().find(|it| !it.impls_trait(db, trait_, &[])) {
+        Some(it) if depth < 8 => auto_trait_culprit(db, &it, trait_, depth + 1),
+        _ => ty.clone(),
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Type {
     env: Arc<TraitEnvironment>,
@@ -2812,6 +3190,13 @@ impl Type {
         matches!(self.ty.kind(Interner), TyKind::Slice(..))
     }
 
+    pub fn as_slice(&self) -> Option<Type> {
+        match self.ty.kind(Interner) {
+            TyKind::Slice(ty) => Some(self.derived(ty.clone())),
+            _ => None,
+        }
+    }
+
     pub fn is_usize(&self) -> bool {
         matches!(self.ty.kind(Interner), TyKind::Scalar(Scalar::Uint(UintTy::Usize)))
     }
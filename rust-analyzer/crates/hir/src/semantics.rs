@@ -5,6 +5,7 @@ mod source_to_def;
 use std::{cell::RefCell, fmt, iter, ops};
 
 use base_db::{FileId, FileRange};
+use either::Either;
 use hir_def::{
     body, macro_id_to_def_id,
     resolver::{self, HasResolver, Resolver, TypeNs},
@@ -103,6 +104,72 @@ impl TypeInfo {
     }
 }
 
+/// An operation that is only allowed inside an `unsafe` context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeOp {
+    pub kind: UnsafeOpKind,
+    /// The expression performing the operation. For operations coming from macro expansions this
+    /// node lives in the expansion, use `Semantics::original_range` to map it back.
+    pub expr: ast::Expr,
+}
+
+/// What allows an unsafe operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsafeContext {
+    /// The innermost `unsafe` block around the operation.
+    Block(ast::BlockExpr),
+    /// An `unsafe fn`, for operations of its body outside of any `unsafe` block.
+    Function(ast::Fn),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnsafeOpKind {
+    /// A call of an `unsafe fn`, either as a function or as a method.
+    UnsafeCall,
+    /// A dereference of a raw pointer.
+    RawPtrDeref,
+    /// An access of a `static mut`.
+    MutableStatic,
+}
+
+/// A place at which a local gets a new value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalDefinition {
+    /// The binding of the local, in a pattern or as the `self` parameter.
+    Binding(Either<ast::Pat, ast::SelfParam>),
+    /// The local as the left hand side of an assignment, plain or compound.
+    Assignment(ast::Expr),
+}
+
+/// How the ranges of elements starting at two raw pointers relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerAliasing {
+    /// The pointers point into different allocations.
+    DifferentAllocations,
+    /// The pointers point into the allocation of `allocation`, at these element offsets, and the
+    /// ranges don't overlap.
+    DisjointRanges { allocation: Local, offsets: (i128, i128) },
+    /// The pointers point into the allocation of `allocation` and the ranges overlap.
+    OverlappingRanges { allocation: Local },
+    /// The pointers point into the allocation of `allocation`, the overlap depends on runtime
+    /// values.
+    SameAllocation { allocation: Local },
+    /// Where one of the pointers points isn't known.
+    Unknown,
+}
+
+/// What a call of an unchecked constructor, like `char::from_u32_unchecked`, constructs from its
+/// constant argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UncheckedConstant {
+    Char(char),
+    Str(String),
+    /// The bytes of a C string, without the nul terminator.
+    CStr(Vec<u8>),
+    /// The argument breaks the precondition of the constructor, for this reason.
+    Invalid(String),
+}
+
 /// Primary API to get semantic information, like types, from syntax trees.
 pub struct Semantics<'db, DB> {
     pub db: &'db DB,
@@ -131,6 +198,15 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         Semantics { db, imp: impl_ }
     }
 
+    /// A `Semantics` in which the nodes of `copy`, a copy of the file `file_id`, resolve like
+    /// those of the file. Every node of the file must keep its range in the copy, so nodes added
+    /// to it span no text. Other `Semantics` don't know about the copy.
+    pub fn with_copy(db: &'db DB, copy: &SyntaxNode, file_id: FileId) -> Self {
+        let sema = Semantics::new(db);
+        sema.imp.cache(copy.clone(), file_id.into());
+        sema
+    }
+
     pub fn parse(&self, file_id: FileId) -> ast::SourceFile {
         self.imp.parse(file_id)
     }
@@ -476,6 +552,60 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
     pub fn is_unsafe_ident_pat(&self, ident_pat: &ast::IdentPat) -> bool {
         self.imp.is_unsafe_ident_pat(ident_pat)
     }
+
+    /// Returns all operations within `expr` that require an `unsafe` context, in evaluation order.
+    pub fn unsafe_operations(&self, expr: &ast::Expr) -> Vec<UnsafeOp> {
+        self.imp.unsafe_operations(expr)
+    }
+
+    /// Returns what allows the unsafe operation `expr`: its `unsafe` block, or the `unsafe fn`
+    /// it is in. `None` when `expr` isn't an unsafe operation.
+    pub fn unsafe_context(&self, expr: &ast::Expr) -> Option<UnsafeContext> {
+        self.imp.unsafe_context(expr)
+    }
+
+    /// Whether `local` may be assigned, mutated or moved after `from` is evaluated and before
+    /// `to` is. Answers `true` when the expressions aren't in the body of the local.
+    pub fn is_mutated_or_moved_between(
+        &self,
+        local: Local,
+        from: &ast::Expr,
+        to: &ast::Expr,
+    ) -> bool {
+        self.imp.is_mutated_or_moved_between(local, from, to)
+    }
+
+    /// The definitions of `local` whose value may be seen when `expr` is evaluated.
+    pub fn reaching_definitions(&self, local: Local, expr: &ast::Expr) -> Vec<LocalDefinition> {
+        self.imp.reaching_definitions(local, expr)
+    }
+
+    /// The expressions that may see the value `local` gets at `definition`.
+    pub fn uses_of_definition(&self, local: Local, definition: &LocalDefinition) -> Vec<ast::Expr> {
+        self.imp.uses_of_definition(local, definition)
+    }
+
+    /// Whether every path to `b` evaluates `a` first. Both must be in the same body.
+    pub fn dominates(&self, a: &ast::Expr, b: &ast::Expr) -> bool {
+        self.imp.dominates(a, b)
+    }
+
+    /// How the ranges of `len` elements starting at the raw pointers `a` and `b` relate, as far
+    /// as the body they are in tells.
+    pub fn pointer_aliasing(
+        &self,
+        a: &ast::Expr,
+        b: &ast::Expr,
+        len: Option<&ast::Expr>,
+    ) -> PointerAliasing {
+        self.imp.pointer_aliasing(a, b, len)
+    }
+
+    /// What the call of an unchecked constructor, like `char::from_u32_unchecked`, constructs,
+    /// if its argument is a constant.
+    pub fn unchecked_constant(&self, call: &ast::CallExpr) -> Option<UncheckedConstant> {
+        self.imp.unchecked_constant(call)
+    }
 }
 
 impl<'db> SemanticsImpl<'db> {
@@ -1140,6 +1270,110 @@ impl<'db> SemanticsImpl<'db> {
         sa.is_unsafe_macro_call(self.db, macro_call)
     }
 
+    fn unsafe_operations(&self, expr: &ast::Expr) -> Vec<UnsafeOp> {
+        let sa = match self.analyze(expr.syntax()) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        sa.unsafe_operations(self.db, expr)
+            .into_iter()
+            .filter_map(|(kind, src)| {
+                let root = self.parse_or_expand(src.file_id)?;
+                Some(UnsafeOp { kind, expr: src.value.to_node(&root) })
+            })
+            .collect()
+    }
+
+    fn unsafe_context(&self, expr: &ast::Expr) -> Option<UnsafeContext> {
+        let sa = self.analyze(expr.syntax())?;
+        match sa.unsafe_context(self.db, expr)? {
+            Either::Left(block) => {
+                let root = self.parse_or_expand(block.file_id)?;
+                match block.value.to_node(&root) {
+                    ast::Expr::BlockExpr(block) => Some(UnsafeContext::Block(block)),
+                    _ => None,
+                }
+            }
+            Either::Right(function) => {
+                self.source(function).map(|it| UnsafeContext::Function(it.value))
+            }
+        }
+    }
+
+    fn is_mutated_or_moved_between(&self, local: Local, from: &ast::Expr, to: &ast::Expr) -> bool {
+        let sa = match self.analyze_no_infer(from.syntax()) {
+            Some(it) => it,
+            None => return true,
+        };
+        sa.is_mutated_or_moved_between(self.db, local, from, to).unwrap_or(true)
+    }
+
+    fn reaching_definitions(&self, local: Local, expr: &ast::Expr) -> Vec<LocalDefinition> {
+        let sa = match self.analyze_no_infer(expr.syntax()) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        sa.reaching_definitions(self.db, local, expr)
+            .into_iter()
+            .filter_map(|src| {
+                let root = self.parse_or_expand(src.file_id)?;
+                Some(match src.value {
+                    Either::Left(Either::Left(pat)) => {
+                        LocalDefinition::Binding(Either::Left(pat.to_node(&root)))
+                    }
+                    Either::Left(Either::Right(self_param)) => {
+                        LocalDefinition::Binding(Either::Right(self_param.to_node(&root)))
+                    }
+                    Either::Right(expr) => LocalDefinition::Assignment(expr.to_node(&root)),
+                })
+            })
+            .collect()
+    }
+
+    fn uses_of_definition(&self, local: Local, definition: &LocalDefinition) -> Vec<ast::Expr> {
+        let node = match definition {
+            LocalDefinition::Binding(Either::Left(pat)) => pat.syntax(),
+            LocalDefinition::Binding(Either::Right(self_param)) => self_param.syntax(),
+            LocalDefinition::Assignment(expr) => expr.syntax(),
+        };
+        let sa = match self.analyze_no_infer(node) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        sa.uses_of_definition(self.db, local, definition)
+            .into_iter()
+            .filter_map(|src| {
+                let root = self.parse_or_expand(src.file_id)?;
+                Some(src.value.to_node(&root))
+            })
+            .collect()
+    }
+
+    fn pointer_aliasing(
+        &self,
+        a: &ast::Expr,
+        b: &ast::Expr,
+        len: Option<&ast::Expr>,
+    ) -> PointerAliasing {
+        let sa = match self.analyze_no_infer(a.syntax()) {
+            Some(it) => it,
+            None => return PointerAliasing::Unknown,
+        };
+        sa.pointer_aliasing(self.db, a, b, len).unwrap_or(PointerAliasing::Unknown)
+    }
+
+    fn unchecked_constant(&self, call: &ast::CallExpr) -> Option<UncheckedConstant> {
+        self.analyze_no_infer(call.syntax())?.unchecked_constant(self.db, call)
+    }
+
+    fn dominates(&self, a: &ast::Expr, b: &ast::Expr) -> bool {
+        let sa = match self.analyze_no_infer(a.syntax()) {
+            Some(it) => it,
+            None => return false,
+        };
+        sa.dominates(self.db, a, b).unwrap_or(false)
+    }
+
     fn resolve_attr_macro_call(&self, item: &ast::Item) -> Option<Macro> {
         let item_in_file = self.wrap_node_infile(item.clone());
         let id = self.with_ctx(|ctx| {
@@ -10,6 +10,7 @@ use std::{
     sync::Arc,
 };
 
+use either::Either;
 use hir_def::{
     body::{
         self,
@@ -33,9 +34,11 @@ use hir_expand::{
     HirFileId, InFile,
 };
 use hir_ty::{
+    alias::{AliasAnalysis, Aliasing},
+    dataflow::{BodyDataflow, Definition, ProgramPoint},
     diagnostics::{
-        record_literal_missing_fields, record_pattern_missing_fields, unsafe_expressions,
-        UnsafeExpr,
+        record_literal_missing_fields, record_pattern_missing_fields, unchecked_call_value,
+        unsafe_expressions, UncheckedValue, UnsafeExpr, UnsafeExprKind,
     },
     method_resolution::{self, lang_names_for_bin_op},
     Adjust, Adjustment, AutoBorrow, InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
@@ -45,13 +48,16 @@ use itertools::Itertools;
 use smallvec::SmallVec;
 use syntax::{
     ast::{self, AstNode},
-    SyntaxKind, SyntaxNode, TextRange, TextSize,
+    AstPtr, SyntaxKind, SyntaxNode, TextRange, TextSize,
 };
 
 use crate::{
-    db::HirDatabase, semantics::PathResolution, Adt, AssocItem, BindingMode, BuiltinAttr,
-    BuiltinType, Callable, Const, DeriveHelper, Field, Function, Local, Macro, ModuleDef, Static,
-    Struct, ToolModule, Trait, Type, TypeAlias, Variant,
+    db::HirDatabase,
+    semantics::{
+        LocalDefinition, PathResolution, PointerAliasing, UncheckedConstant, UnsafeOpKind,
+    },
+    Adt, AssocItem, BindingMode, BuiltinAttr, BuiltinType, Callable, Const, DeriveHelper, Field,
+    Function, Local, Macro, ModuleDef, Static, Struct, ToolModule, Trait, Type, TypeAlias, Variant,
 };
 
 /// `SourceAnalyzer` is a convenience wrapper which exposes HIR API in terms of
@@ -761,6 +767,213 @@ impl SourceAnalyzer {
         false
     }
 
+    pub(crate) fn unsafe_operations(
+        &self,
+        db: &dyn HirDatabase,
+        expr: &ast::Expr,
+    ) -> Vec<(UnsafeOpKind, InFile<AstPtr<ast::Expr>>)> {
+        let mut res = Vec::new();
+        let expr_id = match self.expr_id(db, expr) {
+            Some(it) => it,
+            None => return res,
+        };
+        if let (Some((def, body, sm)), Some(infer)) = (&self.def, &self.infer) {
+            unsafe_expressions(
+                db,
+                infer,
+                *def,
+                body,
+                expr_id,
+                &mut |UnsafeExpr { expr, kind, .. }| {
+                    let kind = match kind {
+                        UnsafeExprKind::UnsafeCall => UnsafeOpKind::UnsafeCall,
+                        UnsafeExprKind::RawPtrDeref => UnsafeOpKind::RawPtrDeref,
+                        UnsafeExprKind::MutableStatic => UnsafeOpKind::MutableStatic,
+                    };
+                    if let Ok(src) = sm.expr_syntax(expr) {
+                        res.push((kind, src));
+                    }
+                },
+            );
+        }
+        res
+    }
+
+    /// The `unsafe` block allowing the unsafe operation `expr`, or the `unsafe fn` whose body
+    /// it is in when no block is around it. `None` when `expr` isn't an unsafe operation, or
+    /// when nothing allows it.
+    pub(crate) fn unsafe_context(
+        &self,
+        db: &dyn HirDatabase,
+        expr: &ast::Expr,
+    ) -> Option<Either<InFile<AstPtr<ast::Expr>>, Function>> {
+        let expr_id = self.expr_id(db, expr)?;
+        let (def, body, sm) = self.def.as_ref()?;
+        let infer = self.infer.as_ref()?;
+
+        let mut operation = None;
+        unsafe_expressions(db, infer, *def, body, body.body_expr, &mut |it| {
+            if it.expr == expr_id {
+                operation = Some(it.unsafe_block);
+            }
+        });
+        match operation? {
+            Some(block) => sm.expr_syntax(block).ok().map(Either::Left),
+            None => match def {
+                DefWithBodyId::FunctionId(it) if db.function_data(*it).has_unsafe_kw() => {
+                    Some(Either::Right((*it).into()))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// The program point of an expression of the body, for `local` of the same body.
+    fn dataflow_point(
+        &self,
+        db: &dyn HirDatabase,
+        local: Local,
+        expr: &ast::Expr,
+    ) -> Option<(BodyDataflow, PatId, ProgramPoint)> {
+        let (def, body, _) = self.def.as_ref()?;
+        if local.parent != *def {
+            return None;
+        }
+        let point = ProgramPoint::Expr(self.expr_id(db, expr)?);
+        Some((BodyDataflow::new(db, *def), body.pattern_representative(local.pat_id), point))
+    }
+
+    fn definition_source(
+        &self,
+        point: ProgramPoint,
+    ) -> Option<InFile<Either<body::PatPtr, AstPtr<ast::Expr>>>> {
+        let source_map = self.body_source_map()?;
+        match point {
+            ProgramPoint::Pat(pat) => Some(source_map.pat_syntax(pat).ok()?.map(Either::Left)),
+            ProgramPoint::Expr(expr) => Some(source_map.expr_syntax(expr).ok()?.map(Either::Right)),
+        }
+    }
+
+    pub(crate) fn is_mutated_or_moved_between(
+        &self,
+        db: &dyn HirDatabase,
+        local: Local,
+        from: &ast::Expr,
+        to: &ast::Expr,
+    ) -> Option<bool> {
+        let (dataflow, local, from) = self.dataflow_point(db, local, from)?;
+        let to = ProgramPoint::Expr(self.expr_id(db, to)?);
+        Some(dataflow.is_mutated_or_moved_between(local, from, to))
+    }
+
+    pub(crate) fn reaching_definitions(
+        &self,
+        db: &dyn HirDatabase,
+        local: Local,
+        expr: &ast::Expr,
+    ) -> Vec<InFile<Either<body::PatPtr, AstPtr<ast::Expr>>>> {
+        let (dataflow, local, point) = match self.dataflow_point(db, local, expr) {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        dataflow
+            .reaching_definitions(point, local)
+            .into_iter()
+            .filter_map(|def| self.definition_source(def.point))
+            .collect()
+    }
+
+    pub(crate) fn uses_of_definition(
+        &self,
+        db: &dyn HirDatabase,
+        local: Local,
+        definition: &LocalDefinition,
+    ) -> Vec<InFile<AstPtr<ast::Expr>>> {
+        let (def, body, source_map) = match &self.def {
+            Some((def, body, source_map)) if local.parent == *def => (def, body, source_map),
+            _ => return Vec::new(),
+        };
+        let point = match definition {
+            LocalDefinition::Binding(Either::Left(pat)) => {
+                source_map.node_pat(InFile::new(self.file_id, pat)).map(ProgramPoint::Pat)
+            }
+            LocalDefinition::Binding(Either::Right(self_param)) => source_map
+                .node_self_param(InFile::new(self.file_id, self_param))
+                .map(ProgramPoint::Pat),
+            LocalDefinition::Assignment(expr) => self.expr_id(db, expr).map(ProgramPoint::Expr),
+        };
+        let point = match point {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        let local = body.pattern_representative(local.pat_id);
+        BodyDataflow::new(db, *def)
+            .uses(Definition { local, point })
+            .iter()
+            .filter_map(|point| match point {
+                ProgramPoint::Expr(expr) => source_map.expr_syntax(*expr).ok(),
+                ProgramPoint::Pat(_) => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn pointer_aliasing(
+        &self,
+        db: &dyn HirDatabase,
+        a: &ast::Expr,
+        b: &ast::Expr,
+        len: Option<&ast::Expr>,
+    ) -> Option<PointerAliasing> {
+        let (def, ..) = self.def.as_ref()?;
+        let (a, b) = (self.expr_id(db, a)?, self.expr_id(db, b)?);
+        let len = match len {
+            Some(len) => Some(self.expr_id(db, len)?),
+            None => None,
+        };
+        let local = |pat_id| Local { parent: *def, pat_id };
+        let aliasing = match AliasAnalysis::new(db, *def).range_aliasing(a, b, len) {
+            Aliasing::DifferentAllocations => PointerAliasing::DifferentAllocations,
+            Aliasing::DisjointRanges { root, offsets } => {
+                PointerAliasing::DisjointRanges { allocation: local(root), offsets }
+            }
+            Aliasing::OverlappingRanges { root } => {
+                PointerAliasing::OverlappingRanges { allocation: local(root) }
+            }
+            Aliasing::SameAllocation { root } => {
+                PointerAliasing::SameAllocation { allocation: local(root) }
+            }
+            Aliasing::Unknown => PointerAliasing::Unknown,
+        };
+        Some(aliasing)
+    }
+
+    pub(crate) fn unchecked_constant(
+        &self,
+        db: &dyn HirDatabase,
+        call: &ast::CallExpr,
+    ) -> Option<UncheckedConstant> {
+        let (def, ..) = self.def.as_ref()?;
+        let call = self.expr_id(db, &call.clone().into())?;
+        let constant = match unchecked_call_value(db, *def, call)? {
+            UncheckedValue::Char(c) => UncheckedConstant::Char(c),
+            UncheckedValue::Str(text) => UncheckedConstant::Str(text.into()),
+            UncheckedValue::CStr(bytes) => UncheckedConstant::CStr(bytes.into()),
+            UncheckedValue::Invalid(reason) => UncheckedConstant::Invalid(reason),
+        };
+        Some(constant)
+    }
+
+    pub(crate) fn dominates(
+        &self,
+        db: &dyn HirDatabase,
+        a: &ast::Expr,
+        b: &ast::Expr,
+    ) -> Option<bool> {
+        let (def, ..) = self.def.as_ref()?;
+        let (a, b) = (self.expr_id(db, a)?, self.expr_id(db, b)?);
+        Some(BodyDataflow::new(db, *def).dominates(ProgramPoint::Expr(a), ProgramPoint::Expr(b)))
+    }
+
     fn resolve_impl_method(
         &self,
         db: &dyn HirDatabase,
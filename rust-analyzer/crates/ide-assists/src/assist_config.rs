@@ -14,4 +14,52 @@ pub struct AssistConfig {
     pub allowed: Option<Vec<AssistKind>>,
     pub insert_use: InsertUseConfig,
     pub prefer_no_std: bool,
+    pub unsafe_to_safe: UnsafeToSafeConfig,
+}
+
+/// Settings of the unsafe-to-safe conversions. Rules are named after the unsafe API they
+/// replace, like `get_unchecked` or `ptr::copy_nonoverlapping`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsafeToSafeConfig {
+    /// The rules to suggest, or `None` for all of them.
+    pub enabled_rules: Option<Vec<String>>,
+    pub disabled_rules: Vec<String>,
+    pub error_style: UnsafeToSafeErrorStyle,
+    /// The largest runtime overhead of the safe version, in percent, for a rule to be suggested.
+    pub max_overhead: Option<u32>,
+    /// Rules disabled in single crates, by crate name.
+    pub crate_disabled_rules: Vec<(String, Vec<String>)>,
+}
+
+impl UnsafeToSafeConfig {
+    pub const DEFAULT: UnsafeToSafeConfig = UnsafeToSafeConfig {
+        enabled_rules: None,
+        disabled_rules: Vec::new(),
+        error_style: UnsafeToSafeErrorStyle::Panic,
+        max_overhead: None,
+        crate_disabled_rules: Vec::new(),
+    };
+
+    pub fn is_rule_enabled(&self, rule: &str, krate: Option<&str>) -> bool {
+        let listed = |rules: &[String]| rules.iter().any(|it| it == rule);
+        let crate_disabled = self
+            .crate_disabled_rules
+            .iter()
+            .any(|(name, rules)| Some(name.as_str()) == krate && listed(rules));
+        self.enabled_rules.as_deref().map_or(true, listed)
+            && !listed(&self.disabled_rules)
+            && !crate_disabled
+    }
+}
+
+/// How the safe version of an unsafe operation reports a violated precondition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnsafeToSafeErrorStyle {
+    /// `.unwrap()`
+    Panic,
+    /// `.expect("...")`, with a message describing the precondition.
+    Expect,
+    /// `?`, where the enclosing function returns a compatible `Option` or `Result`. Elsewhere
+    /// the conversion falls back to panicking.
+    QuestionMark,
 }
@@ -22,7 +22,7 @@ use syntax::{
         edit::{AstNodeEdit, IndentLevel},
         AstNode, HasGenericParams,
     },
-    match_ast, ted, SyntaxElement,
+    match_ast, ted, SourceFile, SyntaxElement,
     SyntaxKind::{self, COMMENT},
     SyntaxNode, SyntaxToken, TextRange, TextSize, TokenAtOffset, WalkEvent, T,
 };
@@ -78,6 +78,36 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
     };
 
     let body = extraction_target(&node, range)?;
+    extract_body(acc, ctx, node, body, None)
+}
+
+/// An unsafe block extracted into a safe function, which asserts the preconditions of the
+/// unsafe operations before running the block.
+#[derive(Debug)]
+pub(crate) struct SafeWrapper {
+    pub(crate) name: String,
+    pub(crate) assertions: Vec<String>,
+    /// The lines of the `SAFETY` comment on the unsafe block, without the `//`.
+    pub(crate) safety_comment: Vec<String>,
+}
+
+pub(crate) fn extract_safe_wrapper(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+    unsafe_expr: &ast::BlockExpr,
+    wrapper: SafeWrapper,
+) -> Option<()> {
+    let body = FunctionBody::from_expr(ast::Expr::BlockExpr(unsafe_expr.clone()))?;
+    extract_body(acc, ctx, unsafe_expr.syntax().clone(), body, Some(wrapper))
+}
+
+fn extract_body(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+    node: SyntaxNode,
+    body: FunctionBody,
+    wrapper: Option<SafeWrapper>,
+) -> Option<()> {
     let container_info = body.analyze_container(&ctx.sema)?;
 
     let (locals_used, self_param) = body.analyze(&ctx.sema);
@@ -95,9 +125,14 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
 
     let scope = ImportScope::find_insert_use_container(&node, &ctx.sema)?;
 
+    let (assist_name, assist_label) = match &wrapper {
+        Some(_) => ("extract_safe_wrapper", "Extract into safe wrapper function"),
+        None => ("extract_function", "Extract into function"),
+    };
+
     acc.add(
-        AssistId("extract_function", crate::AssistKind::RefactorExtract),
-        "Extract into function",
+        AssistId(assist_name, crate::AssistKind::RefactorExtract),
+        assist_label,
         target_range,
         move |builder| {
             let outliving_locals: Vec<_> = ret_values.collect();
@@ -111,7 +146,8 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
 
             let extracted_from_trait_impl = body.extracted_from_trait_impl();
 
-            let name = make_function_name(&semantics_scope);
+            let default_name = wrapper.as_ref().map_or("fun_name", |it| it.name.as_str());
+            let name = make_function_name(&semantics_scope, default_name);
 
             let fun = Function {
                 name,
@@ -122,6 +158,7 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
                 body,
                 outliving_locals,
                 mods: container_info,
+                wrapper,
             };
 
             let new_indent = IndentLevel::from_node(&insert_after);
@@ -171,12 +208,13 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
     )
 }
 
-fn make_function_name(semantics_scope: &hir::SemanticsScope<'_>) -> ast::NameRef {
+fn make_function_name(
+    semantics_scope: &hir::SemanticsScope<'_>,
+    default_name: &str,
+) -> ast::NameRef {
     let mut names_in_scope = vec![];
     semantics_scope.process_all_names(&mut |name, _| names_in_scope.push(name.to_string()));
 
-    let default_name = "fun_name";
-
     let mut name = default_name.to_string();
     let mut counter = 0;
     while names_in_scope.contains(&name) {
@@ -245,6 +283,7 @@ struct Function {
     body: FunctionBody,
     outliving_locals: Vec<OutlivedLocal>,
     mods: ContainerInfo,
+    wrapper: Option<SafeWrapper>,
 }
 
 #[derive(Debug)]
@@ -1694,6 +1733,11 @@ fn make_body(
             let expr = rewrite_body_segment(ctx, &fun.params, &handler, expr.syntax());
             let expr = ast::Expr::cast(expr).unwrap();
             match expr {
+                ast::Expr::BlockExpr(block) if fun.wrapper.is_some() => {
+                    // The unsafe block stays as it is, after the checks of its preconditions.
+                    let block = block.dedent(old_indent).indent(IndentLevel(1));
+                    make_wrapper_body(fun.wrapper.as_ref().unwrap(), &block)
+                }
                 ast::Expr::BlockExpr(block) => {
                     // If the extracted expression is itself a block, there is no need to wrap it inside another block.
                     let block = block.dedent(old_indent);
@@ -1805,6 +1849,19 @@ fn make_body(
     block.indent(new_indent)
 }
 
+fn make_wrapper_body(wrapper: &SafeWrapper, unsafe_block: &ast::BlockExpr) -> ast::BlockExpr {
+    let mut text = String::from("{\n");
+    for assertion in &wrapper.assertions {
+        format_to!(text, "    {}\n", assertion);
+    }
+    for line in &wrapper.safety_comment {
+        format_to!(text, "    // {}\n", line);
+    }
+    format_to!(text, "    {}\n}}", unsafe_block);
+    let file = SourceFile::parse(&format!("fn f() {}", text)).tree();
+    file.syntax().descendants().find_map(ast::BlockExpr::cast).unwrap()
+}
+
 fn map_tail_expr(block: ast::BlockExpr, f: impl FnOnce(ast::Expr) -> ast::Expr) -> ast::BlockExpr {
     let tail_expr = match block.tail_expr() {
         Some(tail_expr) => tail_expr,
@@ -0,0 +1,309 @@
+use either::Either;
+use hir::PathResolution;
+use itertools::Itertools;
+use syntax::{ast, AstNode, TextRange, T};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    handlers::{
+        convert_unsafe_to_safe::{operation_preconditions, Precondition},
+        extract_function::{extract_safe_wrapper as extract_wrapper_function, SafeWrapper},
+    },
+};
+
+// Assist: extract_safe_wrapper
+//
+// Extracts an unsafe block into a private safe function, which asserts the preconditions of
+// the unsafe operations before running the block. Only applies when the assertions check every
+// precondition of the block.
+//
+// ```
+// //- minicore: slice
+// fn nth(bytes: &[u8], index: usize) -> u8 {
+//     unsafe$0 { *bytes.get_unchecked(index) }
+// }
+// ```
+// ->
+// ```
+// fn nth(bytes: &[u8], index: usize) -> u8 {
+//     checked_get(bytes, index)
+// }
+//
+// fn $0checked_get(bytes: &[u8], index: usize) -> u8 {
+//     assert!(index < bytes.len());
+//     // SAFETY: the assertions above check that `index` is in bounds of `bytes`.
+//     unsafe { *bytes.get_unchecked(index) }
+// }
+// ```
+pub(crate) fn extract_safe_wrapper(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let unsafe_kw = ctx.find_token_syntax_at_offset(T![unsafe])?;
+    let unsafe_expr = unsafe_kw.parent().and_then(ast::BlockExpr::cast)?;
+
+    let operations = ctx.sema.unsafe_operations(&ast::Expr::BlockExpr(unsafe_expr.clone()));
+    if operations.is_empty() {
+        return None;
+    }
+
+    let block_range = unsafe_expr.syntax().text_range();
+    let mut preconditions = Vec::new();
+    for operation in &operations {
+        let checks = operation_preconditions(&ctx.sema, &operation.expr);
+        // A safe function must not run an operation it can't check. Some preconditions, like a
+        // pointer pointing to a live value, can't be checked at runtime at all, and the checks run
+        // before the block, so they can't use locals declared in it.
+        if checks.is_empty()
+            || checks
+                .iter()
+                .any(|it| it.unchecked.is_some() || !uses_only_inputs(ctx, block_range, it))
+        {
+            return None;
+        }
+        preconditions.extend(checks);
+    }
+    let preconditions: Vec<Precondition> =
+        preconditions.into_iter().unique_by(|it| it.check.clone()).collect();
+
+    let function = &preconditions[0].function;
+    let name = format!("checked_{}", function.strip_suffix("_unchecked").unwrap_or(function));
+    let wrapper = SafeWrapper {
+        name,
+        assertions: preconditions.iter().map(|it| it.assertion(false)).collect(),
+        safety_comment: safety_comment(&preconditions),
+    };
+
+    extract_wrapper_function(acc, ctx, &unsafe_expr, wrapper)
+}
+
+/// Whether the operands of the check only use locals declared before the unsafe block.
+fn uses_only_inputs(
+    ctx: &AssistContext<'_>,
+    block_range: TextRange,
+    precondition: &Precondition,
+) -> bool {
+    let paths = precondition
+        .operands
+        .iter()
+        .flat_map(|operand| operand.syntax().descendants())
+        .filter_map(ast::Path::cast);
+    for path in paths {
+        let local = match ctx.sema.resolve_path(&path) {
+            Some(PathResolution::Local(local)) => local,
+            _ => continue,
+        };
+        let range = match local.source(ctx.db()).value {
+            Either::Left(pat) => pat.syntax().text_range(),
+            Either::Right(self_param) => self_param.syntax().text_range(),
+        };
+        if block_range.contains_range(range) {
+            return false;
+        }
+    }
+    true
+}
+
+fn safety_comment(preconditions: &[Precondition]) -> Vec<String> {
+    let guarantees: Vec<String> = preconditions
+        .iter()
+        .map(|it| match it.debug_only {
+            true => format!("{} in debug builds", it.guarantee),
+            false => it.guarantee.clone(),
+        })
+        .unique()
+        .collect();
+    vec![format!("SAFETY: the assertions above check that {}.", join_clauses(&guarantees))]
+}
+
+/// `a`, `a and b`, or `a, b and c`.
+fn join_clauses(clauses: &[String]) -> String {
+    match clauses {
+        [] => String::new(),
+        [clause] => clause.clone(),
+        [init @ .., last] => format!("{} and {}", init.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn checks_index_bounds() {
+        check_assist(
+            extract_safe_wrapper,
+            r#"
+//- minicore: slice
+fn nth(bytes: &[u8], index: usize) -> u8 {
+    unsafe$0 { *bytes.get_unchecked(index) }
+}
+"#,
+            r#"
+fn nth(bytes: &[u8], index: usize) -> u8 {
+    checked_get(bytes, index)
+}
+
+fn $0checked_get(bytes: &[u8], index: usize) -> u8 {
+    assert!(index < bytes.len());
+    // SAFETY: the assertions above check that `index` is in bounds of `bytes`.
+    unsafe { *bytes.get_unchecked(index) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn checks_range_bounds() {
+        check_assist(
+            extract_safe_wrapper,
+            r#"
+//- minicore: slice
+fn window(bytes: &[u8], start: usize, end: usize) -> &[u8] {
+    unsafe$0 { bytes.get_unchecked(start..end) }
+}
+"#,
+            r#"
+fn window(bytes: &[u8], start: usize, end: usize) -> &[u8] {
+    checked_get(bytes, start, end)
+}
+
+fn $0checked_get(bytes: &[u8], start: usize, end: usize) -> &[u8] {
+    assert!(start <= end);
+    assert!(end <= bytes.len());
+    // SAFETY: the assertions above check that `start..end` is in bounds of `bytes`.
+    unsafe { bytes.get_unchecked(start..end) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn checks_utf8_in_debug_builds() {
+        check_assist(
+            extract_safe_wrapper,
+            r#"
+//- minicore: str
+use core::str;
+fn name(bytes: &[u8]) -> &str {
+    unsafe$0 { str::from_utf8_unchecked(bytes) }
+}
+"#,
+            r#"
+use core::str;
+fn name(bytes: &[u8]) -> &str {
+    checked_from_utf8(bytes)
+}
+
+fn $0checked_from_utf8(bytes: &[u8]) -> &str {
+    debug_assert!(std::str::from_utf8(bytes).is_ok());
+    // SAFETY: the assertions above check that `bytes` is valid UTF-8 in debug builds.
+    unsafe { str::from_utf8_unchecked(bytes) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_unchecked_operations() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+extern "C" {
+    fn abort();
+}
+fn stop() {
+    unsafe$0 { abort() }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_pointer_reads() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+struct Header { len: u32 }
+fn header_len(header: *const Header) -> u32 {
+    let len = unsafe$0 { (*header).len };
+    len
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_copies() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+//- minicore: ptr
+use core::ptr;
+fn copy(src: *const u8, dst: *mut u8, count: usize) {
+    unsafe$0 {
+        ptr::copy_nonoverlapping(src, dst, count);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_one_operation_is_unchecked() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+//- minicore: slice
+fn nth(bytes: &[u8], index: usize, counter: *const u32) -> u32 {
+    unsafe$0 { *bytes.get_unchecked(index) as u32 + *counter }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_operands_declared_in_block() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+//- minicore: slice
+fn nth(bytes: &[u8]) -> u8 {
+    unsafe$0 {
+        let index = bytes.len() - 1;
+        *bytes.get_unchecked(index)
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_one_bound_uses_block_locals() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+//- minicore: slice
+fn head(bytes: &[u8], end: usize) -> &[u8] {
+    unsafe$0 {
+        let start = end / 2;
+        bytes.get_unchecked(start..end)
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_one_bound_has_side_effects() {
+        check_assist_not_applicable(
+            extract_safe_wrapper,
+            r#"
+//- minicore: slice
+fn next_start() -> usize { 0 }
+fn window(bytes: &[u8], end: usize) -> &[u8] {
+    unsafe$0 { bytes.get_unchecked(next_start()..end) }
+}
+"#,
+        );
+    }
+}
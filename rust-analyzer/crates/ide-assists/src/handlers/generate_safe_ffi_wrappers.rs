@@ -0,0 +1,535 @@
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasName},
+    AstNode, SyntaxKind,
+};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    AssistId, AssistKind,
+};
+
+// Assist: generate_safe_ffi_wrappers
+//
+// Generates a module of safe wrappers for the functions of an `extern "C"` block. A
+// `*const c_char` parameter takes a `&CStr`, a pointer followed by its length takes a slice,
+// and a negative `ssize_t` result becomes the `errno` error of a `Result`.
+//
+// ```
+// type c_char = i8;
+// type c_int = i32;
+// type ssize_t = isize;
+// extern "C"$0 {
+//     fn strlen(s: *const c_char) -> usize;
+//     fn write(fd: c_int, buf: *const u8, count: usize) -> ssize_t;
+// }
+// ```
+// ->
+// ```
+// type c_char = i8;
+// type c_int = i32;
+// type ssize_t = isize;
+// extern "C" {
+//     fn strlen(s: *const c_char) -> usize;
+//     fn write(fd: c_int, buf: *const u8, count: usize) -> ssize_t;
+// }
+//
+// mod $0safe_ffi {
+//     use super::*;
+//
+//     pub fn strlen(s: &std::ffi::CStr) -> usize {
+//         unsafe { super::strlen(s.as_ptr()) }
+//     }
+//
+//     pub fn write(fd: c_int, buf: &[u8]) -> std::io::Result<ssize_t> {
+//         let ret = unsafe { super::write(fd, buf.as_ptr(), buf.len()) };
+//         if ret < 0 {
+//             return Err(std::io::Error::last_os_error());
+//         }
+//         Ok(ret)
+//     }
+// }
+// ```
+pub(crate) fn generate_safe_ffi_wrappers(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let abi = ctx.find_node_at_offset::<ast::Abi>()?;
+    let extern_block = abi.syntax().parent().and_then(ast::ExternBlock::cast)?;
+    if !has_c_abi(&abi) {
+        return None;
+    }
+
+    let wrappers: Vec<String> = extern_block
+        .extern_item_list()?
+        .extern_items()
+        .filter_map(|item| match item {
+            ast::ExternItem::Fn(it) => wrapper_fn(ctx, &it),
+            _ => None,
+        })
+        .collect();
+    if wrappers.is_empty() {
+        return None;
+    }
+
+    let target = extern_block.syntax().text_range();
+    acc.add(
+        AssistId("generate_safe_ffi_wrappers", AssistKind::Generate),
+        "Generate safe wrappers for the foreign functions",
+        target,
+        |builder| {
+            let indent = IndentLevel::from_node(extern_block.syntax());
+            let module =
+                format!("mod $0safe_ffi {{\n    use super::*;\n\n{}}}", wrappers.join("\n"));
+            let mut text = String::from("\n");
+            for line in module.lines() {
+                match line {
+                    "" => text.push('\n'),
+                    _ => format_to!(text, "\n{}{}", indent, line),
+                }
+            }
+            match ctx.config.snippet_cap {
+                Some(cap) => builder.insert_snippet(cap, target.end(), text),
+                None => builder.insert(target.end(), text.replace("$0", "")),
+            }
+        },
+    )
+}
+
+/// Whether the functions of the block use the C calling convention, explicitly or by default.
+fn has_c_abi(abi: &ast::Abi) -> bool {
+    let string = abi.syntax().children_with_tokens().find(|it| it.kind() == SyntaxKind::STRING);
+    match string {
+        Some(it) => matches!(it.to_string().as_str(), r#""C""# | r#""C-unwind""# | r#""system""#),
+        None => true,
+    }
+}
+
+/// The safe wrapper of `function`, if all its parameters have a safe equivalent.
+fn wrapper_fn(ctx: &AssistContext<'_>, function: &ast::Fn) -> Option<String> {
+    let name = function.name()?.to_string();
+    let params: Vec<(String, ast::Type)> = function
+        .param_list()?
+        .params()
+        .enumerate()
+        .map(|(idx, param)| {
+            if param.dotdotdot_token().is_some() {
+                return None;
+            }
+            let name = match param.pat()? {
+                ast::Pat::IdentPat(it) => it.name()?.to_string(),
+                _ => format!("arg{}", idx),
+            };
+            Some((name, param.ty()?))
+        })
+        .collect::<Option<_>>()?;
+
+    let mut args: Vec<Option<String>> = vec![None; params.len()];
+    let mut wrapper_params = Vec::new();
+    let mut lengths = String::new();
+    for (idx, (param, ty)) in params.iter().enumerate() {
+        if args[idx].is_some() {
+            continue;
+        }
+        let ptr = match ty {
+            ast::Type::PtrType(it) => it,
+            _ => {
+                wrapper_params.push(format!("{}: {}", param, ty));
+                args[idx] = Some(param.clone());
+                continue;
+            }
+        };
+        let pointee = ptr.ty()?;
+        let pointee_name = type_name(&pointee);
+        let mutable = ptr.mut_token().is_some();
+        let (borrow, as_ptr) = if mutable { ("&mut ", "as_mut_ptr") } else { ("&", "as_ptr") };
+        let is_void = pointee_name.as_deref() == Some("c_void");
+        let is_c_char = pointee_name.as_deref() == Some("c_char");
+
+        if let Some(len_idx) = length_param(ctx, &params, &args, idx) {
+            let elem = if is_void { String::from("u8") } else { pointee.to_string() };
+            wrapper_params.push(format!("{}: {}[{}]", param, borrow, elem));
+            let cast = if is_void { ".cast()" } else { "" };
+            args[idx] = Some(format!("{}.{}(){}", param, as_ptr, cast));
+            let (len, len_ty) = &params[len_idx];
+            let is_usize = ctx.sema.resolve_type(len_ty).map_or(false, |it| it.is_usize());
+            if is_usize {
+                args[len_idx] = Some(format!("{}.len()", param));
+            } else {
+                // A cast would silently truncate the length.
+                format_to!(
+                    lengths,
+                    "        let {}: {} = {}.len().try_into().expect(\"`{}` is too long\");\n",
+                    len,
+                    len_ty,
+                    param,
+                    param
+                );
+                args[len_idx] = Some(len.clone());
+            }
+        } else if is_c_char && !mutable {
+            wrapper_params.push(format!("{}: &std::ffi::CStr", param));
+            args[idx] = Some(format!("{}.as_ptr()", param));
+        } else {
+            // Nothing tells how much memory the function reads or writes, or whether it accepts
+            // null.
+            return None;
+        }
+    }
+
+    let args: Vec<String> = args.into_iter().collect::<Option<_>>()?;
+    let call = format!("super::{}({})", name, args.join(", "));
+    let ret = if params.iter().any(|(it, _)| it == "ret") { "result" } else { "ret" };
+    let mut buf = format!("    pub fn {}({})", name, wrapper_params.join(", "));
+    match function.ret_type().and_then(|it| it.ty()) {
+        None => format_to!(buf, " {{\n{}        unsafe {{ {} }};\n    }}\n", lengths, call),
+        Some(ast::Type::PtrType(ptr))
+            if ptr.ty().and_then(|it| type_name(&it)).as_deref() == Some("c_char") =>
+        {
+            format_to!(
+                buf,
+                " -> Option<String> {{\n{}        let {} = unsafe {{ {} }};\n",
+                lengths,
+                ret,
+                call
+            );
+            format_to!(
+                buf,
+                "        if {}.is_null() {{\n            return None;\n        }}\n",
+                ret
+            );
+            format_to!(
+                buf,
+                "        Some(unsafe {{ std::ffi::CStr::from_ptr({}) }}.to_string_lossy().into_owned())\n    }}\n",
+                ret
+            );
+        }
+        Some(ty) if uses_errno(ctx, &ty) => {
+            format_to!(buf, " -> std::io::Result<{}> {{\n{}", ty, lengths);
+            format_to!(buf, "        let {} = unsafe {{ {} }};\n", ret, call);
+            format_to!(buf, "        if {} < 0 {{\n", ret);
+            buf.push_str("            return Err(std::io::Error::last_os_error());\n        }\n");
+            format_to!(buf, "        Ok({})\n    }}\n", ret);
+        }
+        Some(ty) => {
+            format_to!(buf, " -> {} {{\n{}        unsafe {{ {} }}\n    }}\n", ty, lengths, call)
+        }
+    }
+    Some(buf)
+}
+
+/// The parameter holding the length of the buffer the pointer parameter at `ptr_idx` points to:
+/// an integer named like a length, right after the pointer or named after it, like `buf_len`.
+fn length_param(
+    ctx: &AssistContext<'_>,
+    params: &[(String, ast::Type)],
+    args: &[Option<String>],
+    ptr_idx: usize,
+) -> Option<usize> {
+    let ptr_name = params[ptr_idx].0.to_ascii_lowercase();
+    params.iter().enumerate().skip(ptr_idx + 1).find_map(|(idx, (name, ty))| {
+        let name = name.to_ascii_lowercase();
+        let is_candidate = args[idx].is_none()
+            && is_length_name(&name)
+            && (idx == ptr_idx + 1 || name.starts_with(&ptr_name));
+        let is_int = || {
+            let builtin = ctx.sema.resolve_type(ty).and_then(|it| it.as_builtin());
+            builtin.map_or(false, |it| it.is_int() || it.is_uint())
+        };
+        if is_candidate && is_int() {
+            Some(idx)
+        } else {
+            None
+        }
+    })
+}
+
+fn is_length_name(name: &str) -> bool {
+    matches!(name, "n" | "nbytes" | "nmemb")
+        || ["len", "length", "size", "count"].iter().any(|it| name.ends_with(it))
+}
+
+/// Whether a function returning `ty` reports errors with a negative result and `errno`. Only
+/// `ssize_t` tells that, a plain signed integer may as well be a value, like the result of `atoi`.
+fn uses_errno(ctx: &AssistContext<'_>, ty: &ast::Type) -> bool {
+    let is_signed =
+        ctx.sema.resolve_type(ty).and_then(|it| it.as_builtin()).map_or(false, |it| it.is_int());
+    is_signed && type_name(ty).as_deref() == Some("ssize_t")
+}
+
+fn type_name(ty: &ast::Type) -> Option<String> {
+    match ty {
+        ast::Type::PathType(it) => Some(it.path()?.segment()?.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn wraps_c_strings() {
+        check_assist(
+            generate_safe_ffi_wrappers,
+            r#"
+type c_char = i8;
+type size_t = usize;
+extern "C"$0 {
+    fn strlen(s: *const c_char) -> size_t;
+}
+"#,
+            r#"
+type c_char = i8;
+type size_t = usize;
+extern "C" {
+    fn strlen(s: *const c_char) -> size_t;
+}
+
+mod $0safe_ffi {
+    use super::*;
+
+    pub fn strlen(s: &std::ffi::CStr) -> size_t {
+        unsafe { super::strlen(s.as_ptr()) }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_pointer_and_length_pairs() {
+        check_assist(
+            generate_safe_ffi_wrappers,
+            r#"
+enum c_void {}
+type c_int = i32;
+type size_t = usize;
+type ssize_t = isize;
+extern "C"$0 {
+    fn read(fd: c_int, buf: *mut c_void, count: size_t) -> ssize_t;
+    fn write(fd: c_int, buf: *const c_void, count: size_t) -> ssize_t;
+    fn checksum(data: *const u32, data_len: c_int, flags: c_int) -> u32;
+}
+"#,
+            r#"
+enum c_void {}
+type c_int = i32;
+type size_t = usize;
+type ssize_t = isize;
+extern "C" {
+    fn read(fd: c_int, buf: *mut c_void, count: size_t) -> ssize_t;
+    fn write(fd: c_int, buf: *const c_void, count: size_t) -> ssize_t;
+    fn checksum(data: *const u32, data_len: c_int, flags: c_int) -> u32;
+}
+
+mod $0safe_ffi {
+    use super::*;
+
+    pub fn read(fd: c_int, buf: &mut [u8]) -> std::io::Result<ssize_t> {
+        let ret = unsafe { super::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(ret)
+    }
+
+    pub fn write(fd: c_int, buf: &[u8]) -> std::io::Result<ssize_t> {
+        let ret = unsafe { super::write(fd, buf.as_ptr().cast(), buf.len()) };
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(ret)
+    }
+
+    pub fn checksum(data: &[u32], flags: c_int) -> u32 {
+        let data_len: c_int = data.len().try_into().expect("`data` is too long");
+        unsafe { super::checksum(data.as_ptr(), data_len, flags) }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn pairs_length_named_after_pointer() {
+        check_assist(
+            generate_safe_ffi_wrappers,
+            r#"
+type c_int = i32;
+type size_t = usize;
+extern "C"$0 {
+    fn fill(buf: *mut u8, flags: c_int, buf_len: size_t);
+}
+"#,
+            r#"
+type c_int = i32;
+type size_t = usize;
+extern "C" {
+    fn fill(buf: *mut u8, flags: c_int, buf_len: size_t);
+}
+
+mod $0safe_ffi {
+    use super::*;
+
+    pub fn fill(buf: &mut [u8], flags: c_int) {
+        unsafe { super::fill(buf.as_mut_ptr(), flags, buf.len()) };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_returned_c_strings() {
+        check_assist(
+            generate_safe_ffi_wrappers,
+            r#"
+type c_char = i8;
+extern "C"$0 {
+    fn getenv(name: *const c_char) -> *mut c_char;
+}
+"#,
+            r#"
+type c_char = i8;
+extern "C" {
+    fn getenv(name: *const c_char) -> *mut c_char;
+}
+
+mod $0safe_ffi {
+    use super::*;
+
+    pub fn getenv(name: &std::ffi::CStr) -> Option<String> {
+        let ret = unsafe { super::getenv(name.as_ptr()) };
+        if ret.is_null() {
+            return None;
+        }
+        Some(unsafe { std::ffi::CStr::from_ptr(ret) }.to_string_lossy().into_owned())
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_plain_return_values() {
+        check_assist(
+            generate_safe_ffi_wrappers,
+            r#"
+type c_char = i8;
+type c_int = i32;
+extern$0 {
+    fn strcmp(a: *const c_char, b: *const c_char) -> c_int;
+    fn atoi(s: *const c_char) -> c_int;
+    fn close(fd: c_int) -> c_int;
+    fn abort() -> !;
+    fn srand(seed: u32);
+}
+"#,
+            r#"
+type c_char = i8;
+type c_int = i32;
+extern {
+    fn strcmp(a: *const c_char, b: *const c_char) -> c_int;
+    fn atoi(s: *const c_char) -> c_int;
+    fn close(fd: c_int) -> c_int;
+    fn abort() -> !;
+    fn srand(seed: u32);
+}
+
+mod $0safe_ffi {
+    use super::*;
+
+    pub fn strcmp(a: &std::ffi::CStr, b: &std::ffi::CStr) -> c_int {
+        unsafe { super::strcmp(a.as_ptr(), b.as_ptr()) }
+    }
+
+    pub fn atoi(s: &std::ffi::CStr) -> c_int {
+        unsafe { super::atoi(s.as_ptr()) }
+    }
+
+    pub fn close(fd: c_int) -> c_int {
+        unsafe { super::close(fd) }
+    }
+
+    pub fn abort() -> ! {
+        unsafe { super::abort() }
+    }
+
+    pub fn srand(seed: u32) {
+        unsafe { super::srand(seed) };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn skips_functions_without_safe_equivalent() {
+        check_assist(
+            generate_safe_ffi_wrappers,
+            r#"
+mod sys {
+    pub enum c_void {}
+    pub type c_char = i8;
+    pub type c_int = i32;
+    extern "C"$0 {
+        fn printf(format: *const c_char, ...) -> c_int;
+        fn free(p: *mut c_void);
+        fn strcpy(dst: *mut c_char, src: *const c_char) -> *mut c_char;
+        fn pipe(fds: *mut [c_int; 2]) -> c_int;
+        fn time(t: *mut i64) -> i64;
+        fn getpid() -> c_int;
+    }
+}
+"#,
+            r#"
+mod sys {
+    pub enum c_void {}
+    pub type c_char = i8;
+    pub type c_int = i32;
+    extern "C" {
+        fn printf(format: *const c_char, ...) -> c_int;
+        fn free(p: *mut c_void);
+        fn strcpy(dst: *mut c_char, src: *const c_char) -> *mut c_char;
+        fn pipe(fds: *mut [c_int; 2]) -> c_int;
+        fn time(t: *mut i64) -> i64;
+        fn getpid() -> c_int;
+    }
+
+    mod $0safe_ffi {
+        use super::*;
+
+        pub fn getpid() -> c_int {
+            unsafe { super::getpid() }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_other_abis() {
+        check_assist_not_applicable(
+            generate_safe_ffi_wrappers,
+            r#"
+extern "Rust"$0 {
+    fn next_id() -> u64;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_safe_equivalents() {
+        check_assist_not_applicable(
+            generate_safe_ffi_wrappers,
+            r#"
+enum c_void {}
+extern "C"$0 {
+    fn free(p: *mut c_void);
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,654 @@
+use hir::{HirDisplay, Local, Module, PathResolution, Type};
+use ide_db::{base_db::FileLoader, FxHashSet};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasGenericParams, HasName},
+    AstNode, SourceFile, SyntaxKind, TextSize,
+};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    handlers::convert_unsafe_to_safe::{safe_conversion, SafeConversion},
+    AssistId, AssistKind,
+};
+
+// Assist: generate_unsafe_differential_test
+//
+// Generates a test module that runs an unsafe block and its safe conversion on boundary
+// inputs and compares their results. Running the test under Miri checks that the
+// conversion behaves like the original.
+//
+// ```
+// fn first(bytes: &[u8]) -> u8 {
+//     let value: u8 = unsafe$0 { *bytes.get_unchecked(0) };
+//     value
+// }
+// ```
+// ->
+// ```
+// fn first(bytes: &[u8]) -> u8 {
+//     let value: u8 = unsafe { *bytes.get_unchecked(0) };
+//     value
+// }
+//
+// #[cfg(test)]
+// mod first_unsafe_conversion_tests {
+//     use super::*;
+//
+//     fn original(bytes: &[u8]) -> u8 {
+//         unsafe { *bytes.get_unchecked(0) }
+//     }
+//
+//     fn converted(bytes: &[u8]) -> u8 {
+//         *bytes.get(0).unwrap()
+//     }
+//
+//     // Run with `cargo miri test` to check the original for undefined behavior.
+//     #[test]
+//     fn converted_matches_original() {
+//         for bytes in [Vec::new(), vec![u8::MAX], (0..16).collect::<Vec<u8>>()] {
+//             let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| converted(&bytes)));
+//             // Inputs rejected by the safe version have no defined behavior in the original.
+//             if let Ok(result) = result {
+//                 assert_eq!(original(&bytes), result);
+//             }
+//         }
+//     }
+// }
+// ```
+pub(crate) fn generate_unsafe_differential_test(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let conversion = safe_conversion(ctx)?;
+    // The snippets are cut out of the file, which doesn't contain blocks from macro expansions
+    // or the blocks wrapped around statements of unsafe functions.
+    if conversion.in_copy {
+        return None;
+    }
+    let fn_def = conversion.unsafe_expr.syntax().ancestors().find_map(ast::Fn::cast)?;
+    // The extracted functions can't name the generic parameters of the original one.
+    if fn_def.generic_param_list().is_some() {
+        return None;
+    }
+    let fn_name = fn_def.name()?;
+    let item = fn_def.syntax().ancestors().find(|it| {
+        ast::Item::can_cast(it.kind())
+            && it.parent().map_or(false, |parent| {
+                matches!(parent.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST)
+            })
+    })?;
+
+    let snippets = Snippets::new(ctx, &fn_def, &conversion)?;
+    let module = ctx.sema.scope(fn_def.syntax())?.module();
+    let indent = IndentLevel::from_node(&item);
+    let test_module = snippets.test_module(ctx, module, &fn_name.text(), &conversion, indent)?;
+
+    acc.add(
+        AssistId("generate_unsafe_differential_test", AssistKind::Generate),
+        format!("Generate differential test for the `{}` conversion", conversion.pattern),
+        conversion.target,
+        |builder| builder.insert(item.text_range().end(), test_module),
+    )
+}
+
+/// The original and the converted code, with the values flowing in and out of them.
+struct Snippets {
+    original: String,
+    converted: String,
+    /// Whether the snippets are expressions that evaluate to the single output.
+    returns_snippet: bool,
+    inputs: Vec<Local>,
+    /// Locals declared before the snippet and only assigned inside of it.
+    assigned: Vec<Local>,
+    /// The expressions returned by both versions, along with their types.
+    outputs: Vec<(String, Type)>,
+}
+
+impl Snippets {
+    fn new(
+        ctx: &AssistContext<'_>,
+        fn_def: &ast::Fn,
+        conversion: &SafeConversion,
+    ) -> Option<Snippets> {
+        let db = ctx.db();
+        let text = db.file_text(ctx.file_id());
+        let block_range = conversion.unsafe_expr.syntax().text_range();
+        let region = conversion
+            .rewrite_edits
+            .iter()
+            .fold(block_range, |region, (range, _)| region.cover(*range));
+
+        let mut converted = text[region].to_string();
+        for (range, replacement) in
+            conversion.rewrite_edits.iter().sorted_by_key(|(it, _)| it.start()).rev()
+        {
+            let range = *range - region.start();
+            converted
+                .replace_range(usize::from(range.start())..usize::from(range.end()), replacement);
+        }
+        let base_indent = line_indent(&text, region.start());
+        let mut original = reindent(&text[region], base_indent);
+        let mut converted = reindent(&converted, base_indent);
+
+        let body = fn_def.body()?;
+        let mut inputs = Vec::new();
+        let mut assigned = Vec::new();
+        let mut outputs = Vec::new();
+
+        let is_expression = region == block_range
+            && conversion.unsafe_expr.syntax().parent().map_or(false, |parent| {
+                !matches!(parent.kind(), SyntaxKind::STMT_LIST | SyntaxKind::EXPR_STMT)
+            });
+        if is_expression {
+            let ty =
+                ctx.sema.type_of_expr(&ast::Expr::BlockExpr(conversion.unsafe_expr.clone()))?;
+            outputs.push(("result".to_string(), ty.original));
+        }
+
+        let mut declared = Vec::new();
+        for ident_pat in body.syntax().descendants().filter_map(ast::IdentPat::cast) {
+            if region.contains_range(ident_pat.syntax().text_range()) {
+                let local = ctx.sema.to_def(&ident_pat)?;
+                if !declared.contains(&local) {
+                    declared.push(local);
+                }
+            }
+        }
+
+        let mut used_later = FxHashSet::default();
+        for path_expr in body.syntax().descendants().filter_map(ast::PathExpr::cast) {
+            let range = path_expr.syntax().text_range();
+            let path = path_expr.path()?;
+            if range.start() >= region.end() {
+                if let Some(name_ref) = path.as_single_name_ref() {
+                    used_later.insert(name_ref.text().to_string());
+                }
+                continue;
+            }
+            if !region.contains_range(range) {
+                continue;
+            }
+            let local = match ctx.sema.resolve_path(&path) {
+                Some(PathResolution::Local(local)) => local,
+                _ => continue,
+            };
+            if local.is_self(db) {
+                return None;
+            }
+            if declared.contains(&local) || inputs.contains(&local) || assigned.contains(&local) {
+                continue;
+            }
+            if is_assignee(&path_expr) {
+                assigned.push(local);
+            } else {
+                inputs.push(local);
+            }
+        }
+
+        let mut results: Vec<_> = declared
+            .iter()
+            .filter(|local| used_later.contains(&local.name(db).to_string()))
+            .copied()
+            .collect();
+        if results.is_empty() && !is_expression {
+            results = declared;
+        }
+        for local in results.into_iter().chain(assigned.iter().copied()) {
+            outputs.push((local.name(db).to_string(), local.ty(db)));
+        }
+        for &local in &inputs {
+            let ty = local.ty(db);
+            if ty.is_mutable_reference() || (local.is_mut(db) && !ty.is_reference()) {
+                outputs.push((local.name(db).to_string(), ty));
+            }
+        }
+
+        // A lone value is returned as is, anything else is bound and returned with the other outputs.
+        let returns_snippet = is_expression && outputs.len() == 1 && !outputs[0].1.is_reference();
+        if is_expression && !returns_snippet {
+            original = format!("let result = {};", original);
+            converted = format!("let result = {};", converted);
+        }
+
+        Some(Snippets { original, converted, returns_snippet, inputs, assigned, outputs })
+    }
+
+    fn test_module(
+        &self,
+        ctx: &AssistContext<'_>,
+        module: Module,
+        fn_name: &str,
+        conversion: &SafeConversion,
+        indent: IndentLevel,
+    ) -> Option<String> {
+        let db = ctx.db();
+        let display = |ty: &Type| ty.display_source_code(db, module.into()).ok();
+
+        let mut params = Vec::new();
+        for &local in &self.inputs {
+            let ty = local.ty(db);
+            let mut_ = if local.is_mut(db) && !ty.is_reference() { "mut " } else { "" };
+            params.push(format!("{}{}: {}", mut_, local.name(db), display(&ty)?));
+        }
+        let params = params.join(", ");
+
+        let mut output_exprs = Vec::new();
+        let mut output_types = Vec::new();
+        for (expr, ty) in &self.outputs {
+            match ty.as_reference() {
+                Some((pointee, _)) => {
+                    output_exprs.push(format!("{}.to_owned()", expr));
+                    output_types.push(owned_type(&display(&pointee)?));
+                }
+                None => {
+                    output_exprs.push(expr.clone());
+                    output_types.push(display(ty)?);
+                }
+            }
+        }
+        let (ret_type, tail) = match output_exprs.len() {
+            _ if self.returns_snippet => (format!(" -> {}", output_types[0]), None),
+            0 => (String::new(), None),
+            1 => (format!(" -> {}", output_types[0]), Some(output_exprs[0].clone())),
+            _ => (
+                format!(" -> ({})", output_types.join(", ")),
+                Some(format!("({})", output_exprs.join(", "))),
+            ),
+        };
+
+        let mut declarations = String::new();
+        for local in &self.assigned {
+            format_to!(declarations, "let {};\n", local.name(db));
+        }
+
+        let mut buf = String::new();
+        format_to!(buf, "\n\n{}#[cfg(test)]\n", indent);
+        format_to!(buf, "{}mod {}_unsafe_conversion_tests {{\n", indent, fn_name);
+        format_to!(buf, "{}use super::*;\n", indent + 1);
+        for import in &conversion.imports {
+            format_to!(buf, "{}use {};\n", indent + 1, import);
+        }
+        for (name, snippet) in [("original", &self.original), ("converted", &self.converted)] {
+            let body = with_tail(&format!("{}{}", declarations, snippet), tail.as_deref());
+            format_to!(buf, "\n{}fn {}({}){} {{\n", indent + 1, name, params, ret_type);
+            for line in body.lines() {
+                if line.is_empty() {
+                    buf.push('\n');
+                } else {
+                    format_to!(buf, "{}{}\n", indent + 2, line);
+                }
+            }
+            format_to!(buf, "{}}}\n", indent + 1);
+        }
+
+        let mut loops = Vec::new();
+        let mut args = Vec::new();
+        for &local in &self.inputs {
+            let name = local.name(db).to_string();
+            let ty = local.ty(db);
+            let (pointee, arg) = match ty.as_reference() {
+                Some((pointee, hir::Mutability::Mut)) => {
+                    (pointee, format!("&mut {}.clone()", name))
+                }
+                Some((pointee, hir::Mutability::Shared)) => (pointee, format!("&{}", name)),
+                None if is_copy_scalar(&display(&ty)?) => (ty, name.clone()),
+                None => (ty, format!("{}.clone()", name)),
+            };
+            loops.push(format!(
+                "for {} in [{}] {{",
+                name,
+                boundary_values(&display(&pointee)?).join(", ")
+            ));
+            args.push(arg);
+        }
+        let args = args.join(", ");
+
+        format_to!(
+            buf,
+            "\n{}// Run with `cargo miri test` to check the original for undefined behavior.\n",
+            indent + 1
+        );
+        format_to!(buf, "{}#[test]\n", indent + 1);
+        format_to!(buf, "{}fn converted_matches_original() {{\n", indent + 1);
+        let mut level = indent + 2;
+        for it in &loops {
+            format_to!(buf, "{}{}\n", level, it);
+            level = level + 1;
+        }
+        format_to!(
+            buf,
+            "{}let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| converted({})));\n",
+            level,
+            args
+        );
+        format_to!(
+            buf,
+            "{}// Inputs rejected by the safe version have no defined behavior in the original.\n",
+            level
+        );
+        format_to!(buf, "{}if let Ok(result) = result {{\n", level);
+        format_to!(buf, "{}assert_eq!(original({}), result);\n", level + 1, args);
+        format_to!(buf, "{}}}\n", level);
+        for _ in &loops {
+            level = IndentLevel(level.0 - 1);
+            format_to!(buf, "{}}}\n", level);
+        }
+        format_to!(buf, "{}}}\n", indent + 1);
+        format_to!(buf, "{}}}", indent);
+        Some(buf)
+    }
+}
+
+/// Whether `path_expr` is the left hand side of an assignment.
+fn is_assignee(path_expr: &ast::PathExpr) -> bool {
+    path_expr.syntax().parent().and_then(ast::BinExpr::cast).map_or(false, |bin_expr| {
+        bin_expr.op_kind() == Some(ast::BinaryOp::Assignment { op: None })
+            && bin_expr.lhs().map_or(false, |lhs| lhs.syntax() == path_expr.syntax())
+    })
+}
+
+/// Appends `tail` to `body`. When `body` ends in a block, the tail goes into that block,
+/// so that it sees the locals declared there.
+fn with_tail(body: &str, tail: Option<&str>) -> String {
+    let tail = match tail {
+        Some(it) => it,
+        None => return body.to_string(),
+    };
+    let prefix = "fn f() {\n";
+    let file = SourceFile::parse(&format!("{}{}\n}}", prefix, body)).tree();
+    let stmt_list = file
+        .syntax()
+        .descendants()
+        .find_map(ast::Fn::cast)
+        .and_then(|it| it.body())
+        .and_then(|it| it.stmt_list());
+    let last_block = stmt_list.and_then(|stmt_list| match stmt_list.tail_expr() {
+        Some(ast::Expr::BlockExpr(block)) => Some(block),
+        Some(_) => None,
+        None => match stmt_list.statements().last()? {
+            ast::Stmt::ExprStmt(stmt) if stmt.semicolon_token().is_none() => match stmt.expr()? {
+                ast::Expr::BlockExpr(block) => Some(block),
+                _ => None,
+            },
+            _ => None,
+        },
+    });
+
+    match last_block.and_then(|block| block.stmt_list()?.r_curly_token()) {
+        Some(r_curly) => {
+            let offset = usize::from(r_curly.text_range().start()) - prefix.len();
+            let inner_indent = IndentLevel::from_token(&r_curly) + 1;
+            let before = body[..offset].trim_end();
+            format!("{}\n{}{}\n{}", before, inner_indent, tail, &body[offset..])
+        }
+        None => format!("{}\n{}", body.trim_end(), tail),
+    }
+}
+
+/// The whitespace in front of the line containing `offset`, in columns.
+fn line_indent(text: &str, offset: TextSize) -> usize {
+    let line_start = text[..usize::from(offset)].rfind('\n').map_or(0, |it| it + 1);
+    text[line_start..].chars().take_while(|it| *it == ' ').count()
+}
+
+/// Removes up to `base_indent` columns from the lines after the first one.
+fn reindent(text: &str, base_indent: usize) -> String {
+    text.trim()
+        .lines()
+        .map(|line| {
+            let indent = line.chars().take_while(|it| *it == ' ').count();
+            &line[indent.min(base_indent)..]
+        })
+        .join("\n")
+}
+
+fn owned_type(pointee: &str) -> String {
+    if pointee == "str" {
+        return "String".to_string();
+    }
+    match slice_element(pointee) {
+        Some(element) => format!("Vec<{}>", element),
+        None => pointee.to_string(),
+    }
+}
+
+fn slice_element(ty: &str) -> Option<&str> {
+    if let Some(element) = ty.strip_prefix('[').and_then(|it| it.strip_suffix(']')) {
+        return (!element.contains(';')).then(|| element);
+    }
+    ty.strip_prefix("Vec<").and_then(|it| it.strip_suffix('>'))
+}
+
+const UNSIGNED: &[&str] = &["u8", "u16", "u32", "u64", "u128", "usize"];
+const SIGNED: &[&str] = &["i8", "i16", "i32", "i64", "i128", "isize"];
+
+fn is_copy_scalar(ty: &str) -> bool {
+    UNSIGNED.contains(&ty) || SIGNED.contains(&ty) || matches!(ty, "f32" | "f64" | "char" | "bool")
+}
+
+/// Values at the edges of the domain of `ty`, where conversions tend to differ.
+fn boundary_values(ty: &str) -> Vec<String> {
+    if UNSIGNED.contains(&ty) {
+        return vec!["0".to_string(), "1".to_string(), format!("{}::MAX", ty)];
+    }
+    if SIGNED.contains(&ty) {
+        return vec![
+            format!("{}::MIN", ty),
+            "-1".to_string(),
+            "0".to_string(),
+            "1".to_string(),
+            format!("{}::MAX", ty),
+        ];
+    }
+    match ty {
+        "f32" | "f64" => {
+            return vec![
+                "0.0".to_string(),
+                "-0.0".to_string(),
+                "1.0".to_string(),
+                format!("{}::MIN_POSITIVE", ty),
+                format!("{}::MAX", ty),
+                format!("{}::INFINITY", ty),
+            ]
+        }
+        "char" => return vec![r"'\0'".to_string(), "'a'".to_string(), r"'\u{10FFFF}'".to_string()],
+        "bool" => return vec!["false".to_string(), "true".to_string()],
+        "str" => {
+            return vec![
+                r#""""#.to_string(),
+                r#""a""#.to_string(),
+                r#""\u{e9}t\u{e9}""#.to_string(),
+            ]
+        }
+        "String" => {
+            return vec![
+                "String::new()".to_string(),
+                r#"String::from("a")"#.to_string(),
+                r#"String::from("\u{e9}t\u{e9}")"#.to_string(),
+            ]
+        }
+        _ => (),
+    }
+    match slice_element(ty) {
+        Some(element) if UNSIGNED.contains(&element) || SIGNED.contains(&element) => vec![
+            "Vec::new()".to_string(),
+            format!("vec![{}::MAX]", element),
+            format!("(0..16).collect::<Vec<{}>>()", element),
+        ],
+        Some(element) => vec![format!("Vec::<{}>::new()", element)],
+        None => vec![format!("<{}>::default()", ty)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn expression_result() {
+        check_assist(
+            generate_unsafe_differential_test,
+            r#"
+fn first(bytes: &[u8]) -> u8 {
+    let value: u8 = unsafe$0 { *bytes.get_unchecked(0) };
+    value
+}
+"#,
+            r#"
+fn first(bytes: &[u8]) -> u8 {
+    let value: u8 = unsafe { *bytes.get_unchecked(0) };
+    value
+}
+
+#[cfg(test)]
+mod first_unsafe_conversion_tests {
+    use super::*;
+
+    fn original(bytes: &[u8]) -> u8 {
+        unsafe { *bytes.get_unchecked(0) }
+    }
+
+    fn converted(bytes: &[u8]) -> u8 {
+        *bytes.get(0).unwrap()
+    }
+
+    // Run with `cargo miri test` to check the original for undefined behavior.
+    #[test]
+    fn converted_matches_original() {
+        for bytes in [Vec::new(), vec![u8::MAX], (0..16).collect::<Vec<u8>>()] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| converted(&bytes)));
+            // Inputs rejected by the safe version have no defined behavior in the original.
+            if let Ok(result) = result {
+                assert_eq!(original(&bytes), result);
+            }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn statements_with_local_used_afterwards() {
+        check_assist(
+            generate_unsafe_differential_test,
+            r#"
+fn decode(bytes: &[u8]) -> u16 {
+    unsafe$0 {
+        let int: u16 = ptr::read_unaligned(bytes.as_ptr() as *const u16);
+    }
+    int
+}
+"#,
+            r#"
+fn decode(bytes: &[u8]) -> u16 {
+    unsafe {
+        let int: u16 = ptr::read_unaligned(bytes.as_ptr() as *const u16);
+    }
+    int
+}
+
+#[cfg(test)]
+mod decode_unsafe_conversion_tests {
+    use super::*;
+
+    fn original(bytes: &[u8]) -> u16 {
+        unsafe {
+            let int: u16 = ptr::read_unaligned(bytes.as_ptr() as *const u16);
+            int
+        }
+    }
+
+    fn converted(bytes: &[u8]) -> u16 {
+        let int = u16::from_ne_bytes(bytes[..2].try_into().unwrap());
+        int
+    }
+
+    // Run with `cargo miri test` to check the original for undefined behavior.
+    #[test]
+    fn converted_matches_original() {
+        for bytes in [Vec::new(), vec![u8::MAX], (0..16).collect::<Vec<u8>>()] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| converted(&bytes)));
+            // Inputs rejected by the safe version have no defined behavior in the original.
+            if let Ok(result) = result {
+                assert_eq!(original(&bytes), result);
+            }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn assigned_local() {
+        check_assist(
+            generate_unsafe_differential_test,
+            r#"
+fn decode(code: u32) -> char {
+    let c: char;
+    unsafe$0 {
+        c = char::from_u32_unchecked(code);
+    }
+    c
+}
+"#,
+            r#"
+fn decode(code: u32) -> char {
+    let c: char;
+    unsafe {
+        c = char::from_u32_unchecked(code);
+    }
+    c
+}
+
+#[cfg(test)]
+mod decode_unsafe_conversion_tests {
+    use super::*;
+
+    fn original(code: u32) -> char {
+        let c;
+        unsafe {
+            c = char::from_u32_unchecked(code);
+            c
+        }
+    }
+
+    fn converted(code: u32) -> char {
+        let c;
+        c = char::from_u32(code).unwrap();
+        c
+    }
+
+    // Run with `cargo miri test` to check the original for undefined behavior.
+    #[test]
+    fn converted_matches_original() {
+        for code in [0, 1, u32::MAX] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| converted(code)));
+            // Inputs rejected by the safe version have no defined behavior in the original.
+            if let Ok(result) = result {
+                assert_eq!(original(code), result);
+            }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_generic_function() {
+        check_assist_not_applicable(
+            generate_unsafe_differential_test,
+            r#"
+fn first<T>(items: &[T]) -> &T {
+    unsafe$0 { items.get_unchecked(0) }
+}
+"#,
+        );
+    }
+}
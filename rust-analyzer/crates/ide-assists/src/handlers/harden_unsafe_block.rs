@@ -0,0 +1,379 @@
+use either::Either;
+use hir::PathResolution;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel},
+    AstNode, SyntaxNode, TextSize, T,
+};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    handlers::convert_unsafe_to_safe::{operation_preconditions, Precondition},
+    AssistId, AssistKind,
+};
+
+// Assist: harden_unsafe_block
+//
+// Keeps an unsafe block as it is, and inserts `debug_assert!`s checking the preconditions of
+// its unsafe operations right before them.
+//
+// ```
+// fn sum(a: *const u32, b: *const u32) -> u32 {
+//     unsafe$0 {
+//         let x = *a;
+//         let y = *b;
+//         x + y
+//     }
+// }
+// ```
+// ->
+// ```
+// fn sum(a: *const u32, b: *const u32) -> u32 {
+//     unsafe {
+//         debug_assert!(!a.is_null());
+//         debug_assert!(a.is_aligned());
+//         let x = *a;
+//         debug_assert!(!b.is_null());
+//         debug_assert!(b.is_aligned());
+//         let y = *b;
+//         x + y
+//     }
+// }
+// ```
+pub(crate) fn harden_unsafe_block(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let unsafe_kw = ctx.find_token_syntax_at_offset(T![unsafe])?;
+    let unsafe_expr = unsafe_kw.parent().and_then(ast::BlockExpr::cast)?;
+
+    let operations = ctx.sema.unsafe_operations(&ast::Expr::BlockExpr(unsafe_expr.clone()));
+    let mut insertions: Vec<(SyntaxNode, Vec<String>)> = Vec::new();
+    for operation in &operations {
+        let anchor = match assertion_anchor(&unsafe_expr, operation.expr.syntax()) {
+            Some(it) => it,
+            None => continue,
+        };
+        let position = insertions.iter().position(|(it, _)| *it == anchor);
+        let index = position.unwrap_or_else(|| {
+            insertions.push((anchor.clone(), Vec::new()));
+            insertions.len() - 1
+        });
+        let assertions = &mut insertions[index].1;
+        for precondition in operation_preconditions(&ctx.sema, &operation.expr) {
+            if !declared_before(ctx, anchor.text_range().start(), &precondition)
+                || is_asserted(&anchor, &precondition)
+            {
+                continue;
+            }
+            let assertion = precondition.assertion(true);
+            if !assertions.contains(&assertion) {
+                assertions.push(assertion);
+            }
+        }
+    }
+    insertions.retain(|(_, assertions)| !assertions.is_empty());
+    if insertions.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("harden_unsafe_block", AssistKind::RefactorRewrite),
+        "Insert debug assertions for the preconditions of the unsafe operations",
+        unsafe_kw.text_range(),
+        |builder| {
+            for (anchor, assertions) in insertions {
+                let indent = IndentLevel::from_node(&anchor);
+                let mut text = String::new();
+                for assertion in assertions {
+                    format_to!(text, "{}\n{}", assertion, indent);
+                }
+                builder.insert(anchor.text_range().start(), text);
+            }
+        },
+    )
+}
+
+/// The statement, or the tail expression, the assertions for `operation` go right before.
+///
+/// That's the innermost one containing the operation, unless it's the only expression of an
+/// unsafe block written on one line. The assertions then go before the unsafe block.
+fn assertion_anchor(unsafe_expr: &ast::BlockExpr, operation: &SyntaxNode) -> Option<SyntaxNode> {
+    let anchor = statement_of(operation)?;
+    let stmt_list = unsafe_expr.stmt_list()?;
+    let inline = anchor.parent().as_ref() == Some(stmt_list.syntax())
+        && stmt_list.statements().next().is_none()
+        && !stmt_list.syntax().text().contains_char('\n');
+    if !inline {
+        return Some(anchor);
+    }
+    // The checks would run when a closure is created instead of when it's called.
+    let crosses_closure = unsafe_expr
+        .syntax()
+        .ancestors()
+        .take_while(|it| !ast::StmtList::can_cast(it.kind()))
+        .any(|it| ast::ClosureExpr::can_cast(it.kind()));
+    match crosses_closure {
+        true => None,
+        false => statement_of(unsafe_expr.syntax()),
+    }
+}
+
+fn statement_of(node: &SyntaxNode) -> Option<SyntaxNode> {
+    node.ancestors()
+        .skip(1)
+        .find(|it| ast::StmtList::can_cast(it.kind()))
+        .and_then(|stmt_list| node.ancestors().find(|it| it.parent().as_ref() == Some(&stmt_list)))
+}
+
+/// Whether the locals the check uses are all declared before `offset`.
+fn declared_before(ctx: &AssistContext<'_>, offset: TextSize, precondition: &Precondition) -> bool {
+    let paths = precondition
+        .operands
+        .iter()
+        .flat_map(|operand| operand.syntax().descendants())
+        .filter_map(ast::Path::cast);
+    for path in paths {
+        let local = match ctx.sema.resolve_path(&path) {
+            Some(PathResolution::Local(local)) => local,
+            _ => continue,
+        };
+        let range = match local.source(ctx.db()).value {
+            Either::Left(pat) => pat.syntax().text_range(),
+            Either::Right(self_param) => self_param.syntax().text_range(),
+        };
+        if range.end() > offset {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether one of the statements before `anchor` asserts the check already.
+fn is_asserted(anchor: &SyntaxNode, precondition: &Precondition) -> bool {
+    let check = format!("({})", precondition.check);
+    anchor.siblings(syntax::Direction::Prev).skip(1).any(|stmt| {
+        stmt.descendants().filter_map(ast::MacroCall::cast).any(|call| {
+            let is_assert = call.path().map_or(false, |path| {
+                matches!(path.to_string().as_str(), "assert" | "debug_assert")
+            });
+            is_assert && call.token_tree().map_or(false, |tt| tt.to_string() == check)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn asserts_pointer_reads() {
+        check_assist(
+            harden_unsafe_block,
+            r#"
+fn first(values: *const u32) -> u32 {
+    let value = unsafe$0 { *values };
+    value
+}
+"#,
+            r#"
+fn first(values: *const u32) -> u32 {
+    debug_assert!(!values.is_null());
+    debug_assert!(values.is_aligned());
+    let value = unsafe { *values };
+    value
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn asserts_pointer_method_reads() {
+        check_assist(
+            harden_unsafe_block,
+            r#"
+//- minicore: ptr
+fn first(values: *const u32) -> u32 {
+    let value = unsafe$0 { values.read() };
+    value
+}
+"#,
+            r#"
+fn first(values: *const u32) -> u32 {
+    debug_assert!(!values.is_null());
+    debug_assert!(values.is_aligned());
+    let value = unsafe { values.read() };
+    value
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn asserts_before_each_operation() {
+        check_assist(
+            harden_unsafe_block,
+            r#"
+//- minicore: slice
+fn checksum(bytes: &[u8], index: usize) -> u8 {
+    unsafe$0 {
+        let next = index + 1;
+        let a = *bytes.get_unchecked(index);
+        let b = *bytes.get_unchecked(next);
+        a ^ b
+    }
+}
+"#,
+            r#"
+fn checksum(bytes: &[u8], index: usize) -> u8 {
+    unsafe {
+        let next = index + 1;
+        debug_assert!(index < bytes.len());
+        let a = *bytes.get_unchecked(index);
+        debug_assert!(next < bytes.len());
+        let b = *bytes.get_unchecked(next);
+        a ^ b
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn asserts_ranges_and_overlap() {
+        check_assist(
+            harden_unsafe_block,
+            r#"
+//- minicore: ptr
+use core::ptr;
+fn copy(src: *const u8, dst: *mut u8, count: usize) {
+    unsafe$0 {
+        ptr::copy_nonoverlapping(src, dst, count);
+    }
+}
+"#,
+            r#"
+use core::ptr;
+fn copy(src: *const u8, dst: *mut u8, count: usize) {
+    unsafe {
+        debug_assert!(src.wrapping_add(count) as usize <= dst as usize || dst.wrapping_add(count) as usize <= src as usize);
+        ptr::copy_nonoverlapping(src, dst, count);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn asserts_utf8_and_chars() {
+        check_assist(
+            harden_unsafe_block,
+            r#"
+//- minicore: char, str
+use core::{char, str};
+fn decode(bytes: &[u8], code: u32) -> (&str, char) {
+    unsafe$0 {
+        let text = str::from_utf8_unchecked(bytes);
+        let c = char::from_u32_unchecked(code);
+        (text, c)
+    }
+}
+"#,
+            r#"
+use core::{char, str};
+fn decode(bytes: &[u8], code: u32) -> (&str, char) {
+    unsafe {
+        debug_assert!(std::str::from_utf8(bytes).is_ok());
+        let text = str::from_utf8_unchecked(bytes);
+        debug_assert!(char::from_u32(code).is_some());
+        let c = char::from_u32_unchecked(code);
+        (text, c)
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_existing_assertions() {
+        check_assist(
+            harden_unsafe_block,
+            r#"
+fn first(values: *const u32) -> u32 {
+    unsafe$0 {
+        debug_assert!(!values.is_null());
+        *values
+    }
+}
+"#,
+            r#"
+fn first(values: *const u32) -> u32 {
+    unsafe {
+        debug_assert!(!values.is_null());
+        debug_assert!(values.is_aligned());
+        *values
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_asserted() {
+        check_assist_not_applicable(
+            harden_unsafe_block,
+            r#"
+fn first(values: *const u32) -> u32 {
+    unsafe$0 {
+        debug_assert!(!values.is_null());
+        debug_assert!(values.is_aligned());
+        *values
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_inline_closure_block() {
+        check_assist_not_applicable(
+            harden_unsafe_block,
+            r#"
+fn reader(values: *const u32) -> impl Fn() -> u32 {
+    move || unsafe$0 { *values }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_functions_named_like_std_ones() {
+        check_assist_not_applicable(
+            harden_unsafe_block,
+            r#"
+struct Cursor;
+impl Cursor {
+    unsafe fn get_unchecked(&self, index: usize) -> u32 { 0 }
+}
+unsafe fn read(cursor: Cursor) -> u32 { 0 }
+fn first(cursor: Cursor, index: usize) -> u32 {
+    unsafe$0 { cursor.get_unchecked(index) + read(cursor) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_unchecked_operations() {
+        check_assist_not_applicable(
+            harden_unsafe_block,
+            r#"
+extern "C" {
+    fn abort();
+}
+fn stop() {
+    unsafe$0 { abort() }
+}
+"#,
+        );
+    }
+}
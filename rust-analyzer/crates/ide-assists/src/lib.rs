@@ -74,10 +74,11 @@ pub mod utils;
 use hir::Semantics;
 use ide_db::{base_db::FileRange, RootDatabase};
 use syntax::TextRange;
+use text_edit::TextEdit;
 
 pub(crate) use crate::assist_context::{AssistContext, Assists};
 
-pub use assist_config::AssistConfig;
+pub use assist_config::{AssistConfig, UnsafeToSafeConfig, UnsafeToSafeErrorStyle};
 pub use ide_db::assists::{
     Assist, AssistId, AssistKind, AssistResolveStrategy, GroupLabel, SingleResolve,
 };
@@ -101,6 +102,37 @@ pub fn assists(
     acc.finish()
 }
 
+/// A rewrite of unsafe code into safe code, as the `convert_unsafe_to_safe` assist applies it.
+#[derive(Debug, Clone)]
+pub struct UnsafeToSafeConversion {
+    /// The rule of the rewrite.
+    pub pattern: handlers::convert_unsafe_to_safe::UnsafePattern,
+    /// The unsafe block, or the statement of an `unsafe fn` body, the rewrite replaces.
+    pub source: TextRange,
+    pub edit: TextEdit,
+}
+
+/// Computes only the rewrite of the `convert_unsafe_to_safe` assist at `range`: of the unsafe
+/// block when `range` is on the `unsafe` keyword, or of the unsafe operation under `range`.
+///
+/// Hover and the unsafe suggestion request need just this one rewrite, so this skips
+/// computing every other assist. Like the assist, it only returns a rewrite the function still
+/// type-checks with.
+pub fn unsafe_to_safe_conversion(
+    db: &RootDatabase,
+    config: &AssistConfig,
+    range: FileRange,
+) -> Option<UnsafeToSafeConversion> {
+    let sema = Semantics::new(db);
+    let ctx = AssistContext::new(sema, config, range);
+    let conversion = handlers::convert_unsafe_to_safe::safe_conversion(&ctx)?;
+    Some(UnsafeToSafeConversion {
+        pattern: conversion.pattern,
+        source: conversion.source,
+        edit: conversion.edit,
+    })
+}
+
 pub mod handlers {
     use crate::{AssistContext, Assists};
 
@@ -129,6 +161,7 @@ pub mod handlers {
     mod expand_glob_import;
     mod extract_function;
     mod extract_module;
+    mod extract_safe_wrapper;
     mod extract_struct_from_enum_variant;
     mod extract_type_alias;
     mod extract_variable;
@@ -153,9 +186,12 @@ pub mod handlers {
     mod generate_impl;
     mod generate_is_empty_from_len;
     mod generate_new;
+    mod generate_safe_ffi_wrappers;
     mod generate_setter;
+    mod generate_unsafe_differential_test;
     mod generate_delegate_methods;
     mod add_return_type;
+    mod harden_unsafe_block;
     mod inline_call;
     mod inline_local_variable;
     mod inline_type_alias;
@@ -246,6 +282,8 @@ pub mod handlers {
             generate_impl::generate_impl,
             generate_is_empty_from_len::generate_is_empty_from_len,
             generate_new::generate_new,
+            generate_safe_ffi_wrappers::generate_safe_ffi_wrappers,
+            harden_unsafe_block::harden_unsafe_block,
             inline_call::inline_call,
             inline_call::inline_into_callers,
             inline_local_variable::inline_local_variable,
@@ -307,11 +345,13 @@ pub mod handlers {
             //
             extract_variable::extract_variable,
             extract_function::extract_function,
+            extract_safe_wrapper::extract_safe_wrapper,
             extract_module::extract_module,
             //
             generate_getter::generate_getter,
             generate_getter::generate_getter_mut,
             generate_setter::generate_setter,
+            generate_unsafe_differential_test::generate_unsafe_differential_test,
             generate_delegate_methods::generate_delegate_methods,
             generate_deref::generate_deref,
             // Are you sure you want to add new assist here, and not to the
@@ -618,6 +618,30 @@ fn bar(name: i32) -> i32 {
     )
 }
 
+#[test]
+fn doctest_extract_safe_wrapper() {
+    check_doc_test(
+        "extract_safe_wrapper",
+        r#####"
+//- minicore: slice
+fn nth(bytes: &[u8], index: usize) -> u8 {
+    unsafe$0 { *bytes.get_unchecked(index) }
+}
+"#####,
+        r#####"
+fn nth(bytes: &[u8], index: usize) -> u8 {
+    checked_get(bytes, index)
+}
+
+fn $0checked_get(bytes: &[u8], index: usize) -> u8 {
+    assert!(index < bytes.len());
+    // SAFETY: the assertions above check that `index` is in bounds of `bytes`.
+    unsafe { *bytes.get_unchecked(index) }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_extract_struct_from_enum_variant() {
     check_doc_test(
@@ -1256,6 +1280,47 @@ impl<T: Clone> Ctx<T> {
     )
 }
 
+#[test]
+fn doctest_generate_safe_ffi_wrappers() {
+    check_doc_test(
+        "generate_safe_ffi_wrappers",
+        r#####"
+type c_char = i8;
+type c_int = i32;
+type ssize_t = isize;
+extern "C"$0 {
+    fn strlen(s: *const c_char) -> usize;
+    fn write(fd: c_int, buf: *const u8, count: usize) -> ssize_t;
+}
+"#####,
+        r#####"
+type c_char = i8;
+type c_int = i32;
+type ssize_t = isize;
+extern "C" {
+    fn strlen(s: *const c_char) -> usize;
+    fn write(fd: c_int, buf: *const u8, count: usize) -> ssize_t;
+}
+
+mod $0safe_ffi {
+    use super::*;
+
+    pub fn strlen(s: &std::ffi::CStr) -> usize {
+        unsafe { super::strlen(s.as_ptr()) }
+    }
+
+    pub fn write(fd: c_int, buf: &[u8]) -> std::io::Result<ssize_t> {
+        let ret = unsafe { super::write(fd, buf.as_ptr(), buf.len()) };
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(ret)
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_setter() {
     check_doc_test(
@@ -1279,6 +1344,79 @@ impl Person {
     )
 }
 
+#[test]
+fn doctest_generate_unsafe_differential_test() {
+    check_doc_test(
+        "generate_unsafe_differential_test",
+        r#####"
+fn first(bytes: &[u8]) -> u8 {
+    let value: u8 = unsafe$0 { *bytes.get_unchecked(0) };
+    value
+}
+"#####,
+        r#####"
+fn first(bytes: &[u8]) -> u8 {
+    let value: u8 = unsafe { *bytes.get_unchecked(0) };
+    value
+}
+
+#[cfg(test)]
+mod first_unsafe_conversion_tests {
+    use super::*;
+
+    fn original(bytes: &[u8]) -> u8 {
+        unsafe { *bytes.get_unchecked(0) }
+    }
+
+    fn converted(bytes: &[u8]) -> u8 {
+        *bytes.get(0).unwrap()
+    }
+
+    // Run with `cargo miri test` to check the original for undefined behavior.
+    #[test]
+    fn converted_matches_original() {
+        for bytes in [Vec::new(), vec![u8::MAX], (0..16).collect::<Vec<u8>>()] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| converted(&bytes)));
+            // Inputs rejected by the safe version have no defined behavior in the original.
+            if let Ok(result) = result {
+                assert_eq!(original(&bytes), result);
+            }
+        }
+    }
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_harden_unsafe_block() {
+    check_doc_test(
+        "harden_unsafe_block",
+        r#####"
+fn sum(a: *const u32, b: *const u32) -> u32 {
+    unsafe$0 {
+        let x = *a;
+        let y = *b;
+        x + y
+    }
+}
+"#####,
+        r#####"
+fn sum(a: *const u32, b: *const u32) -> u32 {
+    unsafe {
+        debug_assert!(!a.is_null());
+        debug_assert!(a.is_aligned());
+        let x = *a;
+        debug_assert!(!b.is_null());
+        debug_assert!(b.is_aligned());
+        let y = *b;
+        x + y
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_inline_call() {
     check_doc_test(
@@ -0,0 +1,118 @@
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: invalid-unchecked-argument
+//
+// This diagnostic is triggered when the constant argument of an unchecked constructor, like
+// `char::from_u32_unchecked(0xD800)`, breaks its precondition, which is undefined behavior.
+pub(crate) fn invalid_unchecked_argument(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::InvalidUncheckedArgument,
+) -> Diagnostic {
+    Diagnostic::new(
+        "invalid-unchecked-argument",
+        format!("{}, this call is undefined behavior", d.reason),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn surrogate_code_point() {
+        check_diagnostics(
+            r#"
+//- minicore: char
+fn main() {
+    let c = unsafe { core::char::from_u32_unchecked(0xD800) };
+                   //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: 0xD800 is a surrogate code point, not a `char`, this call is undefined behavior
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn code_point_past_the_last_char_through_a_const() {
+        check_diagnostics(
+            r#"
+//- minicore: char
+use core::char;
+
+const LAST: u32 = 0x10FFFF;
+
+fn main() {
+    let c = unsafe { char::from_u32_unchecked(LAST + 1) };
+                   //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: 0x110000 is past the last `char`, 0x10FFFF, this call is undefined behavior
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bytes_that_are_not_utf8() {
+        check_diagnostics(
+            r#"
+//- minicore: coerce_unsized, str
+fn main() {
+    let s = unsafe { core::str::from_utf8_unchecked(b"caf\xe9s") };
+                   //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: the bytes are not UTF-8: invalid utf-8 sequence of 1 bytes from index 3, this call is undefined behavior
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn interior_nul_of_a_c_string() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:std
+use std::ffi::CString;
+
+fn main() {
+    let s = unsafe { CString::from_vec_unchecked(b"a\0b".to_vec()) };
+                   //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: the bytes have a nul at index 1, this call is undefined behavior
+}
+//- /std.rs crate:std
+pub mod ffi {
+    pub struct CString;
+    impl CString {
+        pub unsafe fn from_vec_unchecked(v: Vec<u8>) -> CString { loop {} }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn valid_or_unknown_arguments() {
+        check_diagnostics(
+            r#"
+//- minicore: coerce_unsized, char, str
+use core::{char, str};
+
+fn main(i: u32, bytes: &[u8]) {
+    let a = unsafe { char::from_u32_unchecked(0x41) };
+    let b = unsafe { char::from_u32_unchecked(i) };
+    let c = unsafe { str::from_utf8_unchecked(b"caf\xc3\xa9") };
+    let d = unsafe { str::from_utf8_unchecked(bytes) };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn user_function_with_the_same_name() {
+        check_diagnostics(
+            r#"
+mod char {
+    pub unsafe fn from_u32_unchecked(i: u32) -> char { loop {} }
+}
+
+fn main() {
+    let c = unsafe { char::from_u32_unchecked(0xD800) };
+}
+"#,
+        );
+    }
+}
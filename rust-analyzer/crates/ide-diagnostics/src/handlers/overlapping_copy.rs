@@ -0,0 +1,133 @@
+use hir::db::AstDatabase;
+use ide_db::source_change::SourceChange;
+use syntax::{ast, AstNode};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: overlapping-copy
+//
+// This diagnostic is triggered when the source and destination ranges of a `copy_nonoverlapping`
+// call provably overlap, which is undefined behavior.
+pub(crate) fn overlapping_copy(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::OverlappingCopy,
+) -> Diagnostic {
+    Diagnostic::new(
+        "overlapping-copy",
+        "the source and destination of `copy_nonoverlapping` overlap, this is undefined behavior",
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::OverlappingCopy) -> Option<Vec<Assist>> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    let call = match d.expr.value.to_node(&root) {
+        ast::Expr::CallExpr(it) => it,
+        _ => return None,
+    };
+    let name = match call.expr()? {
+        ast::Expr::PathExpr(it) => it.path()?.segment()?.name_ref()?,
+        _ => return None,
+    };
+    let trigger_range = call.syntax().text_range();
+
+    // `copy` has the same signature and allows the ranges to overlap
+    let edit = TextEdit::replace(name.syntax().text_range(), "copy".to_string());
+    let source_change =
+        SourceChange::from_text_edit(d.expr.file_id.original_file(ctx.sema.db), edit);
+
+    Some(vec![fix(
+        "use_copy",
+        "Use `copy`, which allows overlapping ranges",
+        source_change,
+        trigger_range,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn overlapping_ranges_of_an_array() {
+        check_diagnostics(
+            r#"
+unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize) {}
+
+fn main() {
+    let mut buf = [0u8; 8];
+    unsafe { copy_nonoverlapping(buf.as_ptr(), buf.as_mut_ptr().add(2), 4) };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 error: the source and destination of `copy_nonoverlapping` overlap, this is undefined behavior
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn overlapping_ranges_through_locals() {
+        check_diagnostics(
+            r#"
+unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize) {}
+
+fn main() {
+    let mut buf = [0u8; 8];
+    let len = 3;
+    let src = buf[1..].as_ptr();
+    let dst = buf[2..].as_mut_ptr();
+    unsafe { copy_nonoverlapping(src, dst, len) };
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 error: the source and destination of `copy_nonoverlapping` overlap, this is undefined behavior
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_for_disjoint_ranges() {
+        check_diagnostics(
+            r#"
+unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize) {}
+
+fn main(i: usize) {
+    let src = [0u8; 8];
+    let mut dst = [0u8; 8];
+    let mut buf = [0u8; 8];
+    unsafe {
+        copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr(), 8);
+        copy_nonoverlapping(buf.as_ptr(), buf.as_mut_ptr().add(4), 4);
+        copy_nonoverlapping(buf.as_ptr(), buf.as_mut_ptr().add(i), 4);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_with_copy() {
+        check_fix(
+            r#"
+mod ptr {
+    pub unsafe fn copy<T>(src: *const T, dst: *mut T, count: usize) {}
+    pub unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize) {}
+}
+
+fn main() {
+    let mut buf = [0u8; 8];
+    unsafe { ptr::copy_nonoverlapping$0(buf.as_ptr(), buf.as_mut_ptr().add(2), 4) };
+}
+"#,
+            r#"
+mod ptr {
+    pub unsafe fn copy<T>(src: *const T, dst: *mut T, count: usize) {}
+    pub unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize) {}
+}
+
+fn main() {
+    let mut buf = [0u8; 8];
+    unsafe { ptr::copy(buf.as_ptr(), buf.as_mut_ptr().add(2), 4) };
+}
+"#,
+        );
+    }
+}
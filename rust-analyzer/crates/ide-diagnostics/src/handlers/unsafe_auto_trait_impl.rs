@@ -0,0 +1,466 @@
+use hir::{
+    db::AstDatabase, AutoTraitBlocker, AutoTraitBlockerKind, FieldSource, HasSource, HirDisplay,
+};
+use ide_db::{base_db::FileId, source_change::SourceChange, RootDatabase};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasGenericParams},
+    AstNode, SyntaxKind, TextRange,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unsafe-auto-trait-impl
+//
+// This diagnostic is triggered by an `unsafe impl Send` or `unsafe impl Sync` for a type whose
+// fields don't all implement the trait, and lists those fields with the reason.
+pub(crate) fn unsafe_auto_trait_impl(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnsafeAutoTraitImpl,
+) -> Diagnostic {
+    let db = ctx.sema.db;
+    let trait_name = d.trait_.name(db);
+    let fields = d.blockers.iter().map(|blocker| {
+        format!(
+            "`{}: {}` ({})",
+            blocker.field.name(db),
+            blocker.ty.display(db),
+            auto_trait_blocker_reason(db, blocker, &trait_name.to_string())
+        )
+    });
+    let message = format!(
+        "`unsafe impl {}` overrides fields that are not `{}`: {}",
+        trait_name,
+        trait_name,
+        fields.format(", ")
+    );
+    Diagnostic::new(
+        "unsafe-auto-trait-impl",
+        message,
+        ctx.sema.diagnostics_display_range(d.impl_.clone().map(|it| it.into())).range,
+    )
+    .severity(Severity::WeakWarning)
+    .with_fixes(fixes(ctx, d))
+}
+
+/// Why the culprit of `blocker` doesn't implement `trait_name`.
+pub fn auto_trait_blocker_reason(
+    db: &RootDatabase,
+    blocker: &AutoTraitBlocker,
+    trait_name: &str,
+) -> String {
+    let culprit = blocker.culprit.display(db);
+    match blocker.kind {
+        AutoTraitBlockerKind::RawPointer => format!("`{}` is a raw pointer", culprit),
+        AutoTraitBlockerKind::Rc { .. } => {
+            format!("`{}` counts references without atomics", culprit)
+        }
+        AutoTraitBlockerKind::InteriorMutability => {
+            format!("`{}` is mutable through shared references without synchronization", culprit)
+        }
+        AutoTraitBlockerKind::TypeParam => {
+            format!("`{}` is not required to be `{}`", culprit, trait_name)
+        }
+        AutoTraitBlockerKind::Other => format!("`{}` is not `{}`", culprit, trait_name),
+    }
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::UnsafeAutoTraitImpl) -> Option<Vec<Assist>> {
+    if d.impl_.file_id.is_macro() {
+        return None;
+    }
+    let file_id = d.impl_.file_id.original_file(ctx.sema.db);
+    let root = ctx.sema.db.parse_or_expand(d.impl_.file_id)?;
+    let impl_ = d.impl_.value.to_node(&root);
+    let trait_name = d.trait_.name(ctx.sema.db).to_string();
+    let fields: Vec<(&AutoTraitBlocker, ast::Type)> = d
+        .blockers
+        .iter()
+        .map(|blocker| Some((blocker, field_type(ctx, file_id, blocker)?)))
+        .collect::<Option<_>>()?;
+
+    let mut fixes = Vec::new();
+    fixes.extend(use_arc(&impl_, &fields, file_id));
+    fixes.extend(use_atomic_ptr(&impl_, &fields, file_id));
+    fixes.extend(use_non_null(ctx, &impl_, &fields, file_id, &trait_name));
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(fixes)
+    }
+}
+
+/// The type of the field of `blocker`, if it's written in `file_id`.
+fn field_type(
+    ctx: &DiagnosticsContext<'_>,
+    file_id: FileId,
+    blocker: &AutoTraitBlocker,
+) -> Option<ast::Type> {
+    let source = blocker.field.source(ctx.sema.db)?;
+    if source.file_id.is_macro() || source.file_id.original_file(ctx.sema.db) != file_id {
+        return None;
+    }
+    match source.value {
+        FieldSource::Named(it) => it.ty(),
+        FieldSource::Pos(it) => it.ty(),
+    }
+}
+
+/// `Arc` counts references with atomics, and is `Send` and `Sync` when the value is both.
+fn use_arc(
+    impl_: &ast::Impl,
+    fields: &[(&AutoTraitBlocker, ast::Type)],
+    file_id: FileId,
+) -> Option<Assist> {
+    let mut edit = TextEdit::builder();
+    let mut replaced = false;
+    for (blocker, ty) in fields {
+        if !matches!(blocker.kind, AutoTraitBlockerKind::Rc { .. }) {
+            continue;
+        }
+        for path in ty.syntax().descendants().filter_map(ast::PathType::cast) {
+            let path = path.path()?;
+            let name = path.segment()?.name_ref()?;
+            let replacement = match name.text().as_str() {
+                "Rc" => "std::sync::Arc",
+                "Weak" => "std::sync::Weak",
+                _ => continue,
+            };
+            let range = TextRange::new(
+                path.syntax().text_range().start(),
+                name.syntax().text_range().end(),
+            );
+            edit.replace(range, replacement.to_string());
+            replaced = true;
+        }
+    }
+    if !replaced {
+        return None;
+    }
+    let arc_suffices = fields
+        .iter()
+        .all(|(it, _)| matches!(it.kind, AutoTraitBlockerKind::Rc { arc_is_send_sync: true }));
+    let label = if arc_suffices {
+        edit.delete(impl_range(impl_));
+        "Use `Arc` instead of `Rc` and remove the `unsafe impl`"
+    } else {
+        "Use `Arc` instead of `Rc`"
+    };
+    let source_change = SourceChange::from_text_edit(file_id, edit.finish());
+    Some(fix("use_arc", label, source_change, impl_.syntax().text_range()))
+}
+
+/// `AtomicPtr` is `Send` and `Sync` whatever it points to.
+fn use_atomic_ptr(
+    impl_: &ast::Impl,
+    fields: &[(&AutoTraitBlocker, ast::Type)],
+    file_id: FileId,
+) -> Option<Assist> {
+    let mut edit = TextEdit::builder();
+    let mut replaced = 0;
+    for (_, ty) in fields {
+        if let ast::Type::PtrType(ptr) = ty {
+            let pointee = ptr.ty()?;
+            let replacement = format!("std::sync::atomic::AtomicPtr<{}>", pointee);
+            edit.replace(ty.syntax().text_range(), replacement);
+            replaced += 1;
+        }
+    }
+    if replaced == 0 {
+        return None;
+    }
+    let label = if replaced == fields.len() {
+        edit.delete(impl_range(impl_));
+        "Use `AtomicPtr` and remove the `unsafe impl`"
+    } else {
+        "Use `AtomicPtr`"
+    };
+    let source_change = SourceChange::from_text_edit(file_id, edit.finish());
+    Some(fix("use_atomic_ptr", label, source_change, impl_.syntax().text_range()))
+}
+
+/// A `NonNull` with a `PhantomData` of its pointee owns the pointee, which then has to implement
+/// the trait for the impl to hold.
+fn use_non_null(
+    ctx: &DiagnosticsContext<'_>,
+    impl_: &ast::Impl,
+    fields: &[(&AutoTraitBlocker, ast::Type)],
+    file_id: FileId,
+    trait_name: &str,
+) -> Option<Assist> {
+    let mut edit = TextEdit::builder();
+    let mut pointees = Vec::new();
+    let mut field_list = None;
+    for (_, ty) in fields {
+        let ptr = match ty {
+            ast::Type::PtrType(it) => it,
+            _ => continue,
+        };
+        let pointee = ptr.ty()?;
+        let list = ty.syntax().ancestors().find_map(ast::FieldList::cast)?;
+        let is_struct = list.syntax().parent().map_or(false, |it| ast::Struct::can_cast(it.kind()));
+        if !is_struct || field_list.as_ref().map_or(false, |it| *it != list) {
+            return None;
+        }
+        field_list = Some(list);
+        edit.replace(ty.syntax().text_range(), format!("std::ptr::NonNull<{}>", pointee));
+        if !pointees.iter().any(|it: &ast::Type| it.to_string() == pointee.to_string()) {
+            pointees.push(pointee);
+        }
+    }
+    let field_list = field_list?;
+
+    let owned = match pointees.as_slice() {
+        [pointee] => pointee.to_string(),
+        _ => format!("({})", pointees.iter().format(", ")),
+    };
+    match field_list {
+        ast::FieldList::RecordFieldList(list) => {
+            let last = list.fields().last()?;
+            let indent = IndentLevel::from_node(last.syntax());
+            let field = format!("\n{}_marker: std::marker::PhantomData<{}>,", indent, owned);
+            let (end, field) = match last.syntax().next_sibling_or_token() {
+                Some(it) if it.kind() == SyntaxKind::COMMA => (it.text_range().end(), field),
+                _ => (last.syntax().text_range().end(), format!(",{}", field)),
+            };
+            edit.insert(end, field);
+        }
+        ast::FieldList::TupleFieldList(list) => {
+            let last = list.fields().last()?;
+            let field = format!(", std::marker::PhantomData<{}>", owned);
+            edit.insert(last.syntax().text_range().end(), field);
+        }
+    }
+
+    // The impl only holds for pointees implementing the trait.
+    let bounds: Vec<String> = pointees
+        .iter()
+        .filter(|it| {
+            ctx.sema.resolve_type(it).map_or(false, |it| it.as_type_param(ctx.sema.db).is_some())
+        })
+        .map(|it| format!("{}: {}", it, trait_name))
+        .collect();
+    if !bounds.is_empty() {
+        let mut bounds_text = String::new();
+        match impl_.where_clause() {
+            Some(where_clause) => {
+                let end = where_clause.syntax().text_range().end();
+                format_to!(bounds_text, ", {}", bounds.join(", "));
+                edit.insert(end, bounds_text);
+            }
+            None => {
+                let end = impl_.self_ty()?.syntax().text_range().end();
+                format_to!(bounds_text, " where {}", bounds.join(", "));
+                edit.insert(end, bounds_text);
+            }
+        }
+    }
+
+    let source_change = SourceChange::from_text_edit(file_id, edit.finish());
+    Some(fix(
+        "use_non_null",
+        "Use `NonNull` and `PhantomData` to own the pointee",
+        source_change,
+        impl_.syntax().text_range(),
+    ))
+}
+
+/// The range of `impl_` with the whitespace before it.
+fn impl_range(impl_: &ast::Impl) -> TextRange {
+    let range = impl_.syntax().text_range();
+    match impl_.syntax().prev_sibling_or_token() {
+        Some(it) if it.kind() == SyntaxKind::WHITESPACE => it.text_range().cover(range),
+        _ => range,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix, check_fixes};
+
+    const SEND: &str = r#"
+#[lang = "send"]
+unsafe auto trait Send {}
+#[lang = "sync"]
+unsafe auto trait Sync {}
+impl<T: ?Sized> !Send for *const T {}
+impl<T: ?Sized> !Send for *mut T {}
+impl<T: ?Sized> !Sync for *const T {}
+impl<T: ?Sized> !Sync for *mut T {}
+mod marker {
+    #[lang = "phantom_data"]
+    pub struct PhantomData<T: ?Sized>;
+}
+mod cell {
+    pub struct Cell<T>(T);
+    impl<T> !super::Sync for Cell<T> {}
+}
+mod rc {
+    pub struct Rc<T>(T);
+    impl<T> !super::Send for Rc<T> {}
+    impl<T> !super::Sync for Rc<T> {}
+}
+mod sync {
+    pub struct Arc<T>(T);
+}
+"#;
+
+    fn with_std(fixture: &str) -> String {
+        format!("{}{}", SEND, fixture)
+    }
+
+    #[test]
+    fn lists_fields_with_reasons() {
+        check_diagnostics(&with_std(
+            r#"
+struct Cache<T> {
+    data: *mut u8,
+    shared: rc::Rc<u32>,
+    list: Vec<rc::Rc<u32>>,
+    value: T,
+    len: usize,
+}
+struct Vec<T>(T);
+  unsafe impl<T> Send for Cache<T> {}
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 weak: `unsafe impl Send` overrides fields that are not `Send`: `data: *mut u8` (`*mut u8` is a raw pointer), `shared: Rc<u32>` (`Rc<u32>` counts references without atomics), `list: Vec<Rc<u32>>` (`Rc<u32>` counts references without atomics), `value: T` (`T` is not required to be `Send`)
+"#,
+        ));
+    }
+
+    #[test]
+    fn lists_interior_mutability_for_sync() {
+        check_diagnostics(&with_std(
+            r#"
+struct Counter {
+    count: cell::Cell<u32>,
+    limit: u32,
+}
+  unsafe impl Sync for Counter {}
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ weak: `unsafe impl Sync` overrides fields that are not `Sync`: `count: Cell<u32>` (`Cell<u32>` is mutable through shared references without synchronization)
+unsafe impl Send for Counter {}
+"#,
+        ));
+    }
+
+    #[test]
+    fn no_diagnostic_when_fields_implement_trait() {
+        check_diagnostics(&with_std(
+            r#"
+struct Id(u32);
+unsafe impl Send for Id {}
+struct Shared<T>(sync::Arc<T>);
+unsafe impl<T: Send> Send for Shared<T> {}
+"#,
+        ));
+    }
+
+    #[test]
+    fn no_diagnostic_for_owned_pointee() {
+        check_diagnostics(&with_std(
+            r#"
+struct Unique<T> {
+    ptr: *const T,
+    _marker: marker::PhantomData<T>,
+}
+unsafe impl<T: Send> Send for Unique<T> {}
+"#,
+        ));
+    }
+
+    #[test]
+    fn enum_variant_fields() {
+        check_diagnostics(&with_std(
+            r#"
+enum Handle {
+    Raw(*mut u8),
+    Closed,
+}
+  unsafe impl Send for Handle {}
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 weak: `unsafe impl Send` overrides fields that are not `Send`: `0: *mut u8` (`*mut u8` is a raw pointer)
+"#,
+        ));
+    }
+
+    #[test]
+    fn use_arc() {
+        check_fix(
+            &with_std(
+                r#"
+struct Cache {
+    shared: rc::Rc<u32>,
+}
+unsafe impl Send for Cache$0 {}
+"#,
+            ),
+            &with_std(
+                r#"
+struct Cache {
+    shared: std::sync::Arc<u32>,
+}
+"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn use_atomic_ptr_and_non_null() {
+        check_fixes(
+            &with_std(
+                r#"
+struct Buffer<T> {
+    data: *mut T,
+    len: usize,
+}
+unsafe impl<T> Send for Buffer<T$0> {}
+"#,
+            ),
+            vec![
+                &with_std(
+                    r#"
+struct Buffer<T> {
+    data: std::sync::atomic::AtomicPtr<T>,
+    len: usize,
+}
+"#,
+                ),
+                &with_std(
+                    r#"
+struct Buffer<T> {
+    data: std::ptr::NonNull<T>,
+    len: usize,
+    _marker: std::marker::PhantomData<T>,
+}
+unsafe impl<T> Send for Buffer<T> where T: Send {}
+"#,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn use_non_null_in_tuple_struct_with_where_clause() {
+        check_fixes(
+            &with_std(
+                r#"
+struct Pair<A, B>(*const A, *const B);
+unsafe impl<A, B> Sync for Pair<A, B>$0 where A: Copy {}
+"#,
+            ),
+            vec![
+                &with_std(
+                    r#"
+struct Pair<A, B>(std::sync::atomic::AtomicPtr<A>, std::sync::atomic::AtomicPtr<B>);
+"#,
+                ),
+                &with_std(
+                    r#"
+struct Pair<A, B>(std::ptr::NonNull<A>, std::ptr::NonNull<B>, std::marker::PhantomData<(A, B)>);
+unsafe impl<A, B> Sync for Pair<A, B> where A: Copy, A: Sync, B: Sync {}
+"#,
+                ),
+            ],
+        );
+    }
+}
@@ -7,7 +7,7 @@ use ide_db::{
     search::FileReference,
     FxIndexMap, RootDatabase,
 };
-use syntax::{ast, AstNode, SyntaxKind::IDENT, TextRange};
+use syntax::{ast, AstNode, SyntaxKind::IDENT, SyntaxNode, TextRange};
 
 use crate::{goto_definition, FilePosition, NavigationTarget, RangeInfo, TryToNav};
 
@@ -88,39 +88,43 @@ pub(crate) fn outgoing_calls(db: &RootDatabase, position: FilePosition) -> Optio
         .into_iter()
         .filter_map(|it| it.parent_ancestors().nth(1).and_then(ast::Item::cast))
         .filter_map(|item| match item {
-            ast::Item::Const(c) => c.body().map(|it| it.syntax().descendants()),
-            ast::Item::Fn(f) => f.body().map(|it| it.syntax().descendants()),
-            ast::Item::Static(s) => s.body().map(|it| it.syntax().descendants()),
+            ast::Item::Const(c) => c.body().map(|it| it.syntax().clone()),
+            ast::Item::Fn(f) => f.body().map(|it| it.syntax().clone()),
+            ast::Item::Static(s) => s.body().map(|it| it.syntax().clone()),
             _ => None,
         })
-        .flatten()
-        .filter_map(ast::CallableExpr::cast)
-        .filter_map(|call_node| {
-            let (nav_target, range) = match call_node {
-                ast::CallableExpr::Call(call) => {
-                    let expr = call.expr()?;
-                    let callable = sema.type_of_expr(&expr)?.original.as_callable(db)?;
-                    match callable.kind() {
-                        hir::CallableKind::Function(it) => {
-                            let range = expr.syntax().text_range();
-                            it.try_to_nav(db).zip(Some(range))
-                        }
-                        _ => None,
-                    }
-                }
-                ast::CallableExpr::MethodCall(expr) => {
-                    let range = expr.name_ref()?.syntax().text_range();
-                    let function = sema.resolve_method_call(&expr)?;
-                    function.try_to_nav(db).zip(Some(range))
-                }
-            }?;
-            Some((nav_target, range))
-        })
+        .flat_map(|body| calls_in_body(&sema, &body))
+        .filter_map(|(function, range)| Some((function.try_to_nav(db)?, range)))
         .for_each(|(nav, range)| calls.add(nav, range));
 
     Some(calls.into_items())
 }
 
+/// Returns the functions called from within `body`, together with the range of the call site.
+pub(crate) fn calls_in_body(
+    sema: &Semantics<'_, RootDatabase>,
+    body: &SyntaxNode,
+) -> Vec<(hir::Function, TextRange)> {
+    body.descendants()
+        .filter_map(ast::CallableExpr::cast)
+        .filter_map(|call_node| match call_node {
+            ast::CallableExpr::Call(call) => {
+                let expr = call.expr()?;
+                let callable = sema.type_of_expr(&expr)?.original.as_callable(sema.db)?;
+                match callable.kind() {
+                    hir::CallableKind::Function(it) => Some((it, expr.syntax().text_range())),
+                    _ => None,
+                }
+            }
+            ast::CallableExpr::MethodCall(expr) => {
+                let range = expr.name_ref()?.syntax().text_range();
+                let function = sema.resolve_method_call(&expr)?;
+                Some((function, range))
+            }
+        })
+        .collect()
+}
+
 #[derive(Default)]
 struct CallLocations {
     funcs: FxIndexMap<NavigationTarget, Vec<TextRange>>,
@@ -7,6 +7,7 @@ use std::iter;
 
 use either::Either;
 use hir::{HasSource, Semantics};
+use ide_assists::UnsafeToSafeConfig;
 use ide_db::{
     base_db::FileRange,
     defs::{Definition, IdentClass, OperatorClass},
@@ -28,6 +29,9 @@ pub struct HoverConfig {
     pub links_in_hover: bool,
     pub documentation: Option<HoverDocFormat>,
     pub keywords: bool,
+    pub unsafe_to_safe: UnsafeToSafeConfig,
+    /// Whether the hover of `unsafe` shows the runtime overhead of the safe version.
+    pub unsafe_to_safe_overhead: bool,
 }
 
 impl HoverConfig {
@@ -158,6 +162,16 @@ pub(crate) fn hover(
             acc
         });
 
+    // Unsafe operations with a safe version show it after what the hovered token is
+    let result = match (result, render::unsafe_operation(sema, config, &original_token)) {
+        (Some(mut res), Some(unsafe_operation)) => {
+            res.actions.extend(unsafe_operation.actions);
+            res.markup = Markup::from(format!("{}\n---\n{}", res.markup, unsafe_operation.markup));
+            Some(res)
+        }
+        (result, unsafe_operation) => result.or(unsafe_operation),
+    };
+
     if result.is_none() {
         // fallbacks, show keywords or types
 
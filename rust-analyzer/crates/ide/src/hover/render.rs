@@ -12,27 +12,21 @@ use ide_db::{
     RootDatabase,
 };
 
-use ide_assists::{
-    handlers::convert_unsafe_to_safe::{UnsafePattern, generate_safevec_format, generate_resizevec_format, 
-        generate_copywithin_format, generate_let_get_mut, generate_get_mut, generate_copy_from_slice_format, check_convert_type, 
-        generate_cstring_new_format, generate_bytes_len_format, generate_from_utf8, generate_let_from_utf8, generate_from_transmute,
-        generate_bytes_to_convert, generate_from_u32, generate_let_from_u32, generate_from_utf8_expr_stmt, generate_get_mut_expr,
-        generate_from_u32_expr_stmt, generate_get_prefix_mut_expr}
-};
-
 use itertools::Itertools;
 use stdx::format_to;
 use syntax::{
-    algo, ast::{self, MethodCallExpr, CallExpr, BlockExpr}, match_ast, AstNode, Direction,
-    SyntaxKind::{LET_EXPR, LET_STMT, UNSAFE_KW, STMT_LIST, BIN_EXPR, EXPR_STMT, PREFIX_EXPR},
-    SyntaxToken, T, SyntaxNode,
+    algo, ast, match_ast, AstNode, Direction,
+    SyntaxKind::{LET_EXPR, LET_STMT, UNSAFE_KW},
+    SyntaxToken, T,
 };
 
 use crate::{
     doc_links::{remove_links, rewrite_links},
     hover::walk_and_push_ty,
     markdown_remove::remove_markdown,
-    HoverAction, HoverConfig, HoverResult, Markup,
+    unsafe_classification,
+    unsafe_suggestions::{self, hover_assist_config},
+    HoverAction, HoverConfig, HoverResult, Markup, UnsafeClassification, UnsafeSuggestion,
 };
 
 pub(super) fn type_info(
@@ -235,646 +229,117 @@ pub(super) fn deref_expr(
     Some(res)
 }
 
-fn generate_description() -> String{
-
-    return "Code Suggestion: translating unsafe to safe code".to_string();
-}
-
-fn generate_original() -> String{
-
-    return "Original Code: \n\n".to_string();
-}
-
-fn generate_modify() -> String{
-
-    return "Modified Code: \n\n".to_string();
-}
-
-fn format_suggestion_unitialized_vec(mcall: MethodCallExpr, unsafe_expr: &BlockExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    let original = generate_original();
-
-    us_docs.push_str(&original);
-
-    let mut safe_vec = String::new();
-
-    let mut backward_list = unsafe_expr.syntax().siblings(Direction::Prev);
-
-    if unsafe_expr.syntax().parent()?.kind() != STMT_LIST {
-        backward_list = unsafe_expr.syntax().parent()?.siblings(Direction::Prev);
+fn unsafe_suggestion(
+    config: &HoverConfig,
+    suggestion: &UnsafeSuggestion,
+    classification: Option<&UnsafeClassification>,
+    actions: Vec<HoverAction>,
+) -> Option<HoverResult> {
+    let mut docs = String::new();
+    format_to!(docs, "Rule: `{}`", suggestion.rule);
+    if let Some(overhead) = suggestion.overhead.filter(|_| config.unsafe_to_safe_overhead) {
+        format_to!(docs, ", Runtime Overhead: {}", overhead);
     }
-
-
-    for iter in backward_list {
-
-        if iter.to_string().contains(&UnsafePattern::SetVecCapacity.to_string()) && iter.to_string().contains(&mcall.receiver()?.to_string()) {
-
-            let let_expr = ast::LetStmt::cast(iter)?;
-
-            format_to!(us_docs, "**```---```** **~~```{}```~~**", let_expr.to_string());
-            // format_to!(us_docs, "```---``` ~~```      {}```~~", let_expr.to_string());
-
-            us_docs.push('\n');
-            us_docs.push('\n');
-
-            format_to!(safe_vec, "**```+++```** **```{} [Runtime Overhead: -0.05%]```**", generate_safevec_format(&mcall)?.to_string());
-            
-            break;
+    docs.push_str("\n\n```diff\n");
+    for edit in &suggestion.edits {
+        for line in edit.original.lines() {
+            format_to!(docs, "-{}\n", line);
         }
-
-        if iter.to_string().contains(&UnsafePattern::ReserveVec.to_string()) && iter.to_string().contains(&mcall.receiver()?.to_string()) {
-
-            let expr_stmt = ast::ExprStmt::cast(iter)?;
-
-            format_to!(us_docs, "**```---```** **~~```{}```~~**", expr_stmt.to_string());
-            // format_to!(us_docs, "```---``` ~~```      {}```~~", let_expr.to_string());
-
-            us_docs.push('\n');
-            us_docs.push('\n');
-
-            format_to!(safe_vec, "**```+++```** **```{} [Runtime Overhead: 12.01%]```**", generate_resizevec_format(&mcall)?.to_string());
-
-            break;
+        for line in edit.replacement.lines() {
+            format_to!(docs, "+{}\n", line);
         }
     }
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut unsafe_vec = String::new();
-    // format_to!(unsafe_vec, "```---``` ~~```      unsafe {{ {} }};```~~", mcall.to_string());
-    format_to!(unsafe_vec, "**```---```** **~~```unsafe {{ {} }};```~~**", mcall.to_string());
-    us_docs.push_str(&unsafe_vec);
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let modify = generate_modify();
-
-    us_docs.push_str(&modify);
-
-    us_docs.push_str(&safe_vec);
-
-    return Some(us_docs.to_string());
-
-
-}
-
-fn display_suggestion_uninitialized_vec(target_expr: &SyntaxNode, unsafe_expr: &BlockExpr, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::MethodCallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_unitialized_vec(mcall, &unsafe_expr)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_ptr_copy(mcall: &CallExpr, unsafe_expr: &BlockExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    let original = generate_original();
-
-    us_docs.push_str(&original);
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", mcall.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_version = String::new();
-
-    format_to!(safe_version, "**```+++```** **```{} [Runtime Overhead: -26.62%]```**", generate_copywithin_format(&mcall, &unsafe_expr)?);
-
-    let modify = generate_modify();
-
-    us_docs.push_str(&modify);
-
-    us_docs.push_str(&safe_version);
-
-    return Some(us_docs.to_string());
-
-}
-
-fn display_suggestion_ptr_copy(target_expr: &SyntaxNode, unsafe_expr: &BlockExpr, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_ptr_copy(&mcall, &unsafe_expr)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_get_uncheck_mut(mcall: MethodCallExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    if mcall.syntax().parent()?.kind() == PREFIX_EXPR {
-
-        let target_expr = &mcall;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-
-        us_docs.push('\n');
-        us_docs.push('\n');
-
-        let mut safe_version = String::new();
-
-        format_to!(safe_version, "**```+++```** **```{} [Runtime Overhead: 7.58%]```**", generate_get_prefix_mut_expr(&mcall)?);
-
-        us_docs.push_str(&safe_version);
-
-        return Some(us_docs.to_string());
-    }
-
-    if mcall.syntax().parent()?.kind() == STMT_LIST{
-
-        let target_expr = &mcall;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-
-        us_docs.push('\n');
-        us_docs.push('\n');
-
-        let mut safe_version = String::new();
-
-        format_to!(safe_version, "**```+++```** **```{} [Runtime Overhead: 7.58%]```**", generate_get_mut_expr(&mcall)?);
-
-        us_docs.push_str(&safe_version);
-
-        return Some(us_docs.to_string());
-    }
-
-    if mcall.syntax().parent()?.kind() == BIN_EXPR {
-
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-
-        us_docs.push('\n');
-        us_docs.push('\n');
-
-        let mut safe_version = String::new();
-
-        format_to!(safe_version, "**```+++```** **```{} [Runtime Overhead: 7.58%]```**", generate_get_mut(&mcall, &target_expr)?);
-
-        us_docs.push_str(&safe_version);
-
-        return Some(us_docs.to_string());
-
+    docs.push_str("```\n\n");
+    docs.push_str(suggestion.explanation);
+    if let Some(classification) = classification.filter(|it| !it.reasons.is_empty()) {
+        docs.push_str("\n\n");
+        push_unsafe_reasons(&mut docs, classification);
     }
 
-    let let_expr = mcall.syntax().parent().and_then(ast::LetStmt::cast)?;
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", let_expr.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_version = String::new();
-
-    format_to!(safe_version, "**```+++```** **```{} [Runtime Overhead: 7.58%]```**", generate_let_get_mut(&mcall, &let_expr)?);
-
-    us_docs.push_str(&safe_version);
-
-    return Some(us_docs.to_string());
-
-}
-
-fn display_suggestion_get_uncheck_mut(target_expr: &SyntaxNode, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::MethodCallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_get_uncheck_mut(mcall)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_ptr_copy_nonoverlapping(mcall: CallExpr, unsafe_expr: &BlockExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", mcall.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_version = String::new();
-
-    format_to!(safe_version, "**```+++```** **```{} [Runtime Overhead: 0.19%]```**", generate_copy_from_slice_format(&mcall, &unsafe_expr)?);
-
-    us_docs.push_str(&safe_version);
-
-    return Some(us_docs.to_string());
-
+    let markup = markup(Some(docs), "Code Suggestion: translating unsafe to safe code".to_string(), None)?;
+    let markup = if config.markdown() { markup } else { Markup::from(remove_markdown(markup.as_str())) };
+    Some(HoverResult { markup, actions })
 }
 
-fn display_suggestion_ptr_copy_nonoverlapping(target_expr: &SyntaxNode, unsafe_expr: &BlockExpr, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_ptr_copy_nonoverlapping(mcall, &unsafe_expr)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_cstring_from_vec_unchecked(mcall: CallExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    if mcall.syntax().parent()?.kind() == BIN_EXPR {
+fn unsafe_classification(
+    config: &HoverConfig,
+    classification: &UnsafeClassification,
+    auto_trait_fields: Option<String>,
+    actions: Vec<HoverAction>,
+) -> Option<HoverResult> {
 
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
+    let kind = if classification.is_impl { "Unsafe impl" } else { "Unsafe block" };
+    let verdict = if classification.is_avoidable() { "avoidable" } else { "necessary" };
 
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 3.27x]```**", generate_cstring_new_format(target_expr.lhs()?.to_string(), &mcall, false)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
+    let mut docs = String::new();
+    if classification.reasons.is_empty() {
+        docs.push_str("No operation of this block needs `unsafe`.");
+    } else {
+        push_unsafe_reasons(&mut docs, classification);
     }
-
-    let let_expr = mcall.syntax().parent().and_then(ast::LetStmt::cast)?;
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", let_expr.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_cstring_new = String::new();
-
-    format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 3.27x]```**", generate_cstring_new_format(let_expr.pat()?.to_string(), &mcall, true)?);
-
-    us_docs.push_str(&safe_cstring_new);
-
-    return Some(us_docs.to_string());
-
-}
-
-
-fn display_suggestion_cstring_from_vec_unchecked(target_expr: &SyntaxNode, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_cstring_from_vec_unchecked(mcall)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_cstring_bytes_len(mcall: CallExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    if mcall.syntax().parent()?.kind() == BIN_EXPR {
-
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_bytes_len = String::new();
-    
-        format_to!(safe_cstring_bytes_len, "**```+++```** **```{} [Runtime Overhead: -2.81%]```**", generate_bytes_len_format(target_expr.lhs()?.to_string(), &mcall, false)?);
-        
-        us_docs.push_str(&safe_cstring_bytes_len);
-    
-        return Some(us_docs.to_string());
+    if let Some(fields) = auto_trait_fields {
+        format_to!(docs, "\n\n{}", fields);
     }
 
-    let let_expr = mcall.syntax().parent().and_then(ast::LetStmt::cast)?;
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", let_expr.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_cstring_bytes_len = String::new();
-
-    format_to!(safe_cstring_bytes_len, "**```+++```** **```{} [Runtime Overhead: -2.81%]```**", generate_bytes_len_format(let_expr.pat()?.to_string(), &mcall, true)?);
-
-    us_docs.push_str(&safe_cstring_bytes_len);
-
-    return Some(us_docs.to_string());
-
-}
-
-fn display_suggestion_cstring_bytes_len(target_expr: &SyntaxNode, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_cstring_bytes_len(mcall)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
+    let markup = markup(Some(docs), format!("{}: {}", kind, verdict), None)?;
+    let markup = if config.markdown() { markup } else { Markup::from(remove_markdown(markup.as_str())) };
+    Some(HoverResult { markup, actions })
 }
 
-fn format_suggestion_from_utf8_unchecked(mcall: CallExpr, mut_sign: bool, string_sign: bool) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    if mcall.syntax().parent()?.kind() == STMT_LIST{
+/// The fields an `unsafe impl Send` or `unsafe impl Sync` vouches for, with why each of them
+/// doesn't implement the trait on its own.
+fn auto_trait_fields(sema: &Semantics<'_, RootDatabase>, impl_: &ast::Impl) -> Option<String> {
 
-        let target_expr = &mcall;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 1.1x]```**", generate_from_utf8_expr_stmt(&mcall, mut_sign, string_sign)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
-    }
-
-    if mcall.syntax().parent()?.kind() == EXPR_STMT {
-
-        let target_expr = mcall.syntax().parent().and_then(ast::ExprStmt::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 1.1x]```**", generate_from_utf8_expr_stmt(&mcall, mut_sign, string_sign)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
+    let impl_ = sema.to_def(impl_)?;
+    let blockers = impl_.auto_trait_blockers(sema.db);
+    if blockers.is_empty() {
+        return None;
     }
-
-    if mcall.syntax().parent()?.kind() == BIN_EXPR {
-
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 1.1x]```**", generate_from_utf8(&mcall, &target_expr, mut_sign, string_sign)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
+    let trait_name = impl_.trait_(sema.db)?.name(sema.db).to_string();
+    let mut docs = format!("Fields that are not `{}`:\n", trait_name);
+    for blocker in &blockers {
+        let reason = ide_diagnostics::auto_trait_blocker_reason(sema.db, blocker, &trait_name);
+        format_to!(docs, "\n- `{}: {}`: {}", blocker.field.name(sema.db), blocker.ty.display(sema.db), reason);
     }
-
-    let let_expr = mcall.syntax().parent().and_then(ast::LetStmt::cast)?;
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", let_expr.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_cstring_new = String::new();
-
-    format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 1.1x]```**", generate_let_from_utf8(&mcall, &let_expr, mut_sign, string_sign)?);
-
-    us_docs.push_str(&safe_cstring_new);
-
-    return Some(us_docs.to_string());
-
+    Some(docs)
 }
 
-fn display_suggestion_from_utf8_unchecked(target_expr: &SyntaxNode, actions: &Vec<HoverAction>, mut_sign: bool, string_sign: bool) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
+fn push_unsafe_reasons(docs: &mut String, classification: &UnsafeClassification) {
 
-    let us_docs = format_suggestion_from_utf8_unchecked(mcall, mut_sign, string_sign)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_to_safe_convert(mcall: CallExpr, unsafe_expr: &BlockExpr) -> Option<String> {
-
-    let let_expr = mcall.syntax().parent().and_then(ast::LetStmt::cast)?;
-
-    let mut us_docs = String::new();
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", mcall.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_version = String::new();
-
-    format_to!(safe_version, "**```+++```** **```{}```**", generate_from_transmute(&mcall, &let_expr, &unsafe_expr)?);
-
-    us_docs.push_str(&safe_version);
-
-    return Some(us_docs.to_string());
-
-}
-
-fn display_suggestion_mem_transmute(target_expr: &SyntaxNode, unsafe_expr: &BlockExpr, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_to_safe_convert(mcall, &unsafe_expr)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_to_from_ne_bytes(mcall: CallExpr, unsafe_expr: &BlockExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", mcall.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_version = String::new();
-    
-    if mcall.syntax().parent()?.kind() == BIN_EXPR {
-        format_to!(safe_version, "**```+++```** **```{}```**", generate_bytes_to_convert(&mcall, &unsafe_expr, false)?);
-    } else {
-        format_to!(safe_version, "**```+++```** **```{}```**", generate_bytes_to_convert(&mcall, unsafe_expr, true)?);
+    format_to!(docs, "Categories: {}\n", classification.categories().iter().join(", "));
+    for reason in &classification.reasons {
+        format_to!(docs, "\n- `{}`: {}", reason.code, reason.code.description());
+        if let Some(twin) = reason.safe_twin {
+            format_to!(docs, ", `{}` is the checked version", twin);
+        }
     }
-
-    us_docs.push_str(&safe_version);
-
-    return Some(us_docs.to_string());
-
 }
 
-fn display_suggestion_read_unaligned(target_expr: &SyntaxNode, unsafe_expr: &BlockExpr, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_to_from_ne_bytes(mcall, &unsafe_expr)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
-}
-
-fn format_suggestion_from_u32_unchecked(mcall: CallExpr) -> Option<String> {
-
-    let mut us_docs = String::new();
-
-    if mcall.syntax().parent()?.kind() == STMT_LIST{
-
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 3.8x]```**", generate_from_u32_expr_stmt(&mcall)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
-    }
-
-    if mcall.syntax().parent()?.kind() == EXPR_STMT {
-
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 3.8x]```**", generate_from_u32_expr_stmt(&mcall)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
+/// The suggestion for the unsafe operation `token` is part of, like the name of an unsafe function
+/// or the `*` of a dereference, in an unsafe block or in the body of an `unsafe fn`.
+pub(super) fn unsafe_operation(
+    sema: &Semantics<'_, RootDatabase>,
+    config: &HoverConfig,
+    token: &SyntaxToken,
+) -> Option<HoverResult> {
+    if config.documentation.is_none() || !config.keywords {
+        return None;
     }
-
-    if mcall.syntax().parent()?.kind() == BIN_EXPR {
-
-        let target_expr = mcall.syntax().parent().and_then(ast::BinExpr::cast)?;
-
-        format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", target_expr.to_string());
-    
-        us_docs.push('\n');
-        us_docs.push('\n');
-    
-        let mut safe_cstring_new = String::new();
-    
-        format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 3.8x]```**", generate_from_u32(&mcall, &target_expr)?);
-        
-        us_docs.push_str(&safe_cstring_new);
-    
-        return Some(us_docs.to_string());
+    // Only code in an unsafe context is worth the semantic lookup
+    let in_unsafe_context = token.parent_ancestors().any(|node| match_ast! {
+        match node {
+            ast::BlockExpr(it) => it.unsafe_token().is_some(),
+            ast::Fn(it) => it.unsafe_token().is_some(),
+            _ => false,
+        }
+    });
+    if !in_unsafe_context {
+        return None;
     }
 
-    let let_expr = mcall.syntax().parent().and_then(ast::LetStmt::cast)?;
-
-    format_to!(us_docs, "**```---```** **~~```unsafe {{ {} }};```~~**", let_expr.to_string());
-
-    us_docs.push('\n');
-    us_docs.push('\n');
-
-    let mut safe_cstring_new = String::new();
-
-    format_to!(safe_cstring_new, "**```+++```** **```{} [Runtime Overhead: 3.8x]```**", generate_let_from_u32(&mcall, &let_expr)?);
-
-    us_docs.push_str(&safe_cstring_new);
-
-    return Some(us_docs.to_string());
-
-}
-
-fn display_suggestion_from_u32_unchecked(target_expr: &SyntaxNode, actions: &Vec<HoverAction>) -> Option<HoverResult> {
-
-    let mcall = target_expr.parent().and_then(ast::CallExpr::cast)?;
-
-    let us_description = generate_description();
-
-    let us_docs = format_suggestion_from_u32_unchecked(mcall)?;
-
-    let markup = process_unsafe_display_text(
-        &markup(Some(us_docs), us_description, None)?,
-    );
-
-    return Some(HoverResult { markup, actions: actions.to_vec() });
-
+    let file_id = sema.original_range(&token.parent()?).file_id;
+    let suggestion = unsafe_suggestions::unsafe_operation_suggestion(sema, &hover_assist_config(&config.unsafe_to_safe), file_id, token)?;
+    unsafe_suggestion(config, &suggestion, None, Vec::new())
 }
 
 pub(super) fn keyword(
@@ -889,34 +354,20 @@ pub(super) fn keyword(
     let parent = token.parent()?;
     let famous_defs = FamousDefs(sema, sema.scope(&parent)?.krate());
 
-    let KeywordHint { description, keyword_mod, actions } = keyword_hints(sema, token, parent);
+    let KeywordHint { description, keyword_mod, actions } = keyword_hints(sema, token, parent.clone());
     
     // Yuchen's Edit -> Detect unsafe keyword
     if token.kind() == UNSAFE_KW {
-
-        let unsafe_expr = token.parent().and_then(ast::BlockExpr::cast)?;
-
-        for target_expr in unsafe_expr.syntax().descendants() {
-
-            let unsafe_type = check_convert_type(&target_expr, &unsafe_expr);
-
-            match unsafe_type {
-                Some(UnsafePattern::UnitializedVec) => return display_suggestion_uninitialized_vec(&target_expr, &unsafe_expr, &actions),
-                Some(UnsafePattern::CopyWithin) => return display_suggestion_ptr_copy(&target_expr, &unsafe_expr, &actions),
-                Some(UnsafePattern::CopyNonOverlap) => return display_suggestion_ptr_copy_nonoverlapping(&target_expr, &unsafe_expr, &actions),
-                Some(UnsafePattern::CStringFromVec) => return display_suggestion_cstring_from_vec_unchecked(&target_expr, &actions),
-                Some(UnsafePattern::CStringLength) => return display_suggestion_cstring_bytes_len(&target_expr, &actions),
-                Some(UnsafePattern::GetUncheckMut) => return display_suggestion_get_uncheck_mut(&target_expr, &actions),
-                Some(UnsafePattern::GetUncheck) => return display_suggestion_get_uncheck_mut(&target_expr, &actions),
-                Some(UnsafePattern::BytesToUTFString) => return display_suggestion_from_utf8_unchecked(&target_expr, &actions, false, false),
-                Some(UnsafePattern::BytesToUTFStringMut) => return display_suggestion_from_utf8_unchecked(&target_expr, &actions, true, false),
-                Some(UnsafePattern::StringBytesToUTFString) => return display_suggestion_from_utf8_unchecked(&target_expr, &actions, false, true),
-                Some(UnsafePattern::TransmuteTo) => return display_suggestion_mem_transmute(&target_expr, &unsafe_expr, &actions),
-                Some(UnsafePattern::ReadUnaligned) => return display_suggestion_read_unaligned(&target_expr, &unsafe_expr, &actions),
-                Some(UnsafePattern::FromU32Unchecked) => return display_suggestion_from_u32_unchecked(&target_expr, &actions),
-                None => continue,
-                _ => todo!(),
-            };
+        let file_id = sema.original_range(&parent).file_id;
+        let classification = unsafe_classification::classify_unsafe_keyword(sema, file_id, token.clone());
+        if let Some(unsafe_expr) = ast::BlockExpr::cast(parent.clone()) {
+            if let Some(suggestion) = unsafe_suggestions::unsafe_suggestion(sema, &hover_assist_config(&config.unsafe_to_safe), file_id, &unsafe_expr) {
+                return unsafe_suggestion(config, &suggestion, classification.as_ref(), actions);
+            }
+        }
+        if let Some(classification) = classification {
+            let auto_trait_fields = ast::Impl::cast(parent.clone()).and_then(|impl_| auto_trait_fields(sema, &impl_));
+            return unsafe_classification(config, &classification, auto_trait_fields, actions);
         }
     }
 
@@ -990,14 +441,6 @@ pub(super) fn process_markup(
     Markup::from(markup)
 }
 
-pub(super) fn process_unsafe_display_text(
-    markup: &Markup,
-) -> Markup {
-    let markup = markup.as_str();
-    let markup = markup.to_string();
-    Markup::from(markup)
-}
-
 fn definition_owner_name(db: &RootDatabase, def: &Definition) -> Option<String> {
     match def {
         Definition::Field(f) => Some(f.parent_def(db).name(db)),
@@ -1,4 +1,5 @@
 use expect_test::{expect, Expect};
+use ide_assists::UnsafeToSafeConfig;
 use ide_db::base_db::{FileLoader, FileRange};
 use syntax::TextRange;
 
@@ -12,6 +13,8 @@ fn check_hover_no_result(ra_fixture: &str) {
                 links_in_hover: true,
                 documentation: Some(HoverDocFormat::Markdown),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
             },
             FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
         )
@@ -28,6 +31,8 @@ fn check(ra_fixture: &str, expect: Expect) {
                 links_in_hover: true,
                 documentation: Some(HoverDocFormat::Markdown),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
             },
             FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
         )
@@ -49,6 +54,8 @@ fn check_hover_no_links(ra_fixture: &str, expect: Expect) {
                 links_in_hover: false,
                 documentation: Some(HoverDocFormat::Markdown),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
             },
             FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
         )
@@ -70,6 +77,31 @@ fn check_hover_no_markdown(ra_fixture: &str, expect: Expect) {
                 links_in_hover: true,
                 documentation: Some(HoverDocFormat::PlainText),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
+            },
+            FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
+        )
+        .unwrap()
+        .unwrap();
+
+    let content = analysis.db.file_text(position.file_id);
+    let hovered_element = &content[hover.range];
+
+    let actual = format!("*{}*\n{}\n", hovered_element, hover.info.markup);
+    expect.assert_eq(&actual)
+}
+
+fn check_hover_no_unsafe_overhead(ra_fixture: &str, expect: Expect) {
+    let (analysis, position) = fixture::position(ra_fixture);
+    let hover = analysis
+        .hover(
+            &HoverConfig {
+                links_in_hover: true,
+                documentation: Some(HoverDocFormat::Markdown),
+                keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: false,
             },
             FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
         )
@@ -91,6 +123,8 @@ fn check_actions(ra_fixture: &str, expect: Expect) {
                 links_in_hover: true,
                 documentation: Some(HoverDocFormat::Markdown),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
             },
             FileRange { file_id, range: position.range_or_empty() },
         )
@@ -107,6 +141,8 @@ fn check_hover_range(ra_fixture: &str, expect: Expect) {
                 links_in_hover: false,
                 documentation: Some(HoverDocFormat::Markdown),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
             },
             range,
         )
@@ -123,6 +159,8 @@ fn check_hover_range_no_results(ra_fixture: &str) {
                 links_in_hover: false,
                 documentation: Some(HoverDocFormat::Markdown),
                 keywords: true,
+                unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+                unsafe_to_safe_overhead: true,
             },
             range,
         )
@@ -4096,6 +4134,102 @@ mod move_keyword {}
     );
 }
 
+#[test]
+fn hover_unsafe_keyword_suggestion() {
+    check(
+        r#"
+fn main() {
+    let vec = vec![1, 2, 3];
+    let value = unsa$0fe { vec.get_unchecked(1) };
+}
+"#,
+        expect![[r#"
+            *unsafe*
+            ```rust
+            Code Suggestion: translating unsafe to safe code
+            ```
+            ___
+
+            Rule: `get_unchecked`, Runtime Overhead: 7.58%
+
+            ```diff
+            -unsafe { vec.get_unchecked(1) }
+            +vec.get(1).unwrap()
+            ```
+
+            `get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` turns an invalid index into a panic.
+        "#]],
+    );
+}
+
+#[test]
+fn hover_unsafe_keyword_suggestion_no_overhead() {
+    check_hover_no_unsafe_overhead(
+        r#"
+fn main() {
+    let vec = vec![1, 2, 3];
+    let value = unsa$0fe { vec.get_unchecked(1) };
+}
+"#,
+        expect![[r#"
+            *unsafe*
+            ```rust
+            Code Suggestion: translating unsafe to safe code
+            ```
+            ___
+
+            Rule: `get_unchecked`
+
+            ```diff
+            -unsafe { vec.get_unchecked(1) }
+            +vec.get(1).unwrap()
+            ```
+
+            `get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` turns an invalid index into a panic.
+        "#]],
+    );
+}
+
+#[test]
+fn hover_unsafe_operation_suggestion() {
+    check(
+        r#"
+#[lang = "slice"]
+impl<T> [T] {
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T { loop {} }
+}
+fn main(vec: &[i32]) {
+    let value = unsafe { vec.get_unch$0ecked(1) };
+}
+"#,
+        expect![[r#"
+            *get_unchecked*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+            pub unsafe fn get_unchecked(&self, index: usize) -> &T
+            ```
+            ---
+            ```rust
+            Code Suggestion: translating unsafe to safe code
+            ```
+            ___
+
+            Rule: `get_unchecked`, Runtime Overhead: 7.58%
+
+            ```diff
+            -unsafe { vec.get_unchecked(1) }
+            +vec.get(1).unwrap()
+            ```
+
+            `get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` turns an invalid index into a panic.
+        "#]],
+    );
+}
+
 #[test]
 fn hover_keyword_as_primitive() {
     check(
@@ -5308,3 +5442,84 @@ fn main() { $0V; }
         "#]],
     );
 }
+
+#[test]
+fn hover_unsafe_keyword_classification() {
+    check(
+        r#"
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+fn main(p: *const i32) {
+    let value = unsa$0fe { abs(*p) };
+}
+"#,
+        expect![[r#"
+            *unsafe*
+            ```rust
+            Unsafe block: necessary
+            ```
+            ___
+
+            Categories: FFI call, raw pointer
+
+            - `ffi-call`: call of a foreign function
+            - `raw-pointer-deref`: dereference of a raw pointer
+        "#]],
+    );
+}
+
+#[test]
+fn hover_unsafe_impl_classification() {
+    check(
+        r#"
+unsafe trait Zeroable {}
+struct S;
+unsa$0fe impl Zeroable for S {}
+"#,
+        expect![[r#"
+            *unsafe*
+            ```rust
+            Unsafe impl: necessary
+            ```
+            ___
+
+            Categories: trait impl contract
+
+            - `unsafe-trait-impl`: implementation of an `unsafe` trait
+        "#]],
+    );
+}
+
+#[test]
+fn hover_unsafe_impl_send_fields() {
+    check(
+        r#"
+#[lang = "send"]
+unsafe auto trait Send {}
+impl<T: ?Sized> !Send for *mut T {}
+struct Buffer<T> {
+    data: *mut T,
+    len: usize,
+    value: T,
+}
+unsa$0fe impl<T> Send for Buffer<T> {}
+"#,
+        expect![[r#"
+            *unsafe*
+            ```rust
+            Unsafe impl: necessary
+            ```
+            ___
+
+            Categories: trait impl contract
+
+            - `unsafe-trait-impl`: implementation of an `unsafe` trait
+
+            Fields that are not `Send`:
+
+            - `data: *mut T`: `*mut T` is a raw pointer
+            - `value: T`: `T` is not required to be `Send`
+        "#]],
+    );
+}
@@ -31,6 +31,7 @@ pub struct InlayHintsConfig {
     pub hide_closure_initialization_hints: bool,
     pub max_length: Option<usize>,
     pub closing_brace_hints_min_lines: Option<usize>,
+    pub unsafe_hints: UnsafeHints,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -54,6 +55,15 @@ pub enum ReborrowHints {
     Never,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnsafeHints {
+    /// Summarize the unsafe operations of each `unsafe` block and mark every single operation.
+    Always,
+    /// Only summarize the unsafe operations of each `unsafe` block.
+    BlockSummary,
+    Never,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum InlayKind {
     BindingModeHint,
@@ -65,6 +75,8 @@ pub enum InlayKind {
     LifetimeHint,
     ParameterHint,
     TypeHint,
+    UnsafeBlockHint,
+    UnsafeOperationHint,
 }
 
 #[derive(Debug)]
@@ -175,6 +187,7 @@ impl fmt::Debug for InlayHintLabelPart {
 // * return types of closure expressions
 // * elided lifetimes
 // * compiler inserted reborrows
+// * the unsafe operations performed by `unsafe` blocks
 //
 // image::https://user-images.githubusercontent.com/48062697/113020660-b5f98b80-917a-11eb-8d70-3be3fd558cdd.png[]
 pub(crate) fn inlay_hints(
@@ -227,6 +240,7 @@ fn hints(
                         param_name_hints(hints, sema, config, ast::Expr::from(it))
                     }
                     ast::Expr::ClosureExpr(it) => closure_ret_hints(hints, sema, &famous_defs, config, file_id, it),
+                    ast::Expr::BlockExpr(it) => unsafe_hints(hints, sema, config, file_id, it),
                     // We could show reborrows for all expressions, but usually that is just noise to the user
                     // and the main point here is to show why "moving" a mutable reference doesn't necessarily move it
                     ast::Expr::PathExpr(_) => reborrow_hints(hints, sema, config, &expr),
@@ -362,6 +376,74 @@ fn closing_brace_hints(
     None
 }
 
+fn unsafe_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<'_, RootDatabase>,
+    config: &InlayHintsConfig,
+    file_id: FileId,
+    block: ast::BlockExpr,
+) -> Option<()> {
+    if config.unsafe_hints == UnsafeHints::Never {
+        return None;
+    }
+    let unsafe_token = block.unsafe_token()?;
+
+    // Operations of nested `unsafe` blocks are attributed to the innermost block only.
+    let ops: Vec<_> = sema
+        .unsafe_operations(&ast::Expr::BlockExpr(block.clone()))
+        .into_iter()
+        .filter(|op| {
+            sema.ancestors_with_macros(op.expr.syntax().clone())
+                .filter_map(ast::BlockExpr::cast)
+                .find(|it| it.unsafe_token().is_some())
+                .map_or(false, |it| it == block)
+        })
+        .collect();
+    if ops.is_empty() {
+        return None;
+    }
+
+    let count = |kind| ops.iter().filter(|op| op.kind == kind).count();
+    let label = [
+        (count(hir::UnsafeOpKind::RawPtrDeref), "raw deref", "raw derefs"),
+        (count(hir::UnsafeOpKind::UnsafeCall), "unsafe call", "unsafe calls"),
+        (count(hir::UnsafeOpKind::MutableStatic), "static mut access", "static mut accesses"),
+    ]
+    .into_iter()
+    .filter(|&(n, ..)| n != 0)
+    .map(|(n, singular, plural)| format!("{n} {}", if n == 1 { singular } else { plural }))
+    .join(", ");
+    acc.push(InlayHint {
+        range: unsafe_token.text_range(),
+        kind: InlayKind::UnsafeBlockHint,
+        label: label.into(),
+        tooltip: Some(InlayTooltip::String("Operations that require this `unsafe` block".into())),
+    });
+
+    if config.unsafe_hints != UnsafeHints::Always {
+        return Some(());
+    }
+    for op in ops {
+        let range = match sema.original_range_opt(op.expr.syntax()) {
+            Some(frange) if frange.file_id == file_id => frange.range,
+            _ => continue,
+        };
+        let (label, tooltip) = match op.kind {
+            hir::UnsafeOpKind::RawPtrDeref => ("raw deref", "Dereference of a raw pointer"),
+            hir::UnsafeOpKind::UnsafeCall => ("unsafe call", "Call to an `unsafe fn`"),
+            hir::UnsafeOpKind::MutableStatic => ("static mut", "Use of a `static mut`"),
+        };
+        acc.push(InlayHint {
+            range,
+            kind: InlayKind::UnsafeOperationHint,
+            label: label.to_owned().into(),
+            tooltip: Some(InlayTooltip::String(tooltip.into())),
+        });
+    }
+
+    Some(())
+}
+
 fn implicit_static_hints(
     acc: &mut Vec<InlayHint>,
     config: &InlayHintsConfig,
@@ -1218,7 +1300,7 @@ mod tests {
     use syntax::{TextRange, TextSize};
     use test_utils::extract_annotations;
 
-    use crate::inlay_hints::ReborrowHints;
+    use crate::inlay_hints::{ReborrowHints, UnsafeHints};
     use crate::{fixture, inlay_hints::InlayHintsConfig, LifetimeElisionHints};
 
     use super::ClosureReturnTypeHints;
@@ -1237,6 +1319,7 @@ mod tests {
         param_names_for_lifetime_elision_hints: false,
         max_length: None,
         closing_brace_hints_min_lines: None,
+        unsafe_hints: UnsafeHints::Never,
     };
     const TEST_CONFIG: InlayHintsConfig = InlayHintsConfig {
         type_hints: true,
@@ -2984,6 +3067,62 @@ fn f() {
     ];
   }
 //^ fn f
+"#,
+        );
+    }
+
+    #[test]
+    fn unsafe_block_hints() {
+        check_with_config(
+            InlayHintsConfig { unsafe_hints: UnsafeHints::BlockSummary, ..DISABLED_CONFIG },
+            r#"
+unsafe fn f() {}
+static mut S: u32 = 0;
+struct Foo;
+impl Foo {
+    unsafe fn m(&self) {}
+}
+
+fn main() {
+    let p = &0u32 as *const u32;
+    unsafe {
+  //^^^^^^ 2 raw derefs, 1 unsafe call
+        let _ = *p + *p;
+        f();
+    }
+    let x = unsafe { S };
+          //^^^^^^ 1 static mut access
+    unsafe {
+  //^^^^^^ 2 unsafe calls
+        Foo.m();
+        unsafe {
+      //^^^^^^ 1 raw deref
+            *p;
+        }
+        f();
+    }
+    unsafe {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsafe_operation_hints() {
+        check_with_config(
+            InlayHintsConfig { unsafe_hints: UnsafeHints::Always, ..DISABLED_CONFIG },
+            r#"
+unsafe fn f(_: u32) {}
+
+fn main() {
+    let p = &0u32 as *const u32;
+    unsafe {
+  //^^^^^^ 1 raw deref, 1 unsafe call
+        f(*p);
+      //^^^^^ unsafe call
+        //^^ raw deref
+    }
+}
 "#,
         );
     }
@@ -54,6 +54,9 @@ mod status;
 mod syntax_highlighting;
 mod syntax_tree;
 mod typing;
+mod unsafe_classification;
+mod unsafe_reachability;
+mod unsafe_suggestions;
 mod view_crate_graph;
 mod view_hir;
 mod view_item_tree;
@@ -83,7 +86,7 @@ pub use crate::{
     hover::{HoverAction, HoverConfig, HoverDocFormat, HoverGotoTypeData, HoverResult},
     inlay_hints::{
         ClosureReturnTypeHints, InlayHint, InlayHintLabel, InlayHintsConfig, InlayKind,
-        InlayTooltip, LifetimeElisionHints, ReborrowHints,
+        InlayTooltip, LifetimeElisionHints, ReborrowHints, UnsafeHints,
     },
     join_lines::JoinLinesConfig,
     markup::Markup,
@@ -100,10 +103,14 @@ pub use crate::{
         tags::{Highlight, HlMod, HlMods, HlOperator, HlPunct, HlTag},
         HighlightConfig, HlRange,
     },
+    unsafe_classification::{UnsafeCategory, UnsafeClassification, UnsafeReason, UnsafeReasonCode},
+    unsafe_reachability::UnsafeReachability,
+    unsafe_suggestions::{UnsafePattern, UnsafeSuggestion, UnsafeSuggestionEdit},
 };
 pub use hir::{Documentation, Semantics};
 pub use ide_assists::{
     Assist, AssistConfig, AssistId, AssistKind, AssistResolveStrategy, SingleResolve,
+    UnsafeToSafeConfig, UnsafeToSafeErrorStyle,
 };
 pub use ide_completion::{
     CallableSnippets, CompletionConfig, CompletionItem, CompletionItemKind, CompletionRelevance,
@@ -482,6 +489,40 @@ impl Analysis {
         self.with_db(|db| call_hierarchy::outgoing_calls(db, position))
     }
 
+    /// Computes the safe public functions of the crate of `file_id` that reach unsafe code.
+    pub fn unsafe_reachability(&self, file_id: FileId) -> Cancellable<Vec<UnsafeReachability>> {
+        self.with_db(|db| unsafe_reachability::unsafe_reachability(db, file_id))
+    }
+
+    /// Computes why the unsafe blocks and `unsafe impl`s intersecting `frange` need `unsafe`.
+    pub fn unsafe_classification(
+        &self,
+        frange: FileRange,
+    ) -> Cancellable<Vec<UnsafeClassification>> {
+        self.with_db(|db| unsafe_classification::unsafe_classification(db, frange))
+    }
+
+    /// Computes the unsafe-to-safe rewrites for the unsafe blocks intersecting `frange`.
+    pub fn unsafe_suggestions(
+        &self,
+        config: &AssistConfig,
+        frange: FileRange,
+    ) -> Cancellable<Vec<UnsafeSuggestion>> {
+        self.with_db(|db| unsafe_suggestions::unsafe_suggestions(db, config, frange))
+    }
+
+    /// Computes the rule of the unsafe-to-safe rewrite of the unsafe block whose `unsafe` keyword
+    /// is at `position`, even when the rewrite doesn't apply.
+    pub fn unsafe_block_rule(&self, position: FilePosition) -> Cancellable<Option<UnsafePattern>> {
+        self.with_db(|db| unsafe_suggestions::unsafe_block_rule(db, position))
+    }
+
+    /// Computes the matches of the unsafe-to-safe patterns in the unsafe block whose `unsafe`
+    /// keyword is at `position`.
+    pub fn unsafe_block_patterns(&self, position: FilePosition) -> Cancellable<Vec<UnsafePattern>> {
+        self.with_db(|db| unsafe_suggestions::unsafe_block_patterns(db, position))
+    }
+
     /// Returns a `mod name;` declaration which created the current module.
     pub fn parent_module(&self, position: FilePosition) -> Cancellable<Vec<NavigationTarget>> {
         self.with_db(|db| parent_module::parent_module(db, position))
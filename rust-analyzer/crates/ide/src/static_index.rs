@@ -15,7 +15,7 @@ use crate::{
     hover::hover_for_definition,
     moniker::{crate_for_file, def_to_moniker, MonikerResult},
     Analysis, Fold, HoverConfig, HoverDocFormat, HoverResult, InlayHint, InlayHintsConfig,
-    TryToNav,
+    TryToNav, UnsafeHints, UnsafeToSafeConfig,
 };
 
 /// A static representation of fully analyzed source code.
@@ -118,6 +118,7 @@ impl StaticIndex<'_> {
                     binding_mode_hints: false,
                     max_length: Some(25),
                     closing_brace_hints_min_lines: Some(25),
+                    unsafe_hints: UnsafeHints::Never,
                 },
                 file_id,
                 None,
@@ -134,6 +135,8 @@ impl StaticIndex<'_> {
             links_in_hover: true,
             documentation: Some(HoverDocFormat::Markdown),
             keywords: true,
+            unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+            unsafe_to_safe_overhead: true,
         };
         let tokens = tokens.filter(|token| {
             matches!(
@@ -0,0 +1,476 @@
+//! Sorts unsafe blocks and `unsafe impl`s by the reasons they need `unsafe`.
+
+use std::fmt;
+
+use hir::{PathResolution, Semantics, UnsafeOpKind, VariantDef};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    RootDatabase,
+};
+use syntax::{ast, AstNode, SyntaxToken, TextRange, T};
+
+use crate::unsafe_suggestions::unsafe_block;
+
+/// The unchecked operations of the standard library, with the checked version doing the same.
+const SAFE_TWINS: &[(&str, &str)] = &[
+    ("get_unchecked", "get"),
+    ("get_unchecked_mut", "get_mut"),
+    ("from_utf8_unchecked", "from_utf8"),
+    ("from_utf8_unchecked_mut", "from_utf8_mut"),
+    ("from_u32_unchecked", "from_u32"),
+    ("from_digit_unchecked", "from_digit"),
+    ("from_vec_unchecked", "new"),
+    ("from_bytes_with_nul_unchecked", "from_bytes_with_nul"),
+    ("unwrap_unchecked", "unwrap"),
+    ("new_unchecked", "new"),
+    ("unchecked_add", "checked_add"),
+    ("unchecked_sub", "checked_sub"),
+    ("unchecked_mul", "checked_mul"),
+    ("unchecked_shl", "checked_shl"),
+    ("unchecked_shr", "checked_shr"),
+    ("slice_unchecked", "get"),
+    ("get_unchecked_slice", "get"),
+];
+
+const POINTER_ARITHMETIC: &[&str] =
+    &["offset", "add", "sub", "offset_from", "byte_offset", "byte_add", "byte_sub"];
+
+const POINTER_ACCESS: &[&str] = &[
+    "read",
+    "write",
+    "read_unaligned",
+    "write_unaligned",
+    "read_volatile",
+    "write_volatile",
+    "copy",
+    "copy_nonoverlapping",
+    "copy_from",
+    "copy_to",
+    "copy_from_nonoverlapping",
+    "copy_to_nonoverlapping",
+    "write_bytes",
+    "replace",
+    "swap",
+    "drop_in_place",
+    "from_raw_parts",
+    "from_raw_parts_mut",
+    "from_raw",
+    "as_ref",
+    "as_mut",
+];
+
+const UNINITIALIZED_MEMORY: &[&str] = &[
+    "set_len",
+    "assume_init",
+    "assume_init_read",
+    "assume_init_ref",
+    "assume_init_mut",
+    "assume_init_drop",
+    "uninitialized",
+    "zeroed",
+];
+
+/// The broad reason a piece of code needs `unsafe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UnsafeCategory {
+    /// Calls into foreign code.
+    Ffi,
+    /// Dereferences, arithmetic and reads or writes through raw pointers.
+    RawPointer,
+    /// Unchecked operations that have a checked twin, used for performance only.
+    UncheckedOperation,
+    /// Accesses of memory that may not be initialized.
+    UninitializedMemory,
+    /// Reads of union fields and accesses of `static mut`s.
+    UnionOrStatic,
+    /// Implementations of `unsafe` traits, whose contract the compiler can't check.
+    TraitContract,
+    /// Other calls of `unsafe fn`s and `transmute`.
+    Other,
+}
+
+impl UnsafeCategory {
+    /// Whether code of this category can usually be written without `unsafe`.
+    pub fn is_avoidable(self) -> bool {
+        matches!(self, UnsafeCategory::UncheckedOperation)
+    }
+}
+
+impl fmt::Display for UnsafeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UnsafeCategory::Ffi => "FFI call",
+            UnsafeCategory::RawPointer => "raw pointer",
+            UnsafeCategory::UncheckedOperation => "unchecked operation",
+            UnsafeCategory::UninitializedMemory => "uninitialized memory",
+            UnsafeCategory::UnionOrStatic => "union or static access",
+            UnsafeCategory::TraitContract => "trait impl contract",
+            UnsafeCategory::Other => "other",
+        })
+    }
+}
+
+/// A stable code for the reason a single operation needs `unsafe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UnsafeReasonCode {
+    FfiCall,
+    RawPointerDeref,
+    RawPointerArithmetic,
+    RawPointerAccess,
+    UncheckedWithSafeTwin,
+    UninitializedMemory,
+    UnionField,
+    MutableStatic,
+    UnsafeTraitImpl,
+    Transmute,
+    UnsafeFnCall,
+}
+
+impl UnsafeReasonCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnsafeReasonCode::FfiCall => "ffi-call",
+            UnsafeReasonCode::RawPointerDeref => "raw-pointer-deref",
+            UnsafeReasonCode::RawPointerArithmetic => "raw-pointer-arithmetic",
+            UnsafeReasonCode::RawPointerAccess => "raw-pointer-access",
+            UnsafeReasonCode::UncheckedWithSafeTwin => "unchecked-with-safe-twin",
+            UnsafeReasonCode::UninitializedMemory => "uninitialized-memory",
+            UnsafeReasonCode::UnionField => "union-field",
+            UnsafeReasonCode::MutableStatic => "mutable-static",
+            UnsafeReasonCode::UnsafeTraitImpl => "unsafe-trait-impl",
+            UnsafeReasonCode::Transmute => "transmute",
+            UnsafeReasonCode::UnsafeFnCall => "unsafe-fn-call",
+        }
+    }
+
+    pub fn category(self) -> UnsafeCategory {
+        match self {
+            UnsafeReasonCode::FfiCall => UnsafeCategory::Ffi,
+            UnsafeReasonCode::RawPointerDeref
+            | UnsafeReasonCode::RawPointerArithmetic
+            | UnsafeReasonCode::RawPointerAccess => UnsafeCategory::RawPointer,
+            UnsafeReasonCode::UncheckedWithSafeTwin => UnsafeCategory::UncheckedOperation,
+            UnsafeReasonCode::UninitializedMemory => UnsafeCategory::UninitializedMemory,
+            UnsafeReasonCode::UnionField | UnsafeReasonCode::MutableStatic => {
+                UnsafeCategory::UnionOrStatic
+            }
+            UnsafeReasonCode::UnsafeTraitImpl => UnsafeCategory::TraitContract,
+            UnsafeReasonCode::Transmute | UnsafeReasonCode::UnsafeFnCall => UnsafeCategory::Other,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            UnsafeReasonCode::FfiCall => "call of a foreign function",
+            UnsafeReasonCode::RawPointerDeref => "dereference of a raw pointer",
+            UnsafeReasonCode::RawPointerArithmetic => "arithmetic on a raw pointer",
+            UnsafeReasonCode::RawPointerAccess => "read or write through a raw pointer",
+            UnsafeReasonCode::UncheckedWithSafeTwin => "unchecked operation with a checked twin",
+            UnsafeReasonCode::UninitializedMemory => "access of possibly uninitialized memory",
+            UnsafeReasonCode::UnionField => "read of a union field",
+            UnsafeReasonCode::MutableStatic => "access of a `static mut`",
+            UnsafeReasonCode::UnsafeTraitImpl => "implementation of an `unsafe` trait",
+            UnsafeReasonCode::Transmute => "`transmute` of a value",
+            UnsafeReasonCode::UnsafeFnCall => "call of an `unsafe fn`",
+        }
+    }
+}
+
+impl fmt::Display for UnsafeReasonCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single operation that needs `unsafe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeReason {
+    pub code: UnsafeReasonCode,
+    pub range: TextRange,
+    /// The checked version of an unchecked operation, like `get` for `get_unchecked`.
+    pub safe_twin: Option<&'static str>,
+}
+
+/// The reasons an unsafe block or `unsafe impl` needs `unsafe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeClassification {
+    /// The unsafe block or `unsafe impl`.
+    pub range: TextRange,
+    pub is_impl: bool,
+    /// The reasons in source order. Empty for blocks that need no `unsafe` at all.
+    pub reasons: Vec<UnsafeReason>,
+}
+
+impl UnsafeClassification {
+    /// The distinct categories of the reasons.
+    pub fn categories(&self) -> Vec<UnsafeCategory> {
+        let mut res: Vec<_> = self.reasons.iter().map(|it| it.code.category()).collect();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    /// Whether the `unsafe` can be removed, either because nothing needs it or because every
+    /// operation has a checked twin.
+    pub fn is_avoidable(&self) -> bool {
+        self.reasons.iter().all(|it| it.code.category().is_avoidable())
+    }
+}
+
+// Feature: Unsafe Classification
+//
+// Sorts the unsafe blocks and `unsafe impl`s of a file by why they need `unsafe`, with a reason
+// code per unsafe operation. Blocks whose operations all have a checked twin are avoidable.
+// The hover of the `unsafe` keyword shows the classification of its block.
+pub(crate) fn unsafe_classification(
+    db: &RootDatabase,
+    frange: FileRange,
+) -> Vec<UnsafeClassification> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(frange.file_id);
+    source_file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|token| token.kind() == T![unsafe])
+        .filter(|token| token.text_range().intersect(frange.range).is_some())
+        .filter_map(|token| classify_unsafe_keyword(&sema, frange.file_id, token))
+        .collect()
+}
+
+/// Classifies the unsafe block or `unsafe impl` of an `unsafe` keyword of `file_id`.
+pub(crate) fn classify_unsafe_keyword(
+    sema: &Semantics<'_, RootDatabase>,
+    file_id: FileId,
+    token: SyntaxToken,
+) -> Option<UnsafeClassification> {
+    if let Some(impl_) = token.parent().and_then(ast::Impl::cast) {
+        let range = impl_.syntax().text_range();
+        let reason = UnsafeReason {
+            code: UnsafeReasonCode::UnsafeTraitImpl,
+            range: impl_.trait_()?.syntax().text_range(),
+            safe_twin: None,
+        };
+        return Some(UnsafeClassification { range, is_impl: true, reasons: vec![reason] });
+    }
+
+    let block = unsafe_block(sema, token)?;
+    block.unsafe_token()?;
+    let original = sema.original_range_opt(block.syntax())?;
+    if original.file_id != file_id {
+        return None;
+    }
+
+    let in_file = |node: &syntax::SyntaxNode| {
+        let range = sema.original_range_opt(node)?;
+        (range.file_id == file_id).then(|| range.range)
+    };
+    let mut reasons: Vec<_> = sema
+        .unsafe_operations(&ast::Expr::BlockExpr(block.clone()))
+        .into_iter()
+        .filter_map(|op| {
+            let range = in_file(op.expr.syntax())?;
+            let (code, safe_twin) = match op.kind {
+                UnsafeOpKind::RawPtrDeref => (UnsafeReasonCode::RawPointerDeref, None),
+                UnsafeOpKind::MutableStatic => (UnsafeReasonCode::MutableStatic, None),
+                UnsafeOpKind::UnsafeCall => classify_call(sema, &op.expr),
+            };
+            Some(UnsafeReason { code, range, safe_twin })
+        })
+        .collect();
+
+    // Union field reads aren't unsafe operations to hir yet
+    let union_fields = block
+        .syntax()
+        .descendants()
+        .filter_map(ast::FieldExpr::cast)
+        .filter(|field| !is_assignee(field))
+        .filter(|field| {
+            matches!(
+                sema.resolve_field(field).map(|it| it.parent_def(sema.db)),
+                Some(VariantDef::Union(_))
+            )
+        })
+        .filter_map(|field| in_file(field.syntax()));
+    reasons.extend(union_fields.map(|range| UnsafeReason {
+        code: UnsafeReasonCode::UnionField,
+        range,
+        safe_twin: None,
+    }));
+    reasons.sort_by_key(|it| it.range.start());
+
+    Some(UnsafeClassification { range: original.range, is_impl: false, reasons })
+}
+
+fn classify_call(
+    sema: &Semantics<'_, RootDatabase>,
+    call: &ast::Expr,
+) -> (UnsafeReasonCode, Option<&'static str>) {
+    let function = match call {
+        ast::Expr::CallExpr(call) => match call.expr() {
+            Some(ast::Expr::PathExpr(path)) => {
+                match path.path().and_then(|path| sema.resolve_path(&path)) {
+                    Some(PathResolution::Def(hir::ModuleDef::Function(it))) => Some(it),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        ast::Expr::MethodCallExpr(call) => sema.resolve_method_call(call),
+        _ => None,
+    };
+    let function = match function {
+        Some(it) => it,
+        None => return (UnsafeReasonCode::UnsafeFnCall, None),
+    };
+    if function.is_extern(sema.db) {
+        return (UnsafeReasonCode::FfiCall, None);
+    }
+
+    let name = function.name(sema.db).to_smol_str();
+    let name = name.as_str();
+    if let Some(&(_, twin)) = SAFE_TWINS.iter().find(|(it, _)| *it == name) {
+        (UnsafeReasonCode::UncheckedWithSafeTwin, Some(twin))
+    } else if POINTER_ARITHMETIC.contains(&name) {
+        (UnsafeReasonCode::RawPointerArithmetic, None)
+    } else if POINTER_ACCESS.contains(&name) {
+        (UnsafeReasonCode::RawPointerAccess, None)
+    } else if UNINITIALIZED_MEMORY.contains(&name) {
+        (UnsafeReasonCode::UninitializedMemory, None)
+    } else if matches!(name, "transmute" | "transmute_copy") {
+        (UnsafeReasonCode::Transmute, None)
+    } else {
+        (UnsafeReasonCode::UnsafeFnCall, None)
+    }
+}
+
+/// Whether the field is written by a plain assignment, which is safe for unions.
+fn is_assignee(field: &ast::FieldExpr) -> bool {
+    let bin = match field.syntax().parent().and_then(ast::BinExpr::cast) {
+        Some(it) => it,
+        None => return false,
+    };
+    bin.lhs().map_or(false, |lhs| lhs.syntax() == field.syntax())
+        && matches!(bin.op_kind(), Some(ast::BinaryOp::Assignment { op: None }))
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::base_db::FileRange;
+    use itertools::Itertools;
+    use stdx::format_to;
+    use syntax::TextRange;
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let text = analysis.file_text(file_id).unwrap();
+        let frange = FileRange { file_id, range: TextRange::up_to((text.len() as u32).into()) };
+        let mut actual = String::new();
+        for it in analysis.unsafe_classification(frange).unwrap() {
+            format_to!(
+                actual,
+                "{:?} {} [{}]\n",
+                it.range,
+                if it.is_avoidable() { "avoidable" } else { "necessary" },
+                it.categories().iter().join(", "),
+            );
+            for reason in &it.reasons {
+                format_to!(actual, "    {} {:?}", reason.code, &text[reason.range]);
+                if let Some(twin) = reason.safe_twin {
+                    format_to!(actual, " -> {}", twin);
+                }
+                actual.push('\n');
+            }
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn ffi_and_raw_pointers() {
+        check(
+            r#"
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+unsafe fn offset(p: *const i32, count: isize) -> *const i32 { p }
+fn f(p: *const i32) -> i32 {
+    unsafe { abs(*offset(p, 1)) }
+}
+"#,
+            expect![[r#"
+                141..170 necessary [FFI call, raw pointer]
+                    ffi-call "abs(*offset(p, 1))"
+                    raw-pointer-deref "*offset(p, 1)"
+                    raw-pointer-arithmetic "offset(p, 1)"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unchecked_operations_are_avoidable() {
+        check(
+            r#"
+struct Buf;
+impl Buf {
+    unsafe fn get_unchecked(&self, index: usize) -> u8 { 0 }
+    unsafe fn set_len(&mut self, len: usize) {}
+}
+fn f(mut buf: Buf) {
+    let a = unsafe { buf.get_unchecked(1) };
+    unsafe { buf.set_len(4); buf.get_unchecked(0) };
+}
+"#,
+            expect![[r#"
+                167..198 avoidable [unchecked operation]
+                    unchecked-with-safe-twin "buf.get_unchecked(1)" -> get
+                204..251 necessary [unchecked operation, uninitialized memory]
+                    uninitialized-memory "buf.set_len(4)"
+                    unchecked-with-safe-twin "buf.get_unchecked(0)" -> get
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unions_statics_and_impls() {
+        check(
+            r#"
+union Bits { int: u32, float: f32 }
+static mut COUNTER: u32 = 0;
+unsafe trait Zeroable {}
+struct S;
+unsafe impl Zeroable for S {}
+fn f(mut bits: Bits) -> f32 {
+    unsafe {
+        COUNTER += 1;
+        bits.int = 1;
+        bits.float
+    }
+}
+"#,
+            expect![[r#"
+                100..129 necessary [trait impl contract]
+                    unsafe-trait-impl "Zeroable"
+                164..241 necessary [union or static access]
+                    mutable-static "COUNTER"
+                    union-field "bits.float"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn block_without_unsafe_operations() {
+        check(
+            r#"
+fn f() -> i32 {
+    unsafe { 1 + 1 }
+}
+"#,
+            expect![[r#"
+                20..36 avoidable []
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Finds the safe public functions of a crate that transitively reach unsafe code.
+
+use std::{
+    collections::{hash_map::Entry, VecDeque},
+    iter,
+};
+
+use hir::{AsAssocItem, AssocItemContainer, HasVisibility, Semantics, Visibility};
+use ide_db::{base_db::FileRange, FxHashMap, RootDatabase};
+use syntax::{ast, AstNode};
+
+use crate::{call_hierarchy, FileId, NavigationTarget, TryToNav};
+
+/// A call path from a safe public function to a function containing unsafe operations.
+#[derive(Debug, Clone)]
+pub struct UnsafeReachability {
+    /// The functions along the path. The first one is the public entry point,
+    /// the last one contains the unsafe operations.
+    pub path: Vec<NavigationTarget>,
+    /// `call_sites[i]` is the call inside of `path[i]` that leads to `path[i + 1]`.
+    pub call_sites: Vec<FileRange>,
+    /// The unsafe operations of the last function of the path.
+    pub unsafe_ops: Vec<FileRange>,
+}
+
+// Feature: Unsafe Reachability
+//
+// Lists the safe, public functions of the current crate that end up, through calls, in
+// unsafe code, together with the shortest call path leading there. Only calls between
+// functions of the same crate are followed.
+pub(crate) fn unsafe_reachability(db: &RootDatabase, file_id: FileId) -> Vec<UnsafeReachability> {
+    let sema = Semantics::new(db);
+    let krate = match sema.to_module_def(file_id) {
+        Some(module) => module.krate(),
+        None => return Vec::new(),
+    };
+
+    let mut graph = FxHashMap::default();
+    for function in crate_functions(db, krate) {
+        if let Some(node) = call_graph_node(&sema, krate, function) {
+            graph.insert(function, node);
+        }
+    }
+
+    // Breadth first search backwards from the functions containing unsafe operations, so that
+    // every function learns about its shortest path to unsafe code.
+    let mut next: FxHashMap<hir::Function, Option<(hir::Function, FileRange)>> =
+        FxHashMap::default();
+    let mut callers: FxHashMap<hir::Function, Vec<(hir::Function, FileRange)>> =
+        FxHashMap::default();
+    let mut queue = VecDeque::new();
+    for (&function, node) in &graph {
+        for &(callee, call_site) in &node.calls {
+            callers.entry(callee).or_default().push((function, call_site));
+        }
+        if !node.unsafe_ops.is_empty() {
+            next.insert(function, None);
+            queue.push_back(function);
+        }
+    }
+    while let Some(callee) = queue.pop_front() {
+        for &(caller, call_site) in callers.get(&callee).into_iter().flatten() {
+            if let Entry::Vacant(entry) = next.entry(caller) {
+                entry.insert(Some((callee, call_site)));
+                queue.push_back(caller);
+            }
+        }
+    }
+
+    let mut res: Vec<_> = graph
+        .keys()
+        .copied()
+        .filter(|&function| next.contains_key(&function))
+        .filter(|&function| !function.is_unsafe_to_call(db) && is_public_api(db, function))
+        .filter_map(|entry| {
+            let mut path = Vec::new();
+            let mut call_sites = Vec::new();
+            let mut current = entry;
+            loop {
+                path.push(current.try_to_nav(db)?);
+                match next[&current] {
+                    Some((callee, call_site)) => {
+                        call_sites.push(call_site);
+                        current = callee;
+                    }
+                    None => break,
+                }
+            }
+            let unsafe_ops = graph[&current].unsafe_ops.clone();
+            Some(UnsafeReachability { path, call_sites, unsafe_ops })
+        })
+        .collect();
+    res.sort_by_key(|it| (it.path[0].file_id, it.path[0].full_range.start()));
+    res
+}
+
+struct CallGraphNode {
+    calls: Vec<(hir::Function, FileRange)>,
+    unsafe_ops: Vec<FileRange>,
+}
+
+fn call_graph_node(
+    sema: &Semantics<'_, RootDatabase>,
+    krate: hir::Crate,
+    function: hir::Function,
+) -> Option<CallGraphNode> {
+    let db = sema.db;
+    let src = sema.source(function)?;
+    let body = src.value.body()?;
+    let file_id = src.file_id.original_file(db);
+
+    let calls = call_hierarchy::calls_in_body(sema, body.syntax())
+        .into_iter()
+        .filter(|(callee, _)| callee.module(db).krate() == krate)
+        .map(|(callee, range)| {
+            let call_site = if src.file_id.is_macro() {
+                sema.original_range(body.syntax())
+            } else {
+                FileRange { file_id, range }
+            };
+            (callee, call_site)
+        })
+        .collect();
+    let unsafe_ops = sema
+        .unsafe_operations(&ast::Expr::BlockExpr(body))
+        .into_iter()
+        .map(|op| sema.original_range(op.expr.syntax()))
+        .collect();
+    Some(CallGraphNode { calls, unsafe_ops })
+}
+
+fn crate_functions(db: &RootDatabase, krate: hir::Crate) -> Vec<hir::Function> {
+    let assoc_functions = |items: Vec<hir::AssocItem>| {
+        items.into_iter().filter_map(|item| match item {
+            hir::AssocItem::Function(it) => Some(it),
+            _ => None,
+        })
+    };
+
+    let mut res = Vec::new();
+    let mut worklist = vec![krate.root_module(db)];
+    while let Some(module) = worklist.pop() {
+        for decl in module.declarations(db) {
+            match decl {
+                hir::ModuleDef::Function(it) => res.push(it),
+                hir::ModuleDef::Trait(it) => res.extend(assoc_functions(it.items(db))),
+                _ => (),
+            }
+        }
+        for impl_ in module.impl_defs(db) {
+            res.extend(assoc_functions(impl_.items(db)));
+        }
+        worklist.extend(module.children(db));
+    }
+    res
+}
+
+/// Whether `function` can be called from outside of its crate.
+fn is_public_api(db: &RootDatabase, function: hir::Function) -> bool {
+    let module_is_public = iter::successors(Some(function.module(db)), |it| it.parent(db))
+        .all(|module| module.visibility(db) == Visibility::Public);
+    if !module_is_public {
+        return false;
+    }
+    match function.as_assoc_item(db).map(|it| it.container(db)) {
+        Some(AssocItemContainer::Trait(trait_)) => trait_.visibility(db) == Visibility::Public,
+        Some(AssocItemContainer::Impl(impl_)) => match impl_.trait_(db) {
+            Some(trait_) => trait_.visibility(db) == Visibility::Public,
+            None => function.visibility(db) == Visibility::Public,
+        },
+        None => function.visibility(db) == Visibility::Public,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use itertools::Itertools;
+    use stdx::format_to;
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let paths = analysis.unsafe_reachability(file_id).unwrap();
+        let mut actual = String::new();
+        for it in paths {
+            format_to!(
+                actual,
+                "{} via {:?}: {:?}\n",
+                it.path.iter().map(|nav| &nav.name).join(" -> "),
+                it.call_sites.iter().map(|it| it.range).collect::<Vec<_>>(),
+                it.unsafe_ops.iter().map(|it| it.range).collect::<Vec<_>>(),
+            );
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn direct_unsafe_block() {
+        check(
+            r#"
+pub fn read(p: *const u8) -> u8 {
+    unsafe { *p }
+}
+fn private(p: *const u8) -> u8 {
+    unsafe { *p }
+}
+pub unsafe fn already_unsafe(p: *const u8) -> u8 {
+    *p
+}
+"#,
+            expect![[r#"
+                read via []: [47..49]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn transitive_calls() {
+        check(
+            r#"
+pub fn entry() -> u32 {
+    middle()
+}
+fn middle() -> u32 {
+    leaf()
+}
+fn leaf() -> u32 {
+    static mut COUNTER: u32 = 0;
+    unsafe { COUNTER }
+}
+pub fn safe() -> u32 {
+    0
+}
+"#,
+            expect![[r#"
+                entry -> middle -> leaf via [28..34, 64..68]: [138..145]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn methods_and_private_modules() {
+        check(
+            r#"
+pub struct S;
+impl S {
+    pub fn method(&self) {
+        helper()
+    }
+    fn private_method(&self) {
+        helper()
+    }
+}
+mod private {
+    pub fn hidden() {
+        crate::helper()
+    }
+}
+fn helper() {
+    unsafe { danger() }
+}
+unsafe fn danger() {}
+"#,
+            expect![[r#"
+                method -> helper via [58..64]: [224..232]
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,601 @@
+//! Structured unsafe-to-safe suggestions, built from the `convert_unsafe_to_safe` assist.
+
+use hir::Semantics;
+use hir::UnsafeContext;
+use ide_assists::{
+    handlers::convert_unsafe_to_safe::{
+        collect_unsafe_patterns, unsafe_block_pattern, unsafe_operation_at,
+    },
+    unsafe_to_safe_conversion, AssistConfig, UnsafeToSafeConfig, UnsafeToSafeConversion,
+};
+use ide_db::{
+    base_db::{FileId, FileLoader, FilePosition, FileRange},
+    imports::insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
+    RootDatabase,
+};
+use itertools::Itertools;
+use syntax::{ast, AstNode, SyntaxToken, TextRange, T};
+
+pub use ide_assists::handlers::convert_unsafe_to_safe::UnsafePattern;
+
+/// A rewrite of an unsafe block into safe code.
+#[derive(Debug, Clone)]
+pub struct UnsafeSuggestion {
+    /// The rewrite rule, displayed as the unsafe API it replaces, e.g. `get_unchecked`.
+    pub rule: UnsafePattern,
+    /// The unsafe block, or the statement of an `unsafe fn` body with the unsafe operation.
+    pub unsafe_block: TextRange,
+    /// The edits that turn the original code into the safe version.
+    pub edits: Vec<UnsafeSuggestionEdit>,
+    /// The measured runtime overhead of the safe version, like `7.58%` or `3.8x`.
+    pub overhead: Option<&'static str>,
+    pub explanation: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsafeSuggestionEdit {
+    pub range: TextRange,
+    pub original: String,
+    pub replacement: String,
+}
+
+// Feature: Unsafe Suggestions
+//
+// Lists the unsafe blocks of a file that the "Convert Unsafe to Safe" assist can rewrite,
+// along with the edits, the runtime overhead of the safe version and why the rewrite is sound.
+// The statements of `unsafe fn` bodies are listed as well. The hover of the `unsafe` keyword,
+// or of an unsafe operation, shows the same data.
+pub(crate) fn unsafe_suggestions(
+    db: &RootDatabase,
+    config: &AssistConfig,
+    frange: FileRange,
+) -> Vec<UnsafeSuggestion> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(frange.file_id);
+    let unsafe_tokens = source_file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|token| token.kind() == T![unsafe])
+        .filter(|token| token.text_range().intersect(frange.range).is_some());
+
+    let mut suggestions = Vec::new();
+    for token in unsafe_tokens {
+        if let Some(fn_def) = token.parent().and_then(ast::Fn::cast) {
+            suggestions.extend(unsafe_fn_suggestions(&sema, config, frange.file_id, &fn_def));
+            continue;
+        }
+        let suggestion = unsafe_block(&sema, token)
+            .and_then(|block| unsafe_suggestion(&sema, config, frange.file_id, &block));
+        suggestions.extend(suggestion);
+    }
+    suggestions
+}
+
+/// The rule the "Convert Unsafe to Safe" assist rewrites the unsafe block whose `unsafe` keyword
+/// is at `position` with, whether or not the rewrite applies.
+pub(crate) fn unsafe_block_rule(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<UnsafePattern> {
+    let sema = Semantics::new(db);
+    let token = sema
+        .parse(position.file_id)
+        .syntax()
+        .token_at_offset(position.offset)
+        .find(|token| token.kind() == T![unsafe])?;
+    unsafe_block_pattern(&sema, &unsafe_block(&sema, token)?)
+}
+
+/// The patterns of the "Convert Unsafe to Safe" assist the unsafe block whose `unsafe` keyword is
+/// at `position` matches, once per match.
+pub(crate) fn unsafe_block_patterns(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Vec<UnsafePattern> {
+    let sema = Semantics::new(db);
+    let token = sema
+        .parse(position.file_id)
+        .syntax()
+        .token_at_offset(position.offset)
+        .find(|token| token.kind() == T![unsafe]);
+    let block = match token.and_then(|token| unsafe_block(&sema, token)) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    collect_unsafe_patterns(&block).into_iter().map(|(_, pattern)| pattern).collect()
+}
+
+/// The suggestions for the statements of an `unsafe fn` body with unsafe operations outside of
+/// any unsafe block, one per statement.
+fn unsafe_fn_suggestions(
+    sema: &Semantics<'_, RootDatabase>,
+    config: &AssistConfig,
+    file_id: FileId,
+    fn_def: &ast::Fn,
+) -> Vec<UnsafeSuggestion> {
+    let body = match fn_def.body() {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    sema.unsafe_operations(&ast::Expr::BlockExpr(body))
+        .into_iter()
+        .filter(|op| matches!(sema.unsafe_context(&op.expr), Some(UnsafeContext::Function(_))))
+        .filter_map(|op| op.expr.syntax().first_token())
+        .filter_map(|token| unsafe_operation_suggestion(sema, config, file_id, &token))
+        .unique_by(|suggestion| suggestion.unsafe_block)
+        .collect()
+}
+
+/// The unsafe block of an `unsafe` keyword, looking into the expansion when it is passed to a
+/// macro.
+pub(crate) fn unsafe_block(
+    sema: &Semantics<'_, RootDatabase>,
+    token: SyntaxToken,
+) -> Option<ast::BlockExpr> {
+    if let Some(block) = token.parent().and_then(ast::BlockExpr::cast) {
+        return Some(block);
+    }
+    token.parent_ancestors().find_map(ast::MacroCall::cast)?;
+    sema.descend_into_macros(token)
+        .into_iter()
+        .find_map(|token| token.parent().and_then(ast::BlockExpr::cast))
+}
+
+/// Computes the suggestion for a single `unsafe` block.
+pub(crate) fn unsafe_suggestion(
+    sema: &Semantics<'_, RootDatabase>,
+    config: &AssistConfig,
+    file_id: FileId,
+    block: &ast::BlockExpr,
+) -> Option<UnsafeSuggestion> {
+    block.unsafe_token()?;
+
+    // Blocks passed to macros only exist in the expansion, the `unsafe` keyword starts the
+    // block in the macro call as well.
+    let original = sema.original_range_opt(block.syntax())?;
+    if original.file_id != file_id {
+        return None;
+    }
+
+    let conversion = unsafe_to_safe_conversion(
+        sema.db,
+        config,
+        FileRange { file_id, range: TextRange::empty(original.range.start()) },
+    )?;
+    Some(suggestion(sema, file_id, conversion, original.range))
+}
+
+/// Computes the suggestion for the unsafe operation `token` is part of, like the name of an
+/// unsafe function or the `*` of a dereference. That operation can be in an unsafe block or in
+/// the body of an `unsafe fn`.
+pub(crate) fn unsafe_operation_suggestion(
+    sema: &Semantics<'_, RootDatabase>,
+    config: &AssistConfig,
+    file_id: FileId,
+    token: &SyntaxToken,
+) -> Option<UnsafeSuggestion> {
+    // The lookup is cheap next to the rewrite, most tokens aren't part of an unsafe operation
+    unsafe_operation_at(sema, token)?;
+    let conversion = unsafe_to_safe_conversion(
+        sema.db,
+        config,
+        FileRange { file_id, range: TextRange::empty(token.text_range().start()) },
+    )?;
+    let range = conversion.source;
+    Some(suggestion(sema, file_id, conversion, range))
+}
+
+/// The suggestion of the rewrite `conversion` of the code at `range`.
+fn suggestion(
+    sema: &Semantics<'_, RootDatabase>,
+    file_id: FileId,
+    conversion: UnsafeToSafeConversion,
+    range: TextRange,
+) -> UnsafeSuggestion {
+    let text = sema.db.file_text(file_id);
+    let edits: Vec<_> = conversion
+        .edit
+        .into_iter()
+        .map(|indel| UnsafeSuggestionEdit {
+            range: indel.delete,
+            original: text[indel.delete].to_string(),
+            replacement: indel.insert,
+        })
+        .collect();
+
+    let rule = conversion.pattern;
+    let resizes_vec = edits.iter().any(|edit| edit.replacement.contains(".resize("));
+    let overhead = rule.runtime_overhead(resizes_vec);
+
+    UnsafeSuggestion { rule, unsafe_block: range, edits, overhead, explanation: explanation(rule) }
+}
+
+fn explanation(rule: UnsafePattern) -> &'static str {
+    match rule {
+        UnsafePattern::UnitializedVec => {
+            "`set_len` exposes uninitialized elements. Creating the buffer zero-initialized \
+             keeps the same length without reading uninitialized memory."
+        }
+        UnsafePattern::ReadBuffer => {
+            "The buffer is only filled by an `io::Read` method. `read` and `read_exact` take \
+             an initialized slice, so the buffer is created zeroed: zeroing is a `memset` of the \
+             same bytes the read copies in, far cheaper than the read itself once a system call \
+             is involved. `read_to_end` appends to the spare capacity on its own, limiting the \
+             reader with `take` keeps the length bound without zeroing. Reading into the spare \
+             capacity without zeroing at all needs a `BorrowedBuf` with `Read::read_buf`."
+        }
+        UnsafePattern::RawPointerRoundTrip => {
+            "The raw pointer is made from a reference in the same function and only ever \
+             dereferenced, while nothing else touches the borrowed place. A plain or scoped \
+             borrow of the place gives the same access and keeps it borrow-checked."
+        }
+        UnsafePattern::CopyWithin => {
+            "Both pointers point into the same slice, so `copy_within` performs the same \
+             overlapping copy with bounds checks."
+        }
+        UnsafePattern::CopyNonOverlap => {
+            "Source and destination are distinct slices, so `copy_from_slice` performs the \
+             same copy and checks that the lengths match."
+        }
+        UnsafePattern::GetUncheck | UnsafePattern::GetUncheckMut => {
+            "`get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` \
+             turns an invalid index into a panic."
+        }
+        UnsafePattern::CStringFromVec => {
+            "`CString::new` checks the bytes for interior nul bytes instead of assuming \
+             there are none."
+        }
+        UnsafePattern::CStringLength => {
+            "`CStr::to_bytes().len()` computes the length without calling into libc on a \
+             raw pointer."
+        }
+        UnsafePattern::BytesToUTFString
+        | UnsafePattern::BytesToUTFStringMut
+        | UnsafePattern::StringBytesToUTFString => {
+            "`from_utf8` validates the bytes instead of assuming they are valid UTF-8."
+        }
+        UnsafePattern::TransmuteTo => {
+            "The conversion functions of the standard library express the same \
+             reinterpretation without `transmute`."
+        }
+        UnsafePattern::TransmuteToEnum => {
+            "Transmuting an integer into an enum is undefined behavior for values that are not a \
+             discriminant. A `TryFrom` impl matching every discriminant rejects those values \
+             instead."
+        }
+        UnsafePattern::ReinterpretBytes => {
+            "A `u8` buffer is only aligned to 1 byte. Reading it as wider elements through a \
+             transmute, a cast pointer or `align_to` assumes an alignment the allocation never \
+             promised: a misaligned slice is undefined behavior, `align_to` moves the unaligned \
+             start into a prefix that gets dropped, and a transmuted `Vec` frees its buffer with \
+             the wrong layout. Reading the bytes in chunks of the element size with \
+             `from_ne_bytes`, `from_le_bytes` or `from_be_bytes` has no alignment requirement \
+             and fixes the byte order when the data comes from outside."
+        }
+        UnsafePattern::PinStackValue => {
+            "The pinned value is never used after it is pinned, so it can be moved into the pin: \
+             `core::pin::pin!` pins it on the stack, `Box::pin` on the heap. Neither can move it \
+             again, which is the promise `Pin::new_unchecked` asks for."
+        }
+        UnsafePattern::NonNullFromRef => {
+            "A pointer made from a reference is never null. `NonNull::from` takes the reference \
+             itself, so there is nothing left to promise."
+        }
+        UnsafePattern::ManuallyDropTake => {
+            "`ManuallyDrop::take` and `ManuallyDrop::drop` leave the slot logically \
+             uninitialized, and using it again is undefined behavior. `Option::take` leaves \
+             `None` behind, `mem::take` a default value, so a later use reads a valid value."
+        }
+        UnsafePattern::BoxRoundTrip => {
+            "The raw pointer from `Box::into_raw` is only dereferenced until `Box::from_raw` \
+             takes it back, and never escapes. Keeping the box gives the same access to the \
+             value, and frees it exactly once."
+        }
+        UnsafePattern::StringVecEdit => {
+            "Editing the bytes of a string is unsafe because the string has to stay UTF-8. \
+             The bytes added are ASCII or come from another string, so the methods of `String` \
+             make the same edit, and check char boundaries where the bytes didn't."
+        }
+        UnsafePattern::StringRawParts => {
+            "The pointer, length and capacity come from a string nothing touches until \
+             `String::from_raw_parts` puts it back together. Moving the string itself keeps \
+             the same buffer; a length passed as the capacity needs the buffer shrunk to fit, \
+             which `into_boxed_str` does."
+        }
+        UnsafePattern::KnownUtf8 => {
+            "The bytes are those of a string, like one made by `to_string()` or `format!`, or \
+             an ASCII literal, so they are UTF-8 by construction. Using the string itself \
+             skips both the check and the `unsafe`."
+        }
+        UnsafePattern::ConstantArgument => {
+            "The argument is a constant, and it meets the precondition of the constructor. The \
+             literal of the constructed value needs no check at runtime, and no `unsafe`."
+        }
+        UnsafePattern::ReadUnaligned => {
+            "`from_ne_bytes` reads the value from a byte array, which has no alignment \
+             requirement."
+        }
+        UnsafePattern::FromU32Unchecked => {
+            "`char::from_u32` returns `None` for values that are not valid scalar values."
+        }
+        _ => "The safe version performs the same operation with its invariants checked.",
+    }
+}
+
+/// The assist config used to preview suggestions in hovers.
+pub(crate) fn hover_assist_config(unsafe_to_safe: &UnsafeToSafeConfig) -> AssistConfig {
+    AssistConfig {
+        snippet_cap: None,
+        allowed: None,
+        insert_use: InsertUseConfig {
+            granularity: ImportGranularity::Crate,
+            enforce_granularity: true,
+            prefix_kind: PrefixKind::Plain,
+            group: true,
+            skip_glob_imports: true,
+        },
+        prefer_no_std: false,
+        unsafe_to_safe: unsafe_to_safe.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::base_db::FileRange;
+    use syntax::TextRange;
+
+    use ide_assists::UnsafeToSafeConfig;
+
+    use crate::{fixture, unsafe_suggestions::hover_assist_config, UnsafePattern};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let text = analysis.file_text(file_id).unwrap();
+        let frange = FileRange { file_id, range: TextRange::up_to((text.len() as u32).into()) };
+        let suggestions = analysis
+            .unsafe_suggestions(&hover_assist_config(&UnsafeToSafeConfig::DEFAULT), frange)
+            .unwrap();
+        expect.assert_debug_eq(&suggestions);
+    }
+
+    #[test]
+    fn get_unchecked() {
+        check(
+            r#"
+fn main() {
+    let vec = vec![1, 2, 3];
+    let value = unsafe { vec.get_unchecked(1) };
+}
+"#,
+            expect![[r#"
+                [
+                    UnsafeSuggestion {
+                        rule: GetUncheck,
+                        unsafe_block: 57..88,
+                        edits: [
+                            UnsafeSuggestionEdit {
+                                range: 57..88,
+                                original: "unsafe { vec.get_unchecked(1) }",
+                                replacement: "vec.get(1).unwrap()",
+                            },
+                        ],
+                        overhead: Some(
+                            "7.58%",
+                        ),
+                        explanation: "`get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` turns an invalid index into a panic.",
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn uninitialized_vec() {
+        check(
+            r#"
+fn main() {
+    let cap = 10;
+    let mut buffer = Vec::with_capacity(cap);
+    unsafe {
+        buffer.set_len(cap);
+    }
+}
+"#,
+            expect![[r#"
+                [
+                    UnsafeSuggestion {
+                        rule: UnitializedVec,
+                        unsafe_block: 80..123,
+                        edits: [
+                            UnsafeSuggestionEdit {
+                                range: 34..75,
+                                original: "let mut buffer = Vec::with_capacity(cap);",
+                                replacement: "let mut buffer = vec![0; cap];",
+                            },
+                            UnsafeSuggestionEdit {
+                                range: 75..123,
+                                original: "\n    unsafe {\n        buffer.set_len(cap);\n    }",
+                                replacement: "",
+                            },
+                        ],
+                        overhead: Some(
+                            "-0.05%",
+                        ),
+                        explanation: "`set_len` exposes uninitialized elements. Creating the buffer zero-initialized keeps the same length without reading uninitialized memory.",
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn reinterpret_bytes() {
+        check(
+            r#"
+mod slice {
+    pub unsafe fn from_raw_parts<'a, T>(data: *const T, len: usize) -> &'a [T] { loop {} }
+}
+fn main(bytes: &[u8]) {
+    let words: &[u32] = unsafe { slice::from_raw_parts(bytes.as_ptr() as *const u32, bytes.len() / 4) };
+}
+"#,
+            expect![[r#"
+                [
+                    UnsafeSuggestion {
+                        rule: ReinterpretBytes,
+                        unsafe_block: 153..232,
+                        edits: [
+                            UnsafeSuggestionEdit {
+                                range: 153..232,
+                                original: "unsafe { slice::from_raw_parts(bytes.as_ptr() as *const u32, bytes.len() / 4) }",
+                                replacement: "&bytes.chunks_exact(4).map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap())).collect::<Vec<_>>()",
+                            },
+                        ],
+                        overhead: None,
+                        explanation: "A `u8` buffer is only aligned to 1 byte. Reading it as wider elements through a transmute, a cast pointer or `align_to` assumes an alignment the allocation never promised: a misaligned slice is undefined behavior, `align_to` moves the unaligned start into a prefix that gets dropped, and a transmuted `Vec` frees its buffer with the wrong layout. Reading the bytes in chunks of the element size with `from_ne_bytes`, `from_le_bytes` or `from_be_bytes` has no alignment requirement and fixes the byte order when the data comes from outside.",
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unsafe_fn_body() {
+        check(
+            r#"
+#[lang = "slice"]
+impl<T> [T] {
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T { loop {} }
+}
+unsafe fn second(vec: &[i32]) -> i32 {
+    let value = *vec.get_unchecked(1);
+    value
+}
+"#,
+            expect![[r#"
+                [
+                    UnsafeSuggestion {
+                        rule: GetUncheck,
+                        unsafe_block: 148..182,
+                        edits: [
+                            UnsafeSuggestionEdit {
+                                range: 160..181,
+                                original: "*vec.get_unchecked(1)",
+                                replacement: "*vec.get(1).unwrap()",
+                            },
+                        ],
+                        overhead: Some(
+                            "7.58%",
+                        ),
+                        explanation: "`get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` turns an invalid index into a panic.",
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_suggestion_without_pattern() {
+        check(
+            r#"
+fn main(p: *const u8) {
+    let value = unsafe { *p };
+}
+"#,
+            expect![[r#"
+                []
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_suggestion_that_does_not_type_check() {
+        check(
+            r#"
+//- minicore: option
+struct Buffer;
+
+impl Buffer {
+    unsafe fn get_unchecked(&self, index: usize) -> &u8 { loop {} }
+}
+
+fn main() {
+    let buffer = Buffer;
+    let value = unsafe { *buffer.get_unchecked(1) };
+}
+"#,
+            expect![[r#"
+                []
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unsafe_block_in_macro_call() {
+        check(
+            r#"
+macro_rules! id {
+    ($e:expr) => { $e };
+}
+
+fn main() {
+    let vec = vec![1, 2, 3];
+    let value = id!(unsafe { *vec.get_unchecked(1) });
+}
+"#,
+            expect![[r#"
+                [
+                    UnsafeSuggestion {
+                        rule: GetUncheck,
+                        unsafe_block: 107..139,
+                        edits: [
+                            UnsafeSuggestionEdit {
+                                range: 107..139,
+                                original: "unsafe { *vec.get_unchecked(1) }",
+                                replacement: "*vec.get(1).unwrap()",
+                            },
+                        ],
+                        overhead: Some(
+                            "7.58%",
+                        ),
+                        explanation: "`get`/`get_mut` return `None` instead of reading out of bounds; the `unwrap` turns an invalid index into a panic.",
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn rule_of_block_with_semantic_rewrite() {
+        let (analysis, position) = fixture::position(
+            r#"
+fn main() {
+    let mut x = 0;
+    let p = &mut x as *mut i32;
+    $0unsafe { *p = 1 };
+}
+"#,
+        );
+        assert_eq!(
+            analysis.unsafe_block_rule(position).unwrap(),
+            Some(UnsafePattern::RawPointerRoundTrip)
+        );
+    }
+
+    #[test]
+    fn rule_of_block_whose_rewrite_does_not_apply() {
+        let (analysis, position) = fixture::position(
+            r#"
+fn main() {
+    let mut x = 0;
+    let p = &mut x as *mut i32;
+    x = 2;
+    $0unsafe { *p = 1 };
+}
+"#,
+        );
+        assert_eq!(analysis.unsafe_block_rule(position).unwrap(), None);
+    }
+}
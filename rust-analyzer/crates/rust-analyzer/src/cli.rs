@@ -7,6 +7,9 @@ mod symbols;
 mod highlight;
 mod analysis_stats;
 mod diagnostics;
+mod unsafe_surface;
+mod unsafe_classify;
+mod unsafe_fix;
 mod ssr;
 mod lsif;
 mod scip;
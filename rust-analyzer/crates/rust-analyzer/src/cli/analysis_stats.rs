@@ -2,13 +2,14 @@
 //! errors.
 
 use std::{
+    collections::BTreeMap,
     env,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use hir::{
     db::{AstDatabase, DefDatabase, HirDatabase},
-    AssocItem, Crate, Function, HasSource, HirDisplay, ModuleDef,
+    AssocItem, Crate, Function, HasSource, HirDisplay, ModuleDef, Semantics, UnsafeOpKind,
 };
 use hir_def::{
     body::{BodySourceMap, SyntheticSyntax},
@@ -16,7 +17,7 @@ use hir_def::{
     FunctionId,
 };
 use hir_ty::{TyExt, TypeWalk};
-use ide::{Analysis, AnalysisHost, LineCol, RootDatabase};
+use ide::{Analysis, AnalysisHost, Cancellable, FilePosition, FileRange, LineCol, RootDatabase};
 use ide_db::base_db::{
     salsa::{self, debug::DebugQueryTable, ParallelDatabase},
     SourceDatabase, SourceDatabaseExt,
@@ -27,8 +28,9 @@ use profile::{Bytes, StopWatch};
 use project_model::{CargoConfig, ProjectManifest, ProjectWorkspace};
 use rayon::prelude::*;
 use rustc_hash::FxHashSet;
+use serde::Serialize;
 use stdx::format_to;
-use syntax::{AstNode, SyntaxNode};
+use syntax::{ast, AstNode, SyntaxNode};
 use vfs::{AbsPathBuf, Vfs, VfsPath};
 
 use crate::cli::{
@@ -36,7 +38,7 @@ use crate::cli::{
     load_cargo::{load_workspace, LoadCargoConfig},
     print_memory_usage,
     progress_report::ProgressReport,
-    report_metric, Result, Verbosity,
+    report_metric, unsafe_fix, Result, Verbosity,
 };
 
 /// Need to wrap Snapshot to provide `Clone` impl for `map_with`
@@ -92,6 +94,7 @@ impl flags::AnalysisStats {
 
         let mut analysis_sw = self.stop_watch();
         let mut num_crates = 0;
+        let mut analyzed_krates = Vec::new();
         let mut visited_modules = FxHashSet::default();
         let mut visit_queue = Vec::new();
 
@@ -107,6 +110,7 @@ impl flags::AnalysisStats {
             let source_root = db.source_root(source_root);
             if !source_root.is_library || self.with_deps {
                 num_crates += 1;
+                analyzed_krates.push(krate);
                 visit_queue.push(module);
             }
         }
@@ -179,6 +183,10 @@ impl flags::AnalysisStats {
             eprintln!("source files: {}, macro files: {}", total_file_size, total_macro_file_size);
         }
 
+        if self.unsafe_stats {
+            self.run_unsafe_stats(&host, &analyzed_krates)?;
+        }
+
         if self.memory_usage && verbosity.is_verbose() {
             print_memory_usage(host, vfs);
         }
@@ -375,11 +383,161 @@ impl flags::AnalysisStats {
         eprintln!("{:<20} {}", "Inference:", inference_sw.elapsed());
     }
 
+    fn run_unsafe_stats(&self, host: &AnalysisHost, krates: &[Crate]) -> Result<()> {
+        let sema = Semantics::new(host.raw_database());
+        let analysis = host.analysis();
+        let stats = krates
+            .iter()
+            .map(|&krate| unsafe_stats(&sema, &analysis, krate))
+            .collect::<Cancellable<Vec<_>>>()?;
+
+        if self.output == Some(OutputFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+            return Ok(());
+        }
+
+        println!(
+            "{:<24} {:>7} {:>5} {:>6} {:>7} {:>7} {:>6} {:>8} {:>11}",
+            "crate", "blocks", "fns", "impls", "traits", "derefs", "calls", "statics", "eliminable"
+        );
+        for it in &stats {
+            println!(
+                "{:<24} {:>7} {:>5} {:>6} {:>7} {:>7} {:>6} {:>8} {:>11}",
+                it.krate,
+                it.unsafe_blocks,
+                it.unsafe_fns,
+                it.unsafe_impls,
+                it.unsafe_traits,
+                it.raw_ptr_derefs,
+                it.unsafe_calls,
+                it.mutable_statics,
+                it.eliminable_blocks,
+            );
+            for (pattern, count) in &it.patterns {
+                println!("    matches of {}: {}", pattern, count);
+            }
+            for (rule, count) in &it.rules {
+                println!("    blocks rewritten with {}: {}", rule, count);
+            }
+        }
+        Ok(())
+    }
+
     fn stop_watch(&self) -> StopWatch {
         StopWatch::start().memory(self.memory_usage)
     }
 }
 
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnsafeStats {
+    #[serde(rename = "crate")]
+    krate: String,
+    unsafe_blocks: u64,
+    unsafe_fns: u64,
+    unsafe_impls: u64,
+    unsafe_traits: u64,
+    raw_ptr_derefs: u64,
+    unsafe_calls: u64,
+    mutable_statics: u64,
+    /// Matches per `UnsafePattern` of `convert_unsafe_to_safe`.
+    patterns: BTreeMap<String, u64>,
+    /// Unsafe blocks per rule `convert_unsafe_to_safe` rewrites them with.
+    rules: BTreeMap<String, u64>,
+    /// Unsafe blocks `convert_unsafe_to_safe` turns into safe code.
+    eliminable_blocks: u64,
+}
+
+fn unsafe_stats(
+    sema: &Semantics<'_, RootDatabase>,
+    analysis: &Analysis,
+    krate: Crate,
+) -> Cancellable<UnsafeStats> {
+    let db = sema.db;
+    let config = unsafe_fix::assist_config();
+    let mut stats = UnsafeStats {
+        krate: krate.display_name(db).as_deref().unwrap_or("unknown").to_string(),
+        ..Default::default()
+    };
+    let as_function = |item| match item {
+        AssocItem::Function(f) => Some(f),
+        _ => None,
+    };
+
+    let mut funcs = Vec::new();
+    let mut visit_queue = vec![krate.root_module(db)];
+    while let Some(module) = visit_queue.pop() {
+        visit_queue.extend(module.children(db));
+        for decl in module.declarations(db) {
+            match decl {
+                ModuleDef::Function(f) => funcs.push(f),
+                ModuleDef::Trait(t) => {
+                    if t.is_unsafe(db) {
+                        stats.unsafe_traits += 1;
+                    }
+                    funcs.extend(t.items(db).into_iter().filter_map(as_function));
+                }
+                _ => (),
+            }
+        }
+        for impl_def in module.impl_defs(db) {
+            if impl_def.source(db).map_or(false, |src| src.value.unsafe_token().is_some()) {
+                stats.unsafe_impls += 1;
+            }
+            funcs.extend(impl_def.items(db).into_iter().filter_map(as_function));
+        }
+    }
+
+    for f in funcs {
+        let src = match sema.source(f) {
+            Some(it) => it,
+            None => continue,
+        };
+        if src.value.unsafe_token().is_some() {
+            stats.unsafe_fns += 1;
+        }
+        let body = match src.value.body() {
+            Some(it) => it,
+            None => continue,
+        };
+        for op in sema.unsafe_operations(&ast::Expr::BlockExpr(body.clone())) {
+            match op.kind {
+                UnsafeOpKind::UnsafeCall => stats.unsafe_calls += 1,
+                UnsafeOpKind::RawPtrDeref => stats.raw_ptr_derefs += 1,
+                UnsafeOpKind::MutableStatic => stats.mutable_statics += 1,
+            }
+        }
+
+        let unsafe_blocks = body
+            .syntax()
+            .descendants()
+            .filter_map(ast::BlockExpr::cast)
+            .filter(|it| it.unsafe_token().is_some());
+        for block in unsafe_blocks {
+            stats.unsafe_blocks += 1;
+            // The assist only rewrites blocks written in a file
+            let (file_id, unsafe_kw) = match block.unsafe_token() {
+                Some(it) if !src.file_id.is_macro() => (src.file_id.original_file(db), it),
+                _ => continue,
+            };
+            let position = FilePosition { file_id, offset: unsafe_kw.text_range().start() };
+            for pattern in analysis.unsafe_block_patterns(position)? {
+                *stats.patterns.entry(pattern.to_string()).or_default() += 1;
+            }
+            if let Some(rule) = analysis.unsafe_block_rule(position)? {
+                *stats.rules.entry(rule.to_string()).or_default() += 1;
+            }
+            let range = block.syntax().text_range();
+            let suggestions = analysis.unsafe_suggestions(&config, FileRange { file_id, range })?;
+            if suggestions.iter().any(|it| it.unsafe_block == range) {
+                stats.eliminable_blocks += 1;
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
 fn location_csv(
     db: &RootDatabase,
     analysis: &Analysis,
@@ -446,3 +604,50 @@ fn syntax_len(node: SyntaxNode) -> usize {
     // to make macro and non-macro code comparable.
     node.to_string().replace(|it: char| it.is_ascii_whitespace(), "").len()
 }
+
+#[cfg(test)]
+mod tests {
+    use ide_db::base_db::fixture::ChangeFixture;
+
+    use super::*;
+
+    #[test]
+    fn unsafe_stats_count_blocks_the_assist_rewrites() {
+        let mut host = AnalysisHost::default();
+        host.raw_database_mut().apply_change(
+            ChangeFixture::parse(
+                r#"
+fn main() {
+    let vec = vec![1, 2, 3];
+    let value = unsafe { vec.get_unchecked(1) };
+    let sum = unsafe { vec.get_unchecked(0) + vec.get_unchecked(2) };
+
+    let mut x = 0;
+    let p = &mut x as *mut i32;
+    unsafe { *p = 1 };
+
+    let q = &mut x as *mut i32;
+    x = 2;
+    unsafe { *q = 3 };
+}
+"#,
+            )
+            .change,
+        );
+        let sema = Semantics::new(host.raw_database());
+        let krate = Crate::all(host.raw_database())[0];
+
+        let stats = unsafe_stats(&sema, &host.analysis(), krate).unwrap();
+        assert_eq!(stats.unsafe_blocks, 4);
+        assert_eq!(stats.raw_ptr_derefs, 2);
+        assert_eq!(stats.eliminable_blocks, 3);
+        assert_eq!(
+            stats.patterns.into_iter().collect::<Vec<_>>(),
+            vec![("get_unchecked".to_string(), 3)]
+        );
+        assert_eq!(
+            stats.rules.into_iter().collect::<Vec<_>>(),
+            vec![("get_unchecked".to_string(), 2), ("raw_pointer_round_trip".to_string(), 1)]
+        );
+    }
+}
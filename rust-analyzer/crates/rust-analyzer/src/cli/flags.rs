@@ -68,6 +68,9 @@ xflags::xflags! {
             optional --memory-usage
             /// Print the total length of all source and macro files (whitespace is not counted).
             optional --source-stats
+            /// Print per-crate counts of unsafe blocks, items and operations, and of the
+            /// patterns `convert_unsafe_to_safe` can rewrite.
+            optional --unsafe-stats
 
             /// Only analyze items matching this path.
             optional -o, --only path: String
@@ -94,6 +97,48 @@ xflags::xflags! {
             optional --disable-proc-macros
         }
 
+        /// Print the safe public functions of every workspace crate that reach unsafe code.
+        cmd unsafe-surface
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+        {
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
+
+        /// Print why every unsafe block and `unsafe impl` of the workspace crates needs `unsafe`.
+        cmd unsafe-classify
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+        {
+            optional --output format: OutputFormat
+
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
+
+        /// Rewrite the unsafe blocks of every workspace crate into safe code where possible.
+        cmd unsafe-fix
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+        {
+            /// Print the changes as unified diffs instead of writing them to disk.
+            optional --dry-run
+            /// Only apply these comma-separated rules, like `get_unchecked,set_len`.
+            optional --rules rules: String
+            /// JSON file with the `rust-analyzer` settings of the workspace, as the editor sends
+            /// them, for the `unsafeToSafe` rules, error style and overhead limit.
+            optional --config config: PathBuf
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
+
         cmd ssr
             /// A structured search replace rule (`$a.foo($b) ==> bar($a, $b)`)
             repeated rule: SsrRule
@@ -140,6 +185,9 @@ pub enum RustAnalyzerCmd {
     Highlight(Highlight),
     AnalysisStats(AnalysisStats),
     Diagnostics(Diagnostics),
+    UnsafeSurface(UnsafeSurface),
+    UnsafeClassify(UnsafeClassify),
+    UnsafeFix(UnsafeFix),
     Ssr(Ssr),
     Search(Search),
     ProcMacro(ProcMacro),
@@ -176,6 +224,7 @@ pub struct AnalysisStats {
     pub parallel: bool,
     pub memory_usage: bool,
     pub source_stats: bool,
+    pub unsafe_stats: bool,
     pub only: Option<String>,
     pub with_deps: bool,
     pub no_sysroot: bool,
@@ -192,6 +241,34 @@ pub struct Diagnostics {
     pub disable_proc_macros: bool,
 }
 
+#[derive(Debug)]
+pub struct UnsafeSurface {
+    pub path: PathBuf,
+
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
+#[derive(Debug)]
+pub struct UnsafeClassify {
+    pub path: PathBuf,
+
+    pub output: Option<OutputFormat>,
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
+#[derive(Debug)]
+pub struct UnsafeFix {
+    pub path: PathBuf,
+
+    pub dry_run: bool,
+    pub rules: Option<String>,
+    pub config: Option<PathBuf>,
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
 #[derive(Debug)]
 pub struct Ssr {
     pub rule: Vec<SsrRule>,
@@ -235,6 +312,7 @@ impl RustAnalyzer {
 #[derive(Debug, PartialEq, Eq)]
 pub enum OutputFormat {
     Csv,
+    Json,
 }
 
 impl RustAnalyzer {
@@ -256,6 +334,7 @@ impl FromStr for OutputFormat {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
             _ => Err(format!("unknown output format `{}`", s)),
         }
     }
@@ -0,0 +1,130 @@
+//! Prints why every unsafe block and `unsafe impl` of the workspace crates needs `unsafe`.
+
+use std::collections::BTreeSet;
+
+use hir::Crate;
+use ide::{Analysis, FileId, FileRange, TextRange};
+use ide_db::base_db::SourceDatabaseExt;
+use itertools::Itertools;
+use serde::Serialize;
+use vfs::Vfs;
+
+use crate::cli::{
+    flags::{self, OutputFormat},
+    load_cargo::{load_workspace_at, LoadCargoConfig},
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClassifiedBlock {
+    location: String,
+    kind: &'static str,
+    avoidable: bool,
+    categories: Vec<String>,
+    reasons: Vec<ClassifiedReason>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClassifiedReason {
+    code: &'static str,
+    location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    safe_twin: Option<&'static str>,
+}
+
+impl flags::UnsafeClassify {
+    pub fn run(self) -> anyhow::Result<()> {
+        let cargo_config = Default::default();
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro: !self.disable_proc_macros,
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
+        let db = host.raw_database();
+        let analysis = host.analysis();
+
+        let files: BTreeSet<FileId> = Crate::all(db)
+            .into_iter()
+            .flat_map(|krate| krate.modules(db))
+            .map(|module| module.definition_source(db).file_id.original_file(db))
+            .filter(|&file_id| !db.source_root(db.file_source_root(file_id)).is_library)
+            .collect();
+
+        let mut blocks = Vec::new();
+        for file_id in files {
+            let text = analysis.file_text(file_id)?;
+            let frange = FileRange { file_id, range: TextRange::up_to((text.len() as u32).into()) };
+            for it in analysis.unsafe_classification(frange)? {
+                let location = |range: TextRange| {
+                    format_location(&analysis, &vfs, FileRange { file_id, range })
+                };
+                let reasons = it
+                    .reasons
+                    .iter()
+                    .map(|reason| {
+                        Ok(ClassifiedReason {
+                            code: reason.code.as_str(),
+                            location: location(reason.range)?,
+                            safe_twin: reason.safe_twin,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                blocks.push(ClassifiedBlock {
+                    location: location(it.range)?,
+                    kind: if it.is_impl { "impl" } else { "block" },
+                    avoidable: it.is_avoidable(),
+                    categories: it.categories().iter().map(ToString::to_string).collect(),
+                    reasons,
+                });
+            }
+        }
+
+        match self.output {
+            Some(OutputFormat::Json) => {
+                println!("{}", serde_json::to_string_pretty(&blocks)?);
+            }
+            Some(OutputFormat::Csv) => {
+                println!("location,kind,avoidable,codes");
+                for it in &blocks {
+                    let codes = it.reasons.iter().map(|reason| reason.code).join(" ");
+                    println!("{},{},{},{}", it.location, it.kind, it.avoidable, codes);
+                }
+            }
+            None => {
+                let avoidable = blocks.iter().filter(|it| it.avoidable).count();
+                println!("{} unsafe blocks and impls, {} avoidable", blocks.len(), avoidable);
+                for it in &blocks {
+                    println!(
+                        "{} {} {}: {}",
+                        it.location,
+                        it.kind,
+                        if it.avoidable { "avoidable" } else { "necessary" },
+                        it.categories.join(", "),
+                    );
+                    for reason in &it.reasons {
+                        match reason.safe_twin {
+                            Some(twin) => {
+                                println!(
+                                    "    {} ({}), use `{}`",
+                                    reason.code, reason.location, twin
+                                )
+                            }
+                            None => println!("    {} ({})", reason.code, reason.location),
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn format_location(analysis: &Analysis, vfs: &Vfs, frange: FileRange) -> anyhow::Result<String> {
+    let line_index = analysis.file_line_index(frange.file_id)?;
+    let line_col = line_index.line_col(frange.range.start());
+    Ok(format!("{}:{}:{}", vfs.file_path(frange.file_id), line_col.line + 1, line_col.col + 1))
+}
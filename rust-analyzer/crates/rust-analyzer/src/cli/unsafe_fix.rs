@@ -0,0 +1,612 @@
+//! Applies the unsafe-to-safe rewrites to every workspace crate from the command line.
+
+use std::{collections::BTreeSet, path::Path};
+
+use hir::{Crate, Semantics, UnsafeContext};
+use ide::{
+    Analysis, AssistConfig, FileId, FilePosition, FileRange, Indel, SourceChange, TextEdit,
+    TextRange, TextSize, UnsafeSuggestion, UnsafeToSafeConfig,
+};
+use ide_db::{
+    base_db::SourceDatabaseExt,
+    imports::insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
+    RootDatabase,
+};
+use lsp_types::ClientCapabilities;
+use rustc_hash::FxHashMap;
+use syntax::{ast, AstNode, SyntaxToken, T};
+use vfs::{AbsPathBuf, Vfs};
+
+use crate::{
+    cli::{
+        flags,
+        load_cargo::{load_workspace_at, LoadCargoConfig},
+    },
+    config::Config,
+};
+
+/// Lines of unchanged text around each hunk of the printed diffs.
+const CONTEXT_LINES: usize = 3;
+
+impl flags::UnsafeFix {
+    pub fn run(self) -> anyhow::Result<()> {
+        let cargo_config = Default::default();
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro: !self.disable_proc_macros,
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
+        let db = host.raw_database();
+        let analysis = host.analysis();
+        let sema = Semantics::new(db);
+        let config = match &self.config {
+            Some(path) => workspace_assist_config(&self.path, path)?,
+            None => assist_config(),
+        };
+        let rules: Option<Vec<&str>> =
+            self.rules.as_deref().map(|rules| rules.split(',').map(str::trim).collect());
+
+        let files: BTreeSet<FileId> = Crate::all(db)
+            .into_iter()
+            .flat_map(|krate| krate.modules(db))
+            .map(|module| module.definition_source(db).file_id.original_file(db))
+            .filter(|&file_id| !db.source_root(db.file_source_root(file_id)).is_library)
+            .collect();
+
+        let mut source_change = SourceChange::default();
+        let (mut converted, mut skipped) = (0, 0);
+        for file_id in files {
+            let file = sema.parse(file_id);
+            let unsafe_code: Vec<_> = file
+                .syntax()
+                .descendants_with_tokens()
+                .filter_map(|it| it.into_token())
+                .filter(|token| token.kind() == T![unsafe])
+                .filter_map(|token| unsafe_code(&sema, token))
+                .collect();
+            if unsafe_code.is_empty() {
+                continue;
+            }
+
+            let full_range = FileRange { file_id, range: file.syntax().text_range() };
+            let mut suggestions: FxHashMap<_, _> = analysis
+                .unsafe_suggestions(&config, full_range)?
+                .into_iter()
+                .map(|it| (it.unsafe_block, it))
+                .collect();
+
+            let mut accepted: Vec<Indel> = Vec::new();
+            for code in unsafe_code {
+                let (offset, block, in_macro) = match code {
+                    UnsafeCode::Block { offset, block, in_macro } => (offset, block, in_macro),
+                    UnsafeCode::FnBody(body) => {
+                        // The suggestions of an `unsafe fn` body rewrite the statement of each
+                        // unsafe operation, several operations can share one.
+                        let mut statements: Vec<TextRange> = Vec::new();
+                        for op in unsafe_fn_operations(&sema, &body, file_id) {
+                            if statements.iter().any(|it| it.contains_range(op)) {
+                                continue;
+                            }
+                            let statement =
+                                suggestions.keys().copied().find(|it| it.contains_range(op));
+                            let suggestion = match statement.and_then(|it| suggestions.remove(&it))
+                            {
+                                Some(it) => it,
+                                None => {
+                                    skipped += 1;
+                                    let location = format_location(
+                                        &analysis,
+                                        &vfs,
+                                        FileRange { file_id, range: op },
+                                    )?;
+                                    println!(
+                                        "skipped {}: no rule rewrites this operation of an `unsafe fn` body",
+                                        location
+                                    );
+                                    continue;
+                                }
+                            };
+                            statements.push(suggestion.unsafe_block);
+                            let location = format_location(
+                                &analysis,
+                                &vfs,
+                                FileRange { file_id, range: suggestion.unsafe_block },
+                            )?;
+                            let rule = suggestion.rule.to_string();
+                            if !rules.as_ref().map_or(true, |rules| rules.contains(&rule.as_str()))
+                            {
+                                skipped += 1;
+                                println!(
+                                    "skipped {} ({}): the rule is not selected by --rules",
+                                    location, rule
+                                );
+                                continue;
+                            }
+                            if !accept(&mut accepted, suggestion) {
+                                skipped += 1;
+                                println!(
+                                    "skipped {} ({}): the rewrite overlaps the rewrite of another block",
+                                    location, rule
+                                );
+                                continue;
+                            }
+                            converted += 1;
+                            println!("converted {} ({})", location, rule);
+                        }
+                        continue;
+                    }
+                };
+                let range = match sema.original_range_opt(block.syntax()) {
+                    Some(it) if it.file_id == file_id => it.range,
+                    _ => continue,
+                };
+                let location = format_location(&analysis, &vfs, FileRange { file_id, range })?;
+                // The rule the assist picks, semantic rules included, even when the rewrite
+                // doesn't apply
+                let rule = match suggestions.get(&range) {
+                    Some(suggestion) => Some(suggestion.rule),
+                    None => analysis.unsafe_block_rule(FilePosition { file_id, offset })?,
+                };
+                let rule = match rule {
+                    Some(rule) => rule.to_string(),
+                    None => {
+                        skipped += 1;
+                        println!("skipped {}: no rule matches this block", location);
+                        continue;
+                    }
+                };
+                if !rules.as_ref().map_or(true, |rules| rules.contains(&rule.as_str())) {
+                    skipped += 1;
+                    println!(
+                        "skipped {} ({}): the rule is not selected by --rules",
+                        location, rule
+                    );
+                    continue;
+                }
+                let suggestion = match suggestions.remove(&range) {
+                    Some(it) => it,
+                    None => {
+                        skipped += 1;
+                        let krate = sema
+                            .scope(block.syntax())
+                            .and_then(|scope| scope.krate().display_name(db))
+                            .map(|name| name.to_string());
+                        let reason = if !config
+                            .unsafe_to_safe
+                            .is_rule_enabled(&rule, krate.as_deref())
+                        {
+                            "the rule is disabled by the workspace config"
+                        } else if in_macro {
+                            "the rewrite does not apply, does not type-check or changes the macro definition"
+                        } else {
+                            "the rewrite does not apply or does not type-check"
+                        };
+                        println!("skipped {} ({}): {}", location, rule, reason);
+                        continue;
+                    }
+                };
+
+                if !accept(&mut accepted, suggestion) {
+                    skipped += 1;
+                    println!(
+                        "skipped {} ({}): the rewrite overlaps the rewrite of another block",
+                        location, rule
+                    );
+                    continue;
+                }
+                converted += 1;
+                println!("converted {} ({})", location, rule);
+            }
+
+            if !accepted.is_empty() {
+                let mut builder = TextEdit::builder();
+                for indel in accepted {
+                    builder.replace(indel.delete, indel.insert);
+                }
+                source_change.insert_source_edit(file_id, builder.finish());
+            }
+        }
+
+        let mut edits: Vec<_> = source_change.source_file_edits.into_iter().collect();
+        edits.sort_by_key(|(file_id, _)| *file_id);
+        for (file_id, edit) in edits {
+            let before = db.file_text(file_id);
+            let path = vfs.file_path(file_id);
+            if self.dry_run {
+                print!("{}", unified_diff(&path.to_string(), &before, &edit));
+            } else if let Some(path) = path.as_path() {
+                let mut after = before.to_string();
+                edit.apply(&mut after);
+                std::fs::write(path, after)?;
+            }
+        }
+
+        println!();
+        println!(
+            "{} unsafe blocks and `unsafe fn` statements converted, {} skipped",
+            converted, skipped
+        );
+        Ok(())
+    }
+}
+
+/// The code an `unsafe` keyword allows unsafe operations in.
+enum UnsafeCode {
+    /// An unsafe block, with the offset of its keyword and whether it comes from the expansion of
+    /// a macro call the keyword is passed to.
+    Block { offset: TextSize, block: ast::BlockExpr, in_macro: bool },
+    /// The body of an `unsafe fn`.
+    FnBody(ast::BlockExpr),
+}
+
+fn unsafe_code(sema: &Semantics<'_, RootDatabase>, token: SyntaxToken) -> Option<UnsafeCode> {
+    let offset = token.text_range().start();
+    if let Some(fn_def) = token.parent().and_then(ast::Fn::cast) {
+        return fn_def.body().map(UnsafeCode::FnBody);
+    }
+    if let Some(block) = token.parent().and_then(ast::BlockExpr::cast) {
+        return Some(UnsafeCode::Block { offset, block, in_macro: false });
+    }
+    token.parent_ancestors().find_map(ast::MacroCall::cast)?;
+    let block = sema
+        .descend_into_macros(token)
+        .into_iter()
+        .find_map(|token| token.parent().and_then(ast::BlockExpr::cast))?;
+    Some(UnsafeCode::Block { offset, block, in_macro: true })
+}
+
+/// The ranges of the unsafe operations of an `unsafe fn` body that aren't inside an unsafe block.
+fn unsafe_fn_operations(
+    sema: &Semantics<'_, RootDatabase>,
+    body: &ast::BlockExpr,
+    file_id: FileId,
+) -> Vec<TextRange> {
+    sema.unsafe_operations(&ast::Expr::BlockExpr(body.clone()))
+        .into_iter()
+        .filter(|op| matches!(sema.unsafe_context(&op.expr), Some(UnsafeContext::Function(_))))
+        .filter_map(|op| sema.original_range_opt(op.expr.syntax()))
+        .filter(|it| it.file_id == file_id)
+        .map(|it| it.range)
+        .collect()
+}
+
+/// Adds the edits of `suggestion` to `accepted`, unless they overlap edits already accepted.
+fn accept(accepted: &mut Vec<Indel>, suggestion: UnsafeSuggestion) -> bool {
+    let indels: Vec<_> = suggestion
+        .edits
+        .into_iter()
+        .map(|edit| Indel::replace(edit.range, edit.replacement))
+        .filter(|indel| !accepted.contains(indel))
+        .collect();
+    if indels.iter().any(|indel| accepted.iter().any(|it| conflicts(it, indel))) {
+        return false;
+    }
+    accepted.extend(indels);
+    true
+}
+
+pub(super) fn assist_config() -> AssistConfig {
+    AssistConfig {
+        snippet_cap: None,
+        allowed: None,
+        insert_use: InsertUseConfig {
+            granularity: ImportGranularity::Crate,
+            enforce_granularity: true,
+            prefix_kind: PrefixKind::Plain,
+            group: true,
+            skip_glob_imports: true,
+        },
+        prefer_no_std: false,
+        unsafe_to_safe: UnsafeToSafeConfig::DEFAULT,
+    }
+}
+
+/// The assist config with the settings of `config`, a JSON file with the `rust-analyzer` section
+/// of the workspace settings.
+fn workspace_assist_config(root: &Path, config: &Path) -> anyhow::Result<AssistConfig> {
+    let json = serde_json::from_str(&std::fs::read_to_string(config)?)?;
+    let root = AbsPathBuf::assert(std::env::current_dir()?.join(root));
+    let mut config = Config::new(root, ClientCapabilities::default());
+    config.update(json).map_err(|e| anyhow::format_err!("invalid config: {}", e))?;
+    Ok(config.assist())
+}
+
+/// Whether two indels can't be part of the same `TextEdit`.
+fn conflicts(a: &Indel, b: &Indel) -> bool {
+    if a.delete.is_empty() && b.delete.is_empty() {
+        return false;
+    }
+    let before = |a: &Indel, b: &Indel| {
+        a.delete.end() <= b.delete.start() && a.delete.start() < b.delete.start()
+    };
+    !before(a, b) && !before(b, a)
+}
+
+/// Renders `edit` as a unified diff against `before`.
+fn unified_diff(path: &str, before: &str, edit: &TextEdit) -> String {
+    let lines: Vec<&str> = before.split_inclusive('\n').collect();
+    let mut line_starts = Vec::with_capacity(lines.len() + 1);
+    let mut offset = TextSize::from(0);
+    for line in &lines {
+        line_starts.push(offset);
+        offset += TextSize::of(*line);
+    }
+    line_starts.push(offset);
+    let line_of = |offset: TextSize| {
+        line_starts[..lines.len()].partition_point(|&start| start <= offset).saturating_sub(1)
+    };
+
+    // Each change replaces the lines `first..=last` of `before` with its own text.
+    let mut changes: Vec<(usize, usize, Vec<&Indel>)> = Vec::new();
+    for indel in edit.iter() {
+        let first = line_of(indel.delete.start());
+        let mut last = line_of(indel.delete.end());
+        if !indel.delete.is_empty() && line_starts[last] == indel.delete.end() {
+            last = last.saturating_sub(1).max(first);
+        }
+        match changes.last_mut() {
+            Some((_, prev_last, indels)) if first <= *prev_last => {
+                *prev_last = last.max(*prev_last);
+                indels.push(indel);
+            }
+            _ => changes.push((first, last, vec![indel])),
+        }
+    }
+    let changes: Vec<_> = changes
+        .into_iter()
+        .map(|(first, last, indels)| {
+            let start = line_starts[first];
+            let range = TextRange::new(start, line_starts[last + 1]);
+            let mut text = before[range].to_string();
+            for indel in indels.iter().rev() {
+                let local = Indel::replace(indel.delete - start, indel.insert.clone());
+                local.apply(&mut text);
+            }
+            (first, last, text)
+        })
+        .collect();
+
+    let mut diff = format!("--- {}\n+++ {}\n", path, path);
+    let mut delta = 0isize;
+    let mut i = 0;
+    while i < changes.len() {
+        let mut j = i;
+        while j + 1 < changes.len() && changes[j + 1].0 <= changes[j].1 + 1 + 2 * CONTEXT_LINES {
+            j += 1;
+        }
+        let hunk_first = changes[i].0.saturating_sub(CONTEXT_LINES);
+        let hunk_last = (changes[j].1 + CONTEXT_LINES).min(lines.len().saturating_sub(1));
+
+        let mut body = String::new();
+        let (mut old_count, mut new_count) = (0, 0);
+        let mut line = hunk_first;
+        for (first, last, text) in &changes[i..=j] {
+            for context in &lines[line..*first] {
+                push_line(&mut body, ' ', context);
+            }
+            for removed in &lines[*first..=*last] {
+                push_line(&mut body, '-', removed);
+            }
+            for added in text.split_inclusive('\n') {
+                push_line(&mut body, '+', added);
+                new_count += 1;
+            }
+            old_count += last + 1 - line;
+            new_count += first - line;
+            line = last + 1;
+        }
+        for context in &lines[line..=hunk_last.max(line - 1)] {
+            push_line(&mut body, ' ', context);
+        }
+        let trailing = (hunk_last + 1).saturating_sub(line);
+        old_count += trailing;
+        new_count += trailing;
+
+        let new_first = hunk_first as isize + delta;
+        diff += &format!(
+            "@@ -{},{} +{},{} @@\n{}",
+            hunk_first + 1,
+            old_count,
+            new_first + 1,
+            new_count,
+            body
+        );
+        delta += new_count as isize - old_count as isize;
+        i = j + 1;
+    }
+    diff
+}
+
+fn push_line(buf: &mut String, prefix: char, line: &str) {
+    buf.push(prefix);
+    buf.push_str(line);
+    if !line.ends_with('\n') {
+        buf.push('\n');
+    }
+}
+
+fn format_location(analysis: &Analysis, vfs: &Vfs, frange: FileRange) -> anyhow::Result<String> {
+    let line_index = analysis.file_line_index(frange.file_id)?;
+    let line_col = line_index.line_col(frange.range.start());
+    Ok(format!("{}:{}:{}", vfs.file_path(frange.file_id), line_col.line + 1, line_col.col + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check_diff(before: &str, indels: &[(u32, u32, &str)], expect: Expect) {
+        let mut builder = TextEdit::builder();
+        for &(start, end, insert) in indels {
+            builder.replace(TextRange::new(start.into(), end.into()), insert.to_string());
+        }
+        expect.assert_eq(&unified_diff("lib.rs", before, &builder.finish()));
+    }
+
+    fn indel(start: u32, end: u32) -> Indel {
+        Indel::replace(TextRange::new(start.into(), end.into()), String::new())
+    }
+
+    const TEXT: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+
+    #[test]
+    fn diff_of_one_line() {
+        check_diff(
+            TEXT,
+            &[(12, 13, "G")],
+            expect![[r#"
+            --- lib.rs
+            +++ lib.rs
+            @@ -4,7 +4,7 @@
+             d
+             e
+             f
+            -g
+            +G
+             h
+             i
+             j
+        "#]],
+        );
+    }
+
+    #[test]
+    fn diff_at_start_and_end_of_file() {
+        check_diff(
+            TEXT,
+            &[(0, 1, "A"), (26, 27, "N")],
+            expect![[r#"
+            --- lib.rs
+            +++ lib.rs
+            @@ -1,4 +1,4 @@
+            -a
+            +A
+             b
+             c
+             d
+            @@ -11,4 +11,4 @@
+             k
+             l
+             m
+            -n
+            +N
+        "#]],
+        );
+    }
+
+    #[test]
+    fn diff_of_distant_changes_shifts_later_hunks() {
+        check_diff(
+            TEXT,
+            &[(2, 4, "b1\nb2\nb3\n"), (22, 24, "")],
+            expect![[r#"
+            --- lib.rs
+            +++ lib.rs
+            @@ -1,5 +1,7 @@
+             a
+            -b
+            +b1
+            +b2
+            +b3
+             c
+             d
+             e
+            @@ -9,6 +11,5 @@
+             i
+             j
+             k
+            -l
+             m
+             n
+        "#]],
+        );
+    }
+
+    #[test]
+    fn diff_of_close_changes_shares_a_hunk() {
+        check_diff(
+            TEXT,
+            &[(4, 5, "C"), (12, 14, "")],
+            expect![[r#"
+            --- lib.rs
+            +++ lib.rs
+            @@ -1,10 +1,9 @@
+             a
+             b
+            -c
+            +C
+             d
+             e
+             f
+            -g
+             h
+             i
+             j
+        "#]],
+        );
+    }
+
+    #[test]
+    fn diff_of_insertion() {
+        check_diff(
+            TEXT,
+            &[(6, 6, "x\n")],
+            expect![[r#"
+            --- lib.rs
+            +++ lib.rs
+            @@ -1,7 +1,8 @@
+             a
+             b
+             c
+            -d
+            +x
+            +d
+             e
+             f
+             g
+        "#]],
+        );
+    }
+
+    #[test]
+    fn diff_of_file_without_trailing_newline() {
+        check_diff(
+            "a\nb\nc",
+            &[(4, 5, "C")],
+            expect![[r#"
+            --- lib.rs
+            +++ lib.rs
+            @@ -1,3 +1,3 @@
+             a
+             b
+            -c
+            +C
+        "#]],
+        );
+    }
+
+    #[test]
+    fn overlapping_replacements_conflict() {
+        assert!(conflicts(&indel(0, 4), &indel(2, 6)));
+        assert!(conflicts(&indel(2, 6), &indel(0, 4)));
+        assert!(conflicts(&indel(0, 6), &indel(2, 4)));
+    }
+
+    #[test]
+    fn adjacent_replacements_do_not_conflict() {
+        assert!(!conflicts(&indel(0, 2), &indel(2, 4)));
+        assert!(!conflicts(&indel(2, 4), &indel(0, 2)));
+    }
+
+    #[test]
+    fn insertions_conflict_only_inside_replacements() {
+        assert!(!conflicts(&indel(2, 2), &indel(2, 2)));
+        assert!(!conflicts(&indel(0, 2), &indel(2, 2)));
+        assert!(conflicts(&indel(2, 2), &indel(2, 4)));
+        assert!(conflicts(&indel(3, 3), &indel(2, 4)));
+    }
+}
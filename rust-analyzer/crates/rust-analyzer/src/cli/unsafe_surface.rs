@@ -0,0 +1,69 @@
+//! Prints, for every workspace crate, the safe public functions that reach unsafe code.
+
+use hir::{Crate, Semantics};
+use ide::{Analysis, FileRange};
+use ide_db::base_db::SourceDatabaseExt;
+use itertools::Itertools;
+use vfs::Vfs;
+
+use crate::cli::{
+    flags,
+    load_cargo::{load_workspace_at, LoadCargoConfig},
+};
+
+impl flags::UnsafeSurface {
+    pub fn run(self) -> anyhow::Result<()> {
+        let cargo_config = Default::default();
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro: !self.disable_proc_macros,
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
+        let db = host.raw_database();
+        let analysis = host.analysis();
+        let sema = Semantics::new(db);
+
+        let krates = Crate::all(db).into_iter().filter(|krate| {
+            let file_id = krate.root_file(db);
+            let source_root = db.file_source_root(file_id);
+            !db.source_root(source_root).is_library
+        });
+
+        for krate in krates {
+            let crate_name = krate.display_name(db).as_deref().unwrap_or("unknown").to_string();
+            let root_file = krate.root_file(db);
+            if sema.to_module_def(root_file).is_none() {
+                continue;
+            }
+
+            let paths = analysis.unsafe_reachability(root_file)?;
+            println!("crate {}: {} public functions reach unsafe code", crate_name, paths.len());
+            for it in paths {
+                let entry = &it.path[0];
+                let location = format_location(
+                    &analysis,
+                    &vfs,
+                    FileRange { file_id: entry.file_id, range: entry.focus_or_full_range() },
+                )?;
+                println!("  {} ({})", entry.name, location);
+                println!("    path: {}", it.path.iter().map(|nav| &nav.name).join(" -> "));
+                for call_site in it.call_sites {
+                    println!("    call: {}", format_location(&analysis, &vfs, call_site)?);
+                }
+                for op in it.unsafe_ops {
+                    println!("    unsafe: {}", format_location(&analysis, &vfs, op)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn format_location(analysis: &Analysis, vfs: &Vfs, frange: FileRange) -> anyhow::Result<String> {
+    let line_index = analysis.file_line_index(frange.file_id)?;
+    let line_col = line_index.line_col(frange.range.start());
+    Ok(format!("{}:{}:{}", vfs.file_path(frange.file_id), line_col.line + 1, line_col.col + 1))
+}
@@ -13,7 +13,7 @@ use flycheck::FlycheckConfig;
 use ide::{
     AssistConfig, CallableSnippets, CompletionConfig, DiagnosticsConfig, ExprFillDefaultMode,
     HighlightConfig, HighlightRelatedConfig, HoverConfig, HoverDocFormat, InlayHintsConfig,
-    JoinLinesConfig, Snippet, SnippetScope,
+    JoinLinesConfig, Snippet, SnippetScope, UnsafeToSafeConfig, UnsafeToSafeErrorStyle,
 };
 use ide_db::{
     imports::insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
@@ -305,6 +305,8 @@ config_data! {
         inlayHints_typeHints_hideClosureInitialization: bool       = "false",
         /// Whether to hide inlay type hints for constructors.
         inlayHints_typeHints_hideNamedConstructor: bool            = "false",
+        /// Whether to show inlay hints summarizing the unsafe operations of `unsafe` blocks.
+        inlayHints_unsafeHints_enable: UnsafeHintsDef              = "\"never\"",
 
         /// Join lines merges consecutive declaration and initialization of an assignment.
         joinLines_joinAssignments: bool = "true",
@@ -442,6 +444,24 @@ config_data! {
         /// Whether to insert closing angle brackets when typing an opening angle bracket of a generic argument list.
         typing_autoClosingAngleBrackets_enable: bool = "false",
 
+        /// How the safe code of the unsafe-to-safe assist reports a violated precondition.
+        unsafeToSafe_errorStyle: UnsafeToSafeErrorStyleDef = "\"panic\"",
+        /// Whether to show the measured runtime overhead of the safe version in the hover of `unsafe`.
+        unsafeToSafe_hover_overhead_enable: bool = "true",
+        /// The largest measured runtime overhead of the safe version, in percent, for an
+        /// unsafe-to-safe rule to be suggested.
+        unsafeToSafe_maxOverhead: Option<u32> = "null",
+        /// Unsafe-to-safe rules to disable in single crates, by crate name. For example,
+        /// `{ "my_no_std_crate": ["set_len"] }` keeps a crate without an allocator from being
+        /// suggested `vec!`.
+        unsafeToSafe_rules_crateOverrides: FxHashMap<String, Vec<String>> = "{}",
+        /// Unsafe-to-safe rules that are never suggested, named after the unsafe API they replace,
+        /// like `get_unchecked`.
+        unsafeToSafe_rules_disabled: Vec<String> = "[]",
+        /// Unsafe-to-safe rules to suggest, named after the unsafe API they replace, like
+        /// `get_unchecked`. When `null`, every rule is suggested.
+        unsafeToSafe_rules_enabled: Option<Vec<String>> = "null",
+
         /// Workspace symbol search kind.
         workspace_symbol_search_kind: WorkspaceSymbolSearchKindDef = "\"only_types\"",
         /// Limits the number of items returned from a workspace symbol search (Defaults to 128).
@@ -1139,6 +1159,11 @@ impl Config {
                 ReborrowHintsDef::Never => ide::ReborrowHints::Never,
                 ReborrowHintsDef::Mutable => ide::ReborrowHints::MutableOnly,
             },
+            unsafe_hints: match self.data.inlayHints_unsafeHints_enable {
+                UnsafeHintsDef::Always => ide::UnsafeHints::Always,
+                UnsafeHintsDef::Never => ide::UnsafeHints::Never,
+                UnsafeHintsDef::Summary => ide::UnsafeHints::BlockSummary,
+            },
             binding_mode_hints: self.data.inlayHints_bindingModeHints_enable,
             param_names_for_lifetime_elision_hints: self
                 .data
@@ -1213,6 +1238,26 @@ impl Config {
             allowed: None,
             insert_use: self.insert_use_config(),
             prefer_no_std: self.data.imports_prefer_no_std,
+            unsafe_to_safe: self.unsafe_to_safe(),
+        }
+    }
+
+    fn unsafe_to_safe(&self) -> UnsafeToSafeConfig {
+        let mut crate_disabled_rules: Vec<_> = (self.data.unsafeToSafe_rules_crateOverrides)
+            .iter()
+            .map(|(krate, rules)| (krate.clone(), rules.clone()))
+            .collect();
+        crate_disabled_rules.sort();
+        UnsafeToSafeConfig {
+            enabled_rules: self.data.unsafeToSafe_rules_enabled.clone(),
+            disabled_rules: self.data.unsafeToSafe_rules_disabled.clone(),
+            error_style: match self.data.unsafeToSafe_errorStyle {
+                UnsafeToSafeErrorStyleDef::Panic => UnsafeToSafeErrorStyle::Panic,
+                UnsafeToSafeErrorStyleDef::Expect => UnsafeToSafeErrorStyle::Expect,
+                UnsafeToSafeErrorStyleDef::QuestionMark => UnsafeToSafeErrorStyle::QuestionMark,
+            },
+            max_overhead: self.data.unsafeToSafe_maxOverhead,
+            crate_disabled_rules,
         }
     }
 
@@ -1293,6 +1338,8 @@ impl Config {
                 }
             }),
             keywords: self.data.hover_documentation_keywords_enable,
+            unsafe_to_safe: self.unsafe_to_safe(),
+            unsafe_to_safe_overhead: self.data.unsafeToSafe_hover_overhead_enable,
         }
     }
 
@@ -1472,6 +1519,7 @@ mod de_unit_v {
     named_unit_variant!(skip_trivial);
     named_unit_variant!(mutable);
     named_unit_variant!(with_block);
+    named_unit_variant!(summary);
 }
 
 #[derive(Deserialize, Debug, Clone, Copy)]
@@ -1548,6 +1596,14 @@ enum ExprFillDefaultDef {
     Default,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+enum UnsafeToSafeErrorStyleDef {
+    Panic,
+    Expect,
+    QuestionMark,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 enum ImportGranularityDef {
@@ -1606,6 +1662,17 @@ enum ReborrowHintsDef {
     Mutable,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum UnsafeHintsDef {
+    #[serde(deserialize_with = "true_or_always")]
+    Always,
+    #[serde(deserialize_with = "false_or_never")]
+    Never,
+    #[serde(deserialize_with = "de_unit_v::summary")]
+    Summary,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 enum FilesWatcherDef {
@@ -1794,10 +1861,17 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
+        "FxHashMap<String, Vec<String>>" => set! {
+            "type": "object",
+        },
         "Option<usize>" => set! {
             "type": ["null", "integer"],
             "minimum": 0,
         },
+        "Option<u32>" => set! {
+            "type": ["null", "integer"],
+            "minimum": 0,
+        },
         "Option<String>" => set! {
             "type": ["null", "string"],
         },
@@ -1828,6 +1902,15 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
                 "Fill missing expressions with reasonable defaults, `new` or `default` constructors."
             ],
         },
+        "UnsafeToSafeErrorStyleDef" => set! {
+            "type": "string",
+            "enum": ["panic", "expect", "question_mark"],
+            "enumDescriptions": [
+                "Call `unwrap` on the result of the checked operation.",
+                "Call `expect` on the result of the checked operation, with a message naming the precondition.",
+                "Propagate the failure with `?` where the function returns a compatible type, and call `unwrap` elsewhere."
+            ],
+        },
         "ImportGranularityDef" => set! {
             "type": "string",
             "enum": ["preserve", "crate", "module", "item"],
@@ -1915,6 +1998,19 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
                 "Only show mutable reborrow hints."
             ]
         },
+        "UnsafeHintsDef" => set! {
+            "type": "string",
+            "enum": [
+                "always",
+                "never",
+                "summary"
+            ],
+            "enumDescriptions": [
+                "Summarize the unsafe operations of `unsafe` blocks and mark every unsafe operation.",
+                "Never show unsafe hints.",
+                "Only summarize the unsafe operations of `unsafe` blocks."
+            ]
+        },
         "CargoFeaturesDef" => set! {
             "anyOf": [
                 {
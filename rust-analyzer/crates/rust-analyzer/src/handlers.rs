@@ -802,6 +802,74 @@ pub(crate) fn handle_related_tests(
     Ok(res)
 }
 
+pub(crate) fn handle_unsafe_reachability(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::UnsafeReachabilityParams,
+) -> Result<Vec<lsp_ext::UnsafeReachabilityPath>> {
+    let _p = profile::span("handle_unsafe_reachability");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+
+    let paths = snap.analysis.unsafe_reachability(file_id)?;
+    let mut res = Vec::new();
+    for it in paths {
+        let path = it
+            .path
+            .into_iter()
+            .map(|nav| to_proto::call_hierarchy_item(&snap, nav))
+            .collect::<Result<_>>()?;
+        let call_sites = it
+            .call_sites
+            .into_iter()
+            .map(|frange| to_proto::location(&snap, frange))
+            .collect::<Result<_>>()?;
+        let unsafe_operations = it
+            .unsafe_ops
+            .into_iter()
+            .map(|frange| to_proto::location(&snap, frange))
+            .collect::<Result<_>>()?;
+        res.push(lsp_ext::UnsafeReachabilityPath { path, call_sites, unsafe_operations });
+    }
+
+    Ok(res)
+}
+
+pub(crate) fn handle_unsafe_suggestions(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::UnsafeSuggestionsParams,
+) -> Result<Vec<lsp_ext::UnsafeSuggestion>> {
+    let _p = profile::span("handle_unsafe_suggestions");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let frange = match params.range {
+        Some(range) => from_proto::file_range(&snap, params.text_document, range)?,
+        None => {
+            let text = snap.analysis.file_text(file_id)?;
+            FileRange { file_id, range: TextRange::up_to(TextSize::of(&*text)) }
+        }
+    };
+
+    let suggestions = snap.analysis.unsafe_suggestions(&snap.config.assist(), frange)?;
+    let res = suggestions
+        .into_iter()
+        .map(|it| lsp_ext::UnsafeSuggestion {
+            rule: it.rule.to_string(),
+            unsafe_block: to_proto::range(&line_index, it.unsafe_block),
+            edits: it
+                .edits
+                .into_iter()
+                .map(|edit| lsp_ext::UnsafeSuggestionEdit {
+                    range: to_proto::range(&line_index, edit.range),
+                    original: edit.original,
+                    new_text: edit.replacement,
+                })
+                .collect(),
+            overhead: it.overhead.map(ToString::to_string),
+            explanation: it.explanation.to_string(),
+        })
+        .collect();
+    Ok(res)
+}
+
 pub(crate) fn handle_completion(
     snap: GlobalStateSnapshot,
     params: lsp_types::CompletionParams,
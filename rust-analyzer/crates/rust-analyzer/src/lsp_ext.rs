@@ -244,6 +244,62 @@ pub struct TestInfo {
     pub runnable: Runnable,
 }
 
+pub enum UnsafeReachability {}
+
+impl Request for UnsafeReachability {
+    type Params = UnsafeReachabilityParams;
+    type Result = Vec<UnsafeReachabilityPath>;
+    const METHOD: &'static str = "rust-analyzer/unsafeReachability";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsafeReachabilityParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsafeReachabilityPath {
+    pub path: Vec<lsp_types::CallHierarchyItem>,
+    pub call_sites: Vec<lsp_types::Location>,
+    pub unsafe_operations: Vec<lsp_types::Location>,
+}
+
+pub enum UnsafeSuggestions {}
+
+impl Request for UnsafeSuggestions {
+    type Params = UnsafeSuggestionsParams;
+    type Result = Vec<UnsafeSuggestion>;
+    const METHOD: &'static str = "rust-analyzer/unsafeSuggestions";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsafeSuggestionsParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Option<Range>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsafeSuggestion {
+    pub rule: String,
+    pub unsafe_block: Range,
+    pub edits: Vec<UnsafeSuggestionEdit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overhead: Option<String>,
+    pub explanation: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsafeSuggestionEdit {
+    pub range: Range,
+    pub original: String,
+    pub new_text: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct InlayHintsParams {
@@ -637,6 +637,8 @@ impl GlobalState {
             .on::<lsp_ext::ParentModule>(handlers::handle_parent_module)
             .on::<lsp_ext::Runnables>(handlers::handle_runnables)
             .on::<lsp_ext::RelatedTests>(handlers::handle_related_tests)
+            .on::<lsp_ext::UnsafeReachability>(handlers::handle_unsafe_reachability)
+            .on::<lsp_ext::UnsafeSuggestions>(handlers::handle_unsafe_suggestions)
             .on::<lsp_ext::CodeActionRequest>(handlers::handle_code_action)
             .on::<lsp_ext::CodeActionResolveRequest>(handlers::handle_code_action_resolve)
             .on::<lsp_ext::HoverRequest>(handlers::handle_hover)
@@ -434,6 +434,10 @@ pub(crate) fn inlay_hint(
         InlayKind::ParameterHint if render_colons => inlay_hint.label.append_str(":"),
         InlayKind::TypeHint if render_colons => inlay_hint.label.prepend_str(": "),
         InlayKind::ClosureReturnTypeHint => inlay_hint.label.prepend_str(" -> "),
+        InlayKind::UnsafeBlockHint => {
+            inlay_hint.label.prepend_str("/* ");
+            inlay_hint.label.append_str(" */");
+        }
         _ => {}
     }
 
@@ -442,24 +446,29 @@ pub(crate) fn inlay_hint(
             // before annotated thing
             InlayKind::ParameterHint
             | InlayKind::ImplicitReborrowHint
-            | InlayKind::BindingModeHint => position(line_index, inlay_hint.range.start()),
+            | InlayKind::BindingModeHint
+            | InlayKind::UnsafeOperationHint => position(line_index, inlay_hint.range.start()),
             // after annotated thing
             InlayKind::ClosureReturnTypeHint
             | InlayKind::TypeHint
             | InlayKind::ChainingHint
             | InlayKind::GenericParamListHint
             | InlayKind::LifetimeHint
-            | InlayKind::ClosingBraceHint => position(line_index, inlay_hint.range.end()),
+            | InlayKind::ClosingBraceHint
+            | InlayKind::UnsafeBlockHint => position(line_index, inlay_hint.range.end()),
         },
         padding_left: Some(match inlay_hint.kind {
             InlayKind::TypeHint => !render_colons,
-            InlayKind::ChainingHint | InlayKind::ClosingBraceHint => true,
+            InlayKind::ChainingHint | InlayKind::ClosingBraceHint | InlayKind::UnsafeBlockHint => {
+                true
+            }
             InlayKind::BindingModeHint
             | InlayKind::ClosureReturnTypeHint
             | InlayKind::GenericParamListHint
             | InlayKind::ImplicitReborrowHint
             | InlayKind::LifetimeHint
-            | InlayKind::ParameterHint => false,
+            | InlayKind::ParameterHint
+            | InlayKind::UnsafeOperationHint => false,
         }),
         padding_right: Some(match inlay_hint.kind {
             InlayKind::ChainingHint
@@ -469,7 +478,10 @@ pub(crate) fn inlay_hint(
             | InlayKind::TypeHint
             | InlayKind::ClosingBraceHint => false,
             InlayKind::BindingModeHint => inlay_hint.label.as_simple_str() != Some("&"),
-            InlayKind::ParameterHint | InlayKind::LifetimeHint => true,
+            InlayKind::ParameterHint
+            | InlayKind::LifetimeHint
+            | InlayKind::UnsafeBlockHint
+            | InlayKind::UnsafeOperationHint => true,
         }),
         kind: match inlay_hint.kind {
             InlayKind::ParameterHint => Some(lsp_types::InlayHintKind::PARAMETER),
@@ -480,7 +492,9 @@ pub(crate) fn inlay_hint(
             | InlayKind::GenericParamListHint
             | InlayKind::LifetimeHint
             | InlayKind::ImplicitReborrowHint
-            | InlayKind::ClosingBraceHint => None,
+            | InlayKind::ClosingBraceHint
+            | InlayKind::UnsafeBlockHint
+            | InlayKind::UnsafeOperationHint => None,
         },
         text_edits: None,
         data: (|| match inlay_hint.tooltip {
@@ -37,6 +37,9 @@
 //!     add:
 //!     as_ref: sized
 //!     drop:
+//!     char:
+//!     str:
+//!     ptr:
 
 pub mod marker {
     // region:sized
@@ -413,10 +416,46 @@ pub mod slice {
         pub fn len(&self) -> usize {
             loop {}
         }
+        pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+            loop {}
+        }
     }
 }
 // endregion:slice
 
+// region:char
+pub mod char {
+    pub const unsafe fn from_u32_unchecked(i: u32) -> char {
+        loop {}
+    }
+}
+// endregion:char
+
+// region:str
+pub mod str {
+    pub const unsafe fn from_utf8_unchecked(v: &[u8]) -> &str {
+        loop {}
+    }
+}
+// endregion:str
+
+// region:ptr
+pub mod ptr {
+    pub unsafe fn read<T>(src: *const T) -> T {
+        loop {}
+    }
+    pub unsafe fn copy_nonoverlapping<T>(src: *const T, dst: *mut T, count: usize) {
+        loop {}
+    }
+
+    impl<T> *const T {
+        pub unsafe fn read(self) -> T {
+            loop {}
+        }
+    }
+}
+// endregion:ptr
+
 // region:option
 pub mod option {
     pub enum Option<T> {